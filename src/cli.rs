@@ -1,14 +1,25 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use parquet::basic::{BrotliLevel, Compression, GzipLevel, ZstdLevel};
+use parquet::file::properties::{EnabledStatistics, WriterVersion};
 use std::path::{PathBuf, Path};
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum OutputFormat {
     /// CSV格式
     Csv,
-    /// JSON格式
+    /// JSON格式（对象数组）
     Json,
+    /// NDJSON格式（每行一个JSON对象）
+    Ndjson,
     /// Parquet格式
     Parquet,
+    /// Arrow IPC文件格式（Feather v2）
+    Arrow,
+    /// Arrow IPC流格式，适合写入标准输出
+    ArrowStream,
+    /// Excel工作簿（.xlsx）
+    Xlsx,
 }
 
 impl std::fmt::Display for OutputFormat {
@@ -16,11 +27,62 @@ impl std::fmt::Display for OutputFormat {
         match self {
             OutputFormat::Csv => write!(f, "csv"),
             OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Ndjson => write!(f, "ndjson"),
             OutputFormat::Parquet => write!(f, "parquet"),
+            OutputFormat::Arrow => write!(f, "arrow"),
+            OutputFormat::ArrowStream => write!(f, "arrow-stream"),
+            OutputFormat::Xlsx => write!(f, "xlsx"),
         }
     }
 }
 
+/// JSON数组输出的排版方式
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum JsonStyle {
+    /// 缩进排版，每个字段一行
+    #[default]
+    Pretty,
+    /// 紧凑格式，不含空白字符
+    Compact,
+}
+
+/// Arrow IPC输出的数据压缩方式
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum IpcCompression {
+    /// 不压缩
+    #[default]
+    None,
+    /// LZ4帧压缩，速度快
+    Lz4,
+    /// ZSTD压缩，压缩率高
+    Zstd,
+}
+
+/// 单元格无法转换为--schema声明的列类型时的处理方式，推断类型的列总是写为空值
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum CastErrorMode {
+    /// 报错并停止转换
+    #[default]
+    Strict,
+    /// 写为空值并报告无法转换的单元格
+    Lenient,
+}
+
+/// 姓名、地址等语义化数据的语言区域
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// 简体中文
+    #[default]
+    #[value(alias = "zh-CN", alias = "zh_CN")]
+    #[serde(alias = "zh-CN", alias = "zh_CN")]
+    Zh,
+    /// 英文（美国）
+    #[value(alias = "en-US", alias = "en_US")]
+    #[serde(alias = "en-US", alias = "en_US")]
+    En,
+}
+
 #[derive(Debug, Clone, ValueEnum)]
 pub enum SchemaFormat {
     /// CSV格式的列定义
@@ -29,6 +91,101 @@ pub enum SchemaFormat {
     Json,
 }
 
+/// Parquet列统计信息的级别
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum StatisticsLevel {
+    /// 不写入统计信息
+    None,
+    /// 按列块写入统计信息
+    Chunk,
+    /// 按列块和数据页写入统计信息（支持页索引）
+    Page,
+}
+
+impl From<StatisticsLevel> for EnabledStatistics {
+    fn from(level: StatisticsLevel) -> Self {
+        match level {
+            StatisticsLevel::None => EnabledStatistics::None,
+            StatisticsLevel::Chunk => EnabledStatistics::Chunk,
+            StatisticsLevel::Page => EnabledStatistics::Page,
+        }
+    }
+}
+
+/// Parquet文件格式版本
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ParquetWriterVersion {
+    /// Parquet 1.0，兼容性最好
+    #[value(name = "1.0")]
+    V1,
+    /// Parquet 2.0，使用DataPageV2和更高效的编码
+    #[value(name = "2.0")]
+    V2,
+}
+
+impl From<ParquetWriterVersion> for WriterVersion {
+    fn from(version: ParquetWriterVersion) -> Self {
+        match version {
+            ParquetWriterVersion::V1 => WriterVersion::PARQUET_1_0,
+            ParquetWriterVersion::V2 => WriterVersion::PARQUET_2_0,
+        }
+    }
+}
+
+/// CSV和Excel输入的列选择选项，在构建Arrow schema之前应用，未选择的列不会被解析
+#[derive(Args, Debug, Clone)]
+#[command(next_help_heading = "列选择选项")]
+pub struct ColumnArgs {
+    /// 按顺序输出的列，以逗号分隔，可以是列名、列序号（从1开始）或序号范围，例如 name,3,5-8,10-
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    pub select: Vec<String>,
+    
+    /// 不输出的列，以逗号分隔，格式与--select相同
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    pub exclude: Vec<String>,
+    
+    /// 重命名输出的列，格式为 原列名:新列名，多个以逗号分隔
+    #[arg(long, value_name = "OLD:NEW", value_delimiter = ',', value_parser = parse_rename)]
+    pub rename: Vec<(String, String)>,
+}
+
+/// 输出为Parquet格式时的写入选项
+#[derive(Args, Debug, Clone)]
+#[command(next_help_heading = "Parquet输出选项")]
+pub struct ParquetArgs {
+    /// 压缩算法：none、snappy、gzip、lz4、zstd、brotli，gzip、zstd和brotli可用冒号指定级别，例如zstd:9，默认不压缩
+    #[arg(long, value_name = "CODEC", value_parser = parse_compression)]
+    pub compression: Option<Compression>,
+    
+    /// 每个行组的最大行数，默认每个批次写为一个行组
+    #[arg(long, value_name = "ROWS")]
+    pub row_group_size: Option<usize>,
+    
+    /// 启用字典编码
+    #[arg(long, overrides_with = "no_dictionary")]
+    pub dictionary: bool,
+    
+    /// 禁用字典编码。--dictionary和--no-dictionary都未指定时启用字典编码
+    #[arg(long, overrides_with = "dictionary")]
+    pub no_dictionary: bool,
+    
+    /// 列统计信息的级别，默认为page
+    #[arg(long, value_enum)]
+    pub statistics: Option<StatisticsLevel>,
+    
+    /// 数据页的大致最大字节数，支持K、M、G后缀，默认为1M
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub data_page_size: Option<u64>,
+    
+    /// Parquet文件格式版本，默认为1.0
+    #[arg(long, value_enum)]
+    pub writer_version: Option<ParquetWriterVersion>,
+    
+    /// JSON格式的Parquet写入配置文件，可以按列指定压缩、字典编码和统计信息，命令行选项优先
+    #[arg(long, value_name = "CONFIG_FILE")]
+    pub parquet_config: Option<PathBuf>,
+}
+
 #[derive(Debug, Clone, ValueEnum)]
 pub enum DiffOutputMode {
     /// 并集：两个文件中所有的字段
@@ -82,6 +239,80 @@ pub fn parse_delimiter(s: &str) -> Result<char, String> {
     }
 }
 
+/// 解析列重命名，格式为 原列名:新列名
+pub fn parse_rename(s: &str) -> Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((old, new)) if !old.is_empty() && !new.is_empty() => Ok((old.to_string(), new.to_string())),
+        _ => Err(format!("无效的列重命名: {}，格式为 原列名:新列名", s)),
+    }
+}
+
+/// 解析批处理大小，必须为正整数
+pub fn parse_batch_size(s: &str) -> Result<usize, String> {
+    match s.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("无效的批处理大小: {}，必须为大于0的整数", s)),
+    }
+}
+
+/// 解析文件大小，支持K、M、G后缀（以1024为单位），例如 100M、1.5G
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let upper = s.to_uppercase();
+    let upper = upper.strip_suffix("IB").or_else(|| upper.strip_suffix('B')).unwrap_or(&upper);
+    
+    let (number, multiplier) = match upper.chars().last() {
+        Some('K') => (&upper[..upper.len() - 1], 1024u64),
+        Some('M') => (&upper[..upper.len() - 1], 1024 * 1024),
+        Some('G') => (&upper[..upper.len() - 1], 1024 * 1024 * 1024),
+        _ => (upper, 1),
+    };
+    
+    match number.trim().parse::<f64>() {
+        Ok(n) if n > 0.0 => Ok((n * multiplier as f64) as u64),
+        _ => Err(format!("无效的文件大小: {}，示例：1048576、512K、100M、1G", s)),
+    }
+}
+
+/// 解析Parquet压缩算法，支持用冒号指定压缩级别，例如 zstd:9、gzip:6
+pub fn parse_compression(s: &str) -> Result<Compression, String> {
+    let s = s.trim().to_lowercase();
+    let (codec, level) = match s.split_once(':') {
+        Some((codec, level)) => {
+            let level = level.trim().parse::<u32>()
+                .map_err(|_| format!("无效的压缩级别: {}", level))?;
+            (codec.trim(), Some(level))
+        }
+        None => (s.as_str(), None),
+    };
+    
+    let invalid_level = |e: parquet::errors::ParquetError| format!("{}的压缩级别无效: {}", codec, e);
+    let compression = match (codec, level) {
+        ("none" | "uncompressed", None) => Compression::UNCOMPRESSED,
+        ("snappy", None) => Compression::SNAPPY,
+        // LZ4_RAW是Parquet规范推荐的LZ4编码，旧的LZ4编码存在兼容性问题
+        ("lz4", None) => Compression::LZ4_RAW,
+        ("gzip", level) => Compression::GZIP(match level {
+            Some(level) => GzipLevel::try_new(level).map_err(invalid_level)?,
+            None => GzipLevel::default(),
+        }),
+        ("zstd", level) => Compression::ZSTD(match level {
+            Some(level) => ZstdLevel::try_new(level as i32).map_err(invalid_level)?,
+            None => ZstdLevel::default(),
+        }),
+        ("brotli", level) => Compression::BROTLI(match level {
+            Some(level) => BrotliLevel::try_new(level).map_err(invalid_level)?,
+            None => BrotliLevel::default(),
+        }),
+        ("none" | "uncompressed" | "snappy" | "lz4", Some(_)) => {
+            return Err(format!("{}不支持指定压缩级别", codec));
+        }
+        _ => return Err(format!("不支持的压缩算法: {}，可选none、snappy、gzip、lz4、zstd、brotli", codec)),
+    };
+    
+    Ok(compression)
+}
+
 // 从文件扩展名推断输出格式
 pub fn guess_format_from_extension(path: &Path) -> Option<OutputFormat> {
     path.extension()
@@ -90,7 +321,11 @@ pub fn guess_format_from_extension(path: &Path) -> Option<OutputFormat> {
             match ext.as_str() {
                 "csv" => Some(OutputFormat::Csv),
                 "json" => Some(OutputFormat::Json),
+                "jsonl" | "ndjson" => Some(OutputFormat::Ndjson),
                 "parquet" => Some(OutputFormat::Parquet),
+                "arrow" | "feather" | "ipc" => Some(OutputFormat::Arrow),
+                "arrows" => Some(OutputFormat::ArrowStream),
+                "xlsx" => Some(OutputFormat::Xlsx),
                 _ => None,
             }
         })
@@ -100,26 +335,50 @@ pub fn guess_format_from_extension(path: &Path) -> Option<OutputFormat> {
 pub enum Commands {
     /// 转换Excel文件
     Excel {
-        /// 输入Excel文件路径（支持.xlsx、.xls、.xlsm格式）
+        /// 输入Excel文件路径（支持.xlsx、.xlsm、.xlsb、.xls、.ods格式，按文件内容识别），使用-表示从标准输入读取
         #[arg(short, long, value_name = "EXCEL_FILE")]
         input: PathBuf,
         
-        /// 输出文件路径（如果不指定--format，将从文件扩展名推断输出格式）
-        #[arg(short, long, value_name = "OUTPUT_FILE")]
-        output: PathBuf,
+        /// 输出文件路径（如果不指定--format，将从文件扩展名推断输出格式），使用-表示写入标准输出，此时必须指定--format
+        #[arg(short, long, value_name = "OUTPUT_FILE", required_unless_present = "list_sheets")]
+        output: Option<PathBuf>,
+        
+        /// 要转换的工作表名称或序号（从1开始），默认为第一个工作表
+        #[arg(long, value_name = "NAME|INDEX", conflicts_with = "all_sheets")]
+        sheet: Option<String>,
         
-        /// 输出格式（csv、json或parquet），如不指定则从输出文件扩展名推断
+        /// 转换所有工作表，每个工作表输出一个文件，文件名后追加工作表名
+        #[arg(long)]
+        all_sheets: bool,
+        
+        /// 列出所有工作表的名称、范围和行列数后退出
+        #[arg(long)]
+        list_sheets: bool,
+        
+        /// 输出格式（csv、json、ndjson、parquet、arrow、arrow-stream或xlsx），如不指定则从输出文件扩展名推断
         #[arg(short, long, value_enum)]
         format: Option<OutputFormat>,
         
         /// 批处理大小，指定一次处理的行数（较大的值可能提高性能但增加内存使用）
-        #[arg(short, long, default_value = "10000")]
+        #[arg(short, long, default_value = "10000", value_parser = parse_batch_size)]
         batch_size: usize,
         
         /// CSV分隔符（当输出为CSV时使用），支持特殊字符如\t表示制表符
         #[arg(short, long, default_value = ",", value_parser = parse_delimiter)]
         delimiter: char,
         
+        /// 输出为JSON时的排版方式（pretty或compact），NDJSON始终每行一个紧凑的对象
+        #[arg(long, value_enum, default_value = "pretty")]
+        json_style: JsonStyle,
+        
+        /// 输出为Arrow IPC格式时的数据压缩方式（none、lz4或zstd）
+        #[arg(long, value_enum, default_value = "none")]
+        ipc_compression: IpcCompression,
+        
+        /// 输出为Excel时每个批次写入一个工作表，默认所有数据写入同一个工作表（超过行数上限时自动续写到新工作表）
+        #[arg(long)]
+        sheet_per_batch: bool,
+        
         /// 使用的线程数，默认为CPU核心数
         #[arg(short, long)]
         threads: Option<usize>,
@@ -127,30 +386,84 @@ pub enum Commands {
         /// 跳过前几行（例如标题行）
         #[arg(long, default_value = "0")]
         skip_rows: usize,
+        
+        /// 用于推断列类型的采样行数
+        #[arg(long, default_value = "1000")]
+        infer_rows: usize,
+        
+        /// 不推断列类型，除--schema中声明的列外都按字符串处理
+        #[arg(long)]
+        no_infer: bool,
+        
+        /// 列类型定义文件（CSV或JSON格式，与data-gen的列定义相同），按列名把对应列转换为声明的类型，未声明的列仍自动推断
+        #[arg(long, value_name = "SCHEMA_FILE")]
+        schema: Option<PathBuf>,
+        
+        /// 列类型定义文件格式（csv或json），默认按扩展名判断，CSV格式的定义文件使用--delimiter指定的分隔符
+        #[arg(long, value_enum, requires = "schema")]
+        schema_format: Option<SchemaFormat>,
+        
+        /// 单元格无法转换为--schema声明的列类型时的处理方式：strict报错停止，lenient写为空值并报告
+        #[arg(long, value_enum, default_value = "strict")]
+        on_cast_error: CastErrorMode,
+        
+        /// 把所有无法转换的单元格（行号、列名、值和类型）写入CSV报告文件
+        #[arg(long, value_name = "REPORT_FILE")]
+        cast_report: Option<PathBuf>,
+        
+        /// 按行数把输出拆分为多个文件（name_part0001.ext等），默认输出为单个文件
+        #[arg(long, value_name = "ROWS")]
+        split_rows: Option<usize>,
+        
+        /// 按大小把输出拆分为多个文件，支持K、M、G后缀，例如100M
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        split_size: Option<u64>,
+        
+        /// 行过滤条件，只输出满足条件的行，例如 "status == 'active' && amount > 100"，列名为选择和重命名之后的列名
+        #[arg(long = "where", value_name = "EXPR")]
+        filter: Option<String>,
+        
+        #[command(flatten)]
+        columns: ColumnArgs,
+        
+        #[command(flatten)]
+        parquet: ParquetArgs,
     },
     
     /// 转换CSV文件
     Csv {
-        /// 输入CSV文件路径
+        /// 输入CSV文件路径，使用-表示从标准输入读取
         #[arg(short, long, value_name = "CSV_FILE")]
         input: PathBuf,
         
-        /// 输出文件路径（如果不指定--format，将从文件扩展名推断输出格式）
+        /// 输出文件路径（如果不指定--format，将从文件扩展名推断输出格式），使用-表示写入标准输出，此时必须指定--format
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: PathBuf,
         
-        /// 输出格式（csv、json或parquet），如不指定则从输出文件扩展名推断
+        /// 输出格式（csv、json、ndjson、parquet、arrow、arrow-stream或xlsx），如不指定则从输出文件扩展名推断
         #[arg(short, long, value_enum)]
         format: Option<OutputFormat>,
         
         /// 批处理大小，指定一次处理的行数（较大的值可能提高性能但增加内存使用）
-        #[arg(short, long, default_value = "10000")]
+        #[arg(short, long, default_value = "10000", value_parser = parse_batch_size)]
         batch_size: usize,
         
         /// CSV分隔符，支持特殊字符如\t表示制表符
         #[arg(short, long, default_value = ",", value_parser = parse_delimiter)]
         delimiter: char,
         
+        /// 输出为JSON时的排版方式（pretty或compact），NDJSON始终每行一个紧凑的对象
+        #[arg(long, value_enum, default_value = "pretty")]
+        json_style: JsonStyle,
+        
+        /// 输出为Arrow IPC格式时的数据压缩方式（none、lz4或zstd）
+        #[arg(long, value_enum, default_value = "none")]
+        ipc_compression: IpcCompression,
+        
+        /// 输出为Excel时每个批次写入一个工作表，默认所有数据写入同一个工作表（超过行数上限时自动续写到新工作表）
+        #[arg(long)]
+        sheet_per_batch: bool,
+        
         /// 使用的线程数，默认为CPU核心数
         #[arg(short, long)]
         threads: Option<usize>,
@@ -158,11 +471,199 @@ pub enum Commands {
         /// CSV是否有标题行
         #[arg(long, default_value = "true")]
         has_header: bool,
+        
+        /// 用于推断列类型的采样行数
+        #[arg(long, default_value = "1000")]
+        infer_rows: usize,
+        
+        /// 不推断列类型，除--schema中声明的列外都按字符串处理
+        #[arg(long)]
+        no_infer: bool,
+        
+        /// 列类型定义文件（CSV或JSON格式，与data-gen的列定义相同），按列名把对应列转换为声明的类型，未声明的列仍自动推断
+        #[arg(long, value_name = "SCHEMA_FILE")]
+        schema: Option<PathBuf>,
+        
+        /// 列类型定义文件格式（csv或json），默认按扩展名判断，CSV格式的定义文件使用--delimiter指定的分隔符
+        #[arg(long, value_enum, requires = "schema")]
+        schema_format: Option<SchemaFormat>,
+        
+        /// 单元格无法转换为--schema声明的列类型时的处理方式：strict报错停止，lenient写为空值并报告
+        #[arg(long, value_enum, default_value = "strict")]
+        on_cast_error: CastErrorMode,
+        
+        /// 把所有无法转换的单元格（行号、列名、值和类型）写入CSV报告文件
+        #[arg(long, value_name = "REPORT_FILE")]
+        cast_report: Option<PathBuf>,
+        
+        /// 按行数把输出拆分为多个文件（name_part0001.ext等），默认输出为单个文件
+        #[arg(long, value_name = "ROWS")]
+        split_rows: Option<usize>,
+        
+        /// 按大小把输出拆分为多个文件，支持K、M、G后缀，例如100M
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        split_size: Option<u64>,
+        
+        /// 行过滤条件，只输出满足条件的行，例如 "status == 'active' && amount > 100"，列名为选择和重命名之后的列名
+        #[arg(long = "where", value_name = "EXPR")]
+        filter: Option<String>,
+        
+        #[command(flatten)]
+        columns: ColumnArgs,
+        
+        #[command(flatten)]
+        parquet: ParquetArgs,
+    },
+    
+    /// 转换Parquet文件
+    Parquet {
+        /// 输入Parquet文件路径
+        #[arg(short, long, value_name = "PARQUET_FILE")]
+        input: PathBuf,
+        
+        /// 输出文件路径（如果不指定--format，将从文件扩展名推断输出格式），使用-表示写入标准输出，此时必须指定--format
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: PathBuf,
+        
+        /// 输出格式（csv、json、ndjson、parquet、arrow、arrow-stream或xlsx），如不指定则从输出文件扩展名推断
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+        
+        /// 批处理大小，指定一次读取的行数（较大的值可能提高性能但增加内存使用）
+        #[arg(short, long, default_value = "10000", value_parser = parse_batch_size)]
+        batch_size: usize,
+        
+        /// CSV分隔符（当输出为CSV时使用），支持特殊字符如\t表示制表符
+        #[arg(short, long, default_value = ",", value_parser = parse_delimiter)]
+        delimiter: char,
+        
+        /// 输出为JSON时的排版方式（pretty或compact），NDJSON始终每行一个紧凑的对象
+        #[arg(long, value_enum, default_value = "pretty")]
+        json_style: JsonStyle,
+        
+        /// 输出为Arrow IPC格式时的数据压缩方式（none、lz4或zstd）
+        #[arg(long, value_enum, default_value = "none")]
+        ipc_compression: IpcCompression,
+        
+        /// 输出为Excel时每个批次写入一个工作表，默认所有数据写入同一个工作表（超过行数上限时自动续写到新工作表）
+        #[arg(long)]
+        sheet_per_batch: bool,
+        
+        /// 按行数把输出拆分为多个文件（name_part0001.ext等），默认输出为单个文件
+        #[arg(long, value_name = "ROWS")]
+        split_rows: Option<usize>,
+        
+        /// 按大小把输出拆分为多个文件，支持K、M、G后缀，例如100M
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        split_size: Option<u64>,
+        
+        /// 行过滤条件，只输出满足条件的行，例如 "status == 'active' && amount > 100"
+        #[arg(long = "where", value_name = "EXPR")]
+        filter: Option<String>,
+        
+        #[command(flatten)]
+        parquet: ParquetArgs,
+    },
+    
+    /// 转换JSON或NDJSON文件
+    Json {
+        /// 输入JSON文件路径（顶层为对象数组，或每行一个对象的NDJSON），使用-表示从标准输入读取
+        #[arg(short, long, value_name = "JSON_FILE")]
+        input: PathBuf,
+        
+        /// 输出文件路径（如果不指定--format，将从文件扩展名推断输出格式），使用-表示写入标准输出，此时必须指定--format
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: PathBuf,
+        
+        /// 输出格式（csv、json、ndjson、parquet、arrow、arrow-stream或xlsx），如不指定则从输出文件扩展名推断
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+        
+        /// 批处理大小，指定一次处理的行数（较大的值可能提高性能但增加内存使用）
+        #[arg(short, long, default_value = "10000", value_parser = parse_batch_size)]
+        batch_size: usize,
+        
+        /// CSV分隔符（当输出为CSV时使用），支持特殊字符如\t表示制表符
+        #[arg(short, long, default_value = ",", value_parser = parse_delimiter)]
+        delimiter: char,
+        
+        /// 输出为JSON时的排版方式（pretty或compact），NDJSON始终每行一个紧凑的对象
+        #[arg(long, value_enum, default_value = "pretty")]
+        json_style: JsonStyle,
+        
+        /// 输出为Arrow IPC格式时的数据压缩方式（none、lz4或zstd）
+        #[arg(long, value_enum, default_value = "none")]
+        ipc_compression: IpcCompression,
+        
+        /// 输出为Excel时每个批次写入一个工作表，默认所有数据写入同一个工作表（超过行数上限时自动续写到新工作表）
+        #[arg(long)]
+        sheet_per_batch: bool,
+        
+        /// 按行数把输出拆分为多个文件（name_part0001.ext等），默认输出为单个文件
+        #[arg(long, value_name = "ROWS")]
+        split_rows: Option<usize>,
+        
+        /// 按大小把输出拆分为多个文件，支持K、M、G后缀，例如100M
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        split_size: Option<u64>,
+        
+        /// 行过滤条件，只输出满足条件的行，例如 "status == 'active' && amount > 100"
+        #[arg(long = "where", value_name = "EXPR")]
+        filter: Option<String>,
+        
+        #[command(flatten)]
+        parquet: ParquetArgs,
+    },
+    
+    /// 转换Arrow IPC文件（.arrow、.feather）或IPC流
+    Arrow {
+        /// 输入Arrow IPC文件路径，文件格式和流格式按文件头自动识别，使用-表示从标准输入读取
+        #[arg(short, long, value_name = "ARROW_FILE")]
+        input: PathBuf,
+        
+        /// 输出文件路径（如果不指定--format，将从文件扩展名推断输出格式），使用-表示写入标准输出，此时必须指定--format
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: PathBuf,
+        
+        /// 输出格式（csv、json、ndjson、parquet、arrow、arrow-stream或xlsx），如不指定则从输出文件扩展名推断
+        #[arg(short, long, value_enum)]
+        format: Option<OutputFormat>,
+        
+        /// CSV分隔符（当输出为CSV时使用），支持特殊字符如\t表示制表符
+        #[arg(short, long, default_value = ",", value_parser = parse_delimiter)]
+        delimiter: char,
+        
+        /// 输出为JSON时的排版方式（pretty或compact），NDJSON始终每行一个紧凑的对象
+        #[arg(long, value_enum, default_value = "pretty")]
+        json_style: JsonStyle,
+        
+        /// 输出为Arrow IPC格式时的数据压缩方式（none、lz4或zstd）
+        #[arg(long, value_enum, default_value = "none")]
+        ipc_compression: IpcCompression,
+        
+        /// 输出为Excel时每个批次写入一个工作表，默认所有数据写入同一个工作表（超过行数上限时自动续写到新工作表）
+        #[arg(long)]
+        sheet_per_batch: bool,
+        
+        /// 按行数把输出拆分为多个文件（name_part0001.ext等），默认输出为单个文件
+        #[arg(long, value_name = "ROWS")]
+        split_rows: Option<usize>,
+        
+        /// 按大小把输出拆分为多个文件，支持K、M、G后缀，例如100M
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        split_size: Option<u64>,
+        
+        /// 行过滤条件，只输出满足条件的行，例如 "status == 'active' && amount > 100"
+        #[arg(long = "where", value_name = "EXPR")]
+        filter: Option<String>,
+        
+        #[command(flatten)]
+        parquet: ParquetArgs,
     },
     
     /// 生成随机数据
     DataGen {
-        /// 列定义文件路径（CSV或JSON格式）
+        /// 列定义文件路径（CSV或JSON格式），使用-表示从标准输入读取
         #[arg(short, long, value_name = "SCHEMA_FILE")]
         schema: PathBuf,
         
@@ -170,11 +671,11 @@ pub enum Commands {
         #[arg(short = 'm', long, value_enum)]
         schema_format: SchemaFormat,
         
-        /// 输出文件路径（如果不指定--format，将从文件扩展名推断输出格式）
+        /// 输出文件路径（如果不指定--format，将从文件扩展名推断输出格式），使用-表示写入标准输出，此时必须指定--format
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: PathBuf,
         
-        /// 输出格式（csv、json或parquet），如不指定则从输出文件扩展名推断
+        /// 输出格式（csv、json、ndjson、parquet、arrow、arrow-stream或xlsx），如不指定则从输出文件扩展名推断
         #[arg(short, long, value_enum)]
         format: Option<OutputFormat>,
         
@@ -182,26 +683,53 @@ pub enum Commands {
         #[arg(short, long, default_value = "1000")]
         rows: usize,
         
+        /// 批处理大小，每次生成并写出的行数（较大的值可能提高性能但增加内存使用），相同种子下不同的批处理大小生成的数据不同
+        #[arg(short, long, default_value = "10000", value_parser = parse_batch_size)]
+        batch_size: usize,
+        
+        /// 使用的线程数，默认为CPU核心数，线程数不影响生成的数据
+        #[arg(short, long)]
+        threads: Option<usize>,
+        
         /// CSV分隔符（当输入或输出为CSV时使用），支持特殊字符如\t表示制表符
         #[arg(short, long, default_value = ",", value_parser = parse_delimiter)]
         delimiter: char,
         
-        /// 随机数据种子，用于生成可重复的随机数据，默认为当前时间
+        /// 输出为JSON时的排版方式（pretty或compact），NDJSON始终每行一个紧凑的对象
+        #[arg(long, value_enum, default_value = "pretty")]
+        json_style: JsonStyle,
+        
+        /// 输出为Arrow IPC格式时的数据压缩方式（none、lz4或zstd）
+        #[arg(long, value_enum, default_value = "none")]
+        ipc_compression: IpcCompression,
+        
+        /// 输出为Excel时每个批次写入一个工作表，默认所有数据写入同一个工作表（超过行数上限时自动续写到新工作表）
+        #[arg(long)]
+        sheet_per_batch: bool,
+        
+        /// 随机数据种子，相同的种子和批处理大小总是生成相同的数据，默认为当前时间
         #[arg(long)]
         seed: Option<u64>,
+        
+        /// 姓名、地址等语义化类型默认的语言区域（zh或en），可以在列定义中用locale单独指定
+        #[arg(long, value_enum, default_value = "zh")]
+        locale: Locale,
+        
+        #[command(flatten)]
+        parquet: ParquetArgs,
     },
     
     /// 比较两个文件的字段差异
     Diff {
-        /// 输入文件1路径
+        /// 输入文件1路径，使用-表示从标准输入读取（两个输入中只能有一个）
         #[arg(short = '1', long, value_name = "FILE1")]
         input1: PathBuf,
         
-        /// 输入文件2路径
+        /// 输入文件2路径，使用-表示从标准输入读取（两个输入中只能有一个）
         #[arg(short = '2', long, value_name = "FILE2")]
         input2: PathBuf,
         
-        /// 输出文件路径
+        /// 输出文件路径，使用-表示写入标准输出
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: PathBuf,
         
@@ -213,7 +741,7 @@ pub enum Commands {
         #[arg(short = 'd', long, default_value = ",", value_parser = parse_delimiter)]
         delimiter: char,
         
-        /// 输出详细的差异报告到指定文件
+        /// 输出详细的差异报告到指定文件，使用-表示写入标准输出
         #[arg(short = 'r', long, value_name = "REPORT_FILE")]
         report: Option<PathBuf>,
         
@@ -228,5 +756,53 @@ pub enum Commands {
         /// 将每行作为一个单独的字段读取（适用于每个字段占一行的文件）
         #[arg(short = 'l', long)]
         one_field_per_line: bool,
+        
+        /// 按键列匹配并比较两个数据文件的记录（而不是比较字段名），多个键列以逗号分隔
+        #[arg(short = 'k', long, value_name = "COLUMNS", value_delimiter = ',')]
+        key_columns: Vec<String>,
+        
+        /// 记录比较时忽略的列，以逗号分隔
+        #[arg(long, value_name = "COLUMNS", value_delimiter = ',', requires = "key_columns")]
+        ignore_columns: Vec<String>,
+        
+        /// 记录比较时带小数部分或指数的数值的容差，整数按文本精确比较
+        #[arg(long, default_value = "0.0001")]
+        tolerance: f64,
+        
+        /// 记录比较结果的输出格式（csv、json、ndjson、parquet、arrow、arrow-stream或xlsx），如不指定则从输出文件扩展名推断
+        #[arg(short, long, value_enum, requires = "key_columns")]
+        format: Option<OutputFormat>,
+    },
+    
+    /// 列定义相关的工具
+    Schema {
+        #[command(subcommand)]
+        command: SchemaCommands,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SchemaCommands {
+    /// 从数据文件推断data-gen可以使用的JSON列定义，包括类型、取值范围、空值比例等统计信息
+    Infer {
+        /// 输入文件路径（CSV、Excel、JSON、NDJSON、Parquet或Arrow IPC），使用-表示从标准输入读取
+        #[arg(short, long, value_name = "INPUT_FILE")]
+        input: PathBuf,
+        
+        /// 输出的JSON列定义文件路径，默认写入标准输出
+        #[arg(short, long, value_name = "OUTPUT_FILE", default_value = "-")]
+        output: PathBuf,
+        
+        /// CSV分隔符，支持特殊字符如\t表示制表符
+        #[arg(short, long, default_value = ",", value_parser = parse_delimiter)]
+        delimiter: char,
+        
+        /// CSV和Excel推断列类型时采样的行数
+        #[arg(long, default_value = "1000")]
+        infer_rows: usize,
+        
+        /// 不同值不超过该数量且每个值至少出现5次的列输出为带权重的enum（包含输入中的真实值），为0时不输出enum
+        #[arg(long, default_value = "10")]
+        max_enum: usize,
     },
 } 
\ No newline at end of file
@@ -0,0 +1,189 @@
+use crate::cli::ColumnArgs;
+use crate::error::{Result, TransmutaError};
+use std::collections::HashSet;
+
+/// --select、--exclude和--rename指定的输出列，只用于CSV和Excel输入
+#[derive(Debug, Clone, Default)]
+pub struct ColumnSelection {
+    /// 按顺序输出的列，为空时输出所有列
+    pub select: Vec<String>,
+    /// 不输出的列
+    pub exclude: Vec<String>,
+    /// 原列名和新列名
+    pub rename: Vec<(String, String)>,
+}
+
+impl From<ColumnArgs> for ColumnSelection {
+    fn from(args: ColumnArgs) -> Self {
+        ColumnSelection {
+            select: args.select,
+            exclude: args.exclude,
+            rename: args.rename,
+        }
+    }
+}
+
+/// 按输入标题解析出的输出列
+#[derive(Debug, Clone)]
+pub struct ColumnProjection {
+    /// 每个输出列在输入行中的位置
+    pub indices: Vec<usize>,
+    /// 每个输出列的列名
+    pub names: Vec<String>,
+}
+
+impl ColumnSelection {
+    /// 按输入的标题确定输出哪些列、以什么顺序和名称输出
+    pub fn resolve(&self, headers: &[String]) -> Result<ColumnProjection> {
+        let mut indices: Vec<usize> = if self.select.is_empty() {
+            (0..headers.len()).collect()
+        } else {
+            let mut indices = Vec::new();
+            for item in &self.select {
+                indices.extend(resolve_columns(headers, item)?);
+            }
+            indices
+        };
+
+        let mut seen = HashSet::new();
+        if let Some(&index) = indices.iter().find(|&&index| !seen.insert(index)) {
+            return Err(TransmutaError::InvalidArgument(format!("列'{}'被--select选择了多次", headers[index])));
+        }
+
+        let mut excluded = HashSet::new();
+        for item in &self.exclude {
+            excluded.extend(resolve_columns(headers, item)?);
+        }
+        indices.retain(|index| !excluded.contains(index));
+        if indices.is_empty() {
+            return Err(TransmutaError::InvalidArgument("--select和--exclude之后没有剩余的列".to_string()));
+        }
+
+        let mut names: Vec<String> = indices.iter().map(|&index| headers[index].clone()).collect();
+        for (old, new) in &self.rename {
+            let index = find_column(headers, old)?;
+            let position = indices.iter().position(|&i| i == index).ok_or_else(|| TransmutaError::InvalidArgument(
+                format!("--rename中的列'{}'不在输出的列中", old)
+            ))?;
+            names[position] = new.clone();
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(name) = names.iter().find(|name| !seen.insert(name.as_str())) {
+            return Err(TransmutaError::InvalidArgument(format!("输出中有多个名为'{}'的列", name)));
+        }
+
+        Ok(ColumnProjection { indices, names })
+    }
+}
+
+/// 按列名查找列的位置
+fn find_column(headers: &[String], name: &str) -> Result<usize> {
+    headers.iter().position(|header| header == name).ok_or_else(|| TransmutaError::InvalidArgument(format!(
+        "列'{}'在输入中不存在，输入的列: {}", name, headers.join(", ")
+    )))
+}
+
+/// 解析列名、列序号（从1开始）或序号范围，例如 name、3、1-5、8-（第8列到最后一列），同名的列优先
+fn resolve_columns(headers: &[String], item: &str) -> Result<Vec<usize>> {
+    if let Some(index) = headers.iter().position(|header| header == item) {
+        return Ok(vec![index]);
+    }
+
+    let parse_index = |s: &str| -> Option<usize> {
+        match s.trim().parse::<usize>() {
+            Ok(index) if index >= 1 => Some(index),
+            _ => None,
+        }
+    };
+    let range = match item.split_once('-') {
+        Some((start, "")) => parse_index(start).map(|start| (start, headers.len())),
+        Some((start, end)) => parse_index(start).zip(parse_index(end)),
+        None => parse_index(item).map(|index| (index, index)),
+    };
+
+    match range {
+        Some((start, end)) if start.max(end) > headers.len() => Err(TransmutaError::InvalidArgument(format!(
+            "列序号'{}'超出范围，输入共有{}列", item, headers.len()
+        ))),
+        Some((start, end)) if start > end => Err(TransmutaError::InvalidArgument(format!(
+            "列序号范围'{}'的起始序号大于结束序号", item
+        ))),
+        Some((start, end)) => Ok((start - 1..end).collect()),
+        None => find_column(headers, item).map(|index| vec![index]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 第四列的列名是"1"，用于检查同名的列优先于序号
+    fn headers() -> Vec<String> {
+        ["id", "name", "age", "1", "city"].iter().map(|s| s.to_string()).collect()
+    }
+
+    fn selection(select: &[&str], exclude: &[&str], rename: &[(&str, &str)]) -> ColumnSelection {
+        ColumnSelection {
+            select: select.iter().map(|s| s.to_string()).collect(),
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+            rename: rename.iter().map(|(old, new)| (old.to_string(), new.to_string())).collect(),
+        }
+    }
+
+    fn indices(select: &[&str], exclude: &[&str]) -> Vec<usize> {
+        selection(select, exclude, &[]).resolve(&headers()).unwrap().indices
+    }
+
+    fn error(select: &[&str], exclude: &[&str], rename: &[(&str, &str)]) -> String {
+        match selection(select, exclude, rename).resolve(&headers()) {
+            Ok(projection) => panic!("应当报错，实际输出列: {:?}", projection.names),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn select_accepts_names_indices_and_ranges() {
+        assert_eq!(indices(&[], &[]), vec![0, 1, 2, 3, 4]);
+        assert_eq!(indices(&["city", "2"], &[]), vec![4, 1]);
+        assert_eq!(indices(&["2-3"], &[]), vec![1, 2]);
+        assert_eq!(indices(&["4-"], &[]), vec![3, 4]);
+        assert_eq!(indices(&["3-3", "5-5"], &[]), vec![2, 4]);
+    }
+
+    #[test]
+    fn header_named_like_an_index_wins() {
+        assert_eq!(indices(&["1"], &[]), vec![3]);
+        assert_eq!(indices(&["1-2"], &[]), vec![0, 1]);
+    }
+
+    #[test]
+    fn exclude_accepts_ranges() {
+        assert_eq!(indices(&[], &["2-3"]), vec![0, 3, 4]);
+        assert_eq!(indices(&["city", "id", "age"], &["3-"]), vec![0]);
+    }
+
+    #[test]
+    fn invalid_ranges_are_reported() {
+        assert!(error(&["6"], &[], &[]).contains("列序号'6'超出范围，输入共有5列"));
+        assert!(error(&["2-9"], &[], &[]).contains("超出范围"));
+        assert!(error(&["3-2"], &[], &[]).contains("起始序号大于结束序号"));
+        assert!(error(&["0"], &[], &[]).contains("列'0'在输入中不存在"));
+        assert!(error(&[], &["x-"], &[]).contains("列'x-'在输入中不存在"));
+    }
+
+    #[test]
+    fn overlapping_selection_is_rejected() {
+        assert!(error(&["2-4", "age"], &[], &[]).contains("列'age'被--select选择了多次"));
+        assert!(error(&[], &["1-"], &[]).contains("没有剩余的列"));
+    }
+
+    #[test]
+    fn rename_applies_to_output_columns() {
+        let projection = selection(&["3", "id"], &[], &[("age", "years")]).resolve(&headers()).unwrap();
+        assert_eq!(projection.indices, vec![2, 0]);
+        assert_eq!(projection.names, vec!["years", "id"]);
+        assert!(error(&[], &["age"], &[("age", "years")]).contains("--rename中的列'age'不在输出的列中"));
+        assert!(error(&[], &[], &[("age", "name")]).contains("输出中有多个名为'name'的列"));
+    }
+}
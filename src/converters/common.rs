@@ -1,120 +1,185 @@
-use crate::cli::OutputFormat;
+use crate::cli::{CastErrorMode, IpcCompression, JsonStyle};
 use crate::error::Result;
-use std::path::Path;
+use std::path::PathBuf;
 use arrow::array::*;
 use arrow::datatypes::*;
 use arrow::record_batch::RecordBatch;
-use parquet::file::properties::WriterProperties;
-use parquet::arrow::ArrowWriter;
-use std::fs::File;
-use log::{info, debug};
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use super::columns::ColumnSelection;
+use super::filter::Filter;
+use super::writer::{ParquetWriteOptions, WriterOptions};
 use serde_json::{json, Value};
 
-/// 将数据保存为CSV格式
-pub fn save_as_csv(
-    data: &RecordBatch, 
-    output_path: &Path, 
-    delimiter: char
-) -> Result<()> {
-    debug!("将数据保存为CSV格式: {:?}", output_path);
-    
-    let file = File::create(output_path)?;
-    let mut writer = csv::WriterBuilder::new()
-        .delimiter(delimiter as u8)
-        .from_writer(file);
-    
-    // 写入标题行
-    let schema = data.schema();
-    let header: Vec<String> = schema.fields().iter()
-        .map(|f| f.name().clone())
-        .collect();
-    
-    writer.write_record(&header)?;
-    
-    // 写入数据行
-    for row_idx in 0..data.num_rows() {
-        let mut record = Vec::new();
-        
-        for col_idx in 0..data.num_columns() {
-            let column = data.column(col_idx);
-            let value = array_value_to_string(column, row_idx);
-            record.push(value);
-        }
-        
-        writer.write_record(&record)?;
+/// 转换CSV、Excel等文件时共用的选项
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    /// 批处理大小，一次处理的行数
+    pub batch_size: usize,
+    /// CSV分隔符
+    pub delimiter: char,
+    /// 使用的线程数，默认为CPU核心数
+    pub threads: Option<usize>,
+    /// 用于推断列类型的采样行数，为None时所有列都按字符串处理
+    pub infer_rows: Option<usize>,
+    /// 每个分片文件的最大行数，为None时不按行数分片
+    pub split_rows: Option<usize>,
+    /// 每个分片文件的大致最大字节数，为None时不按大小分片
+    pub split_size: Option<u64>,
+    /// JSON数组输出的排版方式
+    pub json_style: JsonStyle,
+    /// Arrow IPC输出的数据压缩方式
+    pub ipc_compression: IpcCompression,
+    /// 输出为Excel时每个批次写入一个工作表
+    pub sheet_per_batch: bool,
+    /// Parquet输出的写入选项
+    pub parquet: ParquetWriteOptions,
+    /// --schema声明的列类型，按列名覆盖推断的类型
+    pub schema: Option<SchemaRef>,
+    /// 值无法转换为列类型时的处理方式
+    pub on_cast_error: CastErrorMode,
+    /// 无法转换的单元格报告文件（CSV格式）
+    pub cast_report: Option<PathBuf>,
+    /// 输出的列及其名称，只用于CSV和Excel输入
+    pub columns: ColumnSelection,
+    /// --where指定的行过滤条件
+    pub filter: Option<Filter>,
+}
+
+impl Default for ConvertOptions {
+    /// 每批10000行、逗号分隔，不推断类型、不分片、不过滤，输出选项使用各自的默认值
+    fn default() -> Self {
+        ConvertOptions {
+            batch_size: 10000,
+            delimiter: ',',
+            threads: None,
+            infer_rows: None,
+            split_rows: None,
+            split_size: None,
+            json_style: JsonStyle::default(),
+            ipc_compression: IpcCompression::default(),
+            sheet_per_batch: false,
+            parquet: ParquetWriteOptions::default(),
+            schema: None,
+            on_cast_error: CastErrorMode::default(),
+            cast_report: None,
+            columns: ColumnSelection::default(),
+            filter: None,
+        }
     }
-    
-    writer.flush()?;
-    Ok(())
 }
 
-/// 将数据保存为JSON格式
-pub fn save_as_json(data: &RecordBatch, output_path: &Path) -> Result<()> {
-    debug!("将数据保存为JSON格式: {:?}", output_path);
-    
-    let schema = data.schema();
-    let mut json_records = Vec::new();
-    
-    for row_idx in 0..data.num_rows() {
-        let mut row_obj = serde_json::Map::new();
-        
-        for col_idx in 0..data.num_columns() {
-            let field = schema.field(col_idx);
-            let column = data.column(col_idx);
-            let field_name = field.name();
-            
-            let value = array_value_to_json(column, row_idx);
-            row_obj.insert(field_name.clone(), value);
-        }
-        
-        json_records.push(Value::Object(row_obj));
+impl ConvertOptions {
+    /// 检查--where条件中的列和值是否与输入的schema匹配
+    pub fn validate_filter(&self, schema: &SchemaRef) -> Result<()> {
+        match &self.filter {
+            Some(filter) => filter.validate(schema),
+            None => Ok(()),
+        }
     }
     
-    let file = File::create(output_path)?;
-    serde_json::to_writer_pretty(file, &json_records)?;
+    /// 只保留满足--where条件的行，未指定条件时原样返回
+    pub fn filter_batch(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        match &self.filter {
+            Some(filter) => filter.apply(&batch),
+            None => Ok(batch),
+        }
+    }
     
-    Ok(())
+    /// 输出文件的写入选项
+    pub fn writer_options(&self) -> WriterOptions {
+        WriterOptions {
+            delimiter: self.delimiter,
+            split_rows: self.split_rows,
+            split_size: self.split_size,
+            json_style: self.json_style,
+            ipc_compression: self.ipc_compression,
+            sheet_per_batch: self.sheet_per_batch,
+            parquet: self.parquet.clone(),
+        }
+    }
 }
 
-/// 将数据保存为Parquet格式
-pub fn save_as_parquet(data: &RecordBatch, output_path: &Path) -> Result<()> {
-    debug!("将数据保存为Parquet格式: {:?}", output_path);
+/// 将非负的纳秒数格式化为ISO 8601时间部分（不含"T"），例如 1H30M、0.25S，
+/// sign添加在每个数字之前，用于时间间隔中的负值
+fn format_iso_time_part(total: i128, sign: &str) -> String {
+    let total_secs = total / 1_000_000_000;
+    let frac = total % 1_000_000_000;
+    let hours = total_secs / 3600;
+    let minutes = total_secs % 3600 / 60;
+    let seconds = total_secs % 60;
     
-    let file = File::create(output_path)?;
-    
-    let props = WriterProperties::builder()
-        .build();
-    
-    let schema = data.schema();
-    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
-    
-    writer.write(data)?;
-    writer.close()?;
-    
-    Ok(())
+    let mut result = String::new();
+    if hours > 0 {
+        result.push_str(&format!("{}{}H", sign, hours));
+    }
+    if minutes > 0 {
+        result.push_str(&format!("{}{}M", sign, minutes));
+    }
+    if seconds > 0 || frac > 0 || (hours == 0 && minutes == 0) {
+        result.push_str(sign);
+        result.push_str(&seconds.to_string());
+        if frac > 0 {
+            result.push('.');
+            result.push_str(format!("{:09}", frac).trim_end_matches('0'));
+        }
+        result.push('S');
+    }
+    result
 }
 
-/// 根据输出格式选择合适的保存方式
-pub fn save_data(
-    data: &RecordBatch,
-    output_path: &Path,
-    format: &OutputFormat,
-    delimiter: char
-) -> Result<()> {
-    crate::utils::ensure_output_dir(output_path)?;
-    
-    match format {
-        OutputFormat::Csv => save_as_csv(data, output_path, delimiter)?,
-        OutputFormat::Json => save_as_json(data, output_path)?,
-        OutputFormat::Parquet => save_as_parquet(data, output_path)?,
+/// 将持续时间格式化为ISO 8601字符串，例如 PT1H30M、PT0.25S、-PT45S
+pub fn format_iso_duration(value: i64, unit: &TimeUnit) -> String {
+    let nanos_per_unit: i128 = match unit {
+        TimeUnit::Second => 1_000_000_000,
+        TimeUnit::Millisecond => 1_000_000,
+        TimeUnit::Microsecond => 1_000,
+        TimeUnit::Nanosecond => 1,
+    };
+    let total = value as i128 * nanos_per_unit;
+    let sign = if total < 0 { "-" } else { "" };
+    format!("{}PT{}", sign, format_iso_time_part(total.abs(), ""))
+}
+
+/// 将时间间隔格式化为ISO 8601字符串，例如 P1Y2M、P3DT4H、PT0S
+///
+/// 月、天和时间部分的符号可能不同，负的部分在每个数字前加负号，例如 P1M-2D。
+pub fn format_iso_interval(months: i32, days: i32, nanos: i64) -> String {
+    let mut result = String::from("P");
+    let (years, months) = (months / 12, months % 12);
+    if years != 0 {
+        result.push_str(&format!("{}Y", years));
+    }
+    if months != 0 {
+        result.push_str(&format!("{}M", months));
+    }
+    if days != 0 {
+        result.push_str(&format!("{}D", days));
+    }
+    if nanos != 0 || result.len() == 1 {
+        let sign = if nanos < 0 { "-" } else { "" };
+        result.push('T');
+        result.push_str(&format_iso_time_part((nanos as i128).abs(), sign));
+    }
+    result
+}
+
+/// 将二进制数据格式化为小写十六进制字符串
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 读取持续时间数组中的原始整数值
+fn duration_value(array: &ArrayRef, index: usize, unit: &TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Second => array.as_any().downcast_ref::<DurationSecondArray>().unwrap().value(index),
+        TimeUnit::Millisecond => array.as_any().downcast_ref::<DurationMillisecondArray>().unwrap().value(index),
+        TimeUnit::Microsecond => array.as_any().downcast_ref::<DurationMicrosecondArray>().unwrap().value(index),
+        TimeUnit::Nanosecond => array.as_any().downcast_ref::<DurationNanosecondArray>().unwrap().value(index),
     }
-    
-    info!("数据已保存到: {}", output_path.display());
-    Ok(())
 }
 
 /// 将数组元素转换为字符串
-fn array_value_to_string(array: &ArrayRef, index: usize) -> String {
+pub fn array_value_to_string(array: &ArrayRef, index: usize) -> String {
     if array.is_null(index) {
         return String::new();
     }
@@ -171,7 +236,9 @@ fn array_value_to_string(array: &ArrayRef, index: usize) -> String {
         }
         DataType::Date32 => {
             let array = array.as_any().downcast_ref::<Date32Array>().unwrap();
-            array.value(index).to_string()
+            array.value_as_date(index)
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| array.value(index).to_string())
         }
         DataType::Date64 => {
             let array = array.as_any().downcast_ref::<Date64Array>().unwrap();
@@ -215,12 +282,116 @@ fn array_value_to_string(array: &ArrayRef, index: usize) -> String {
                 }
             }
         }
-        _ => format!("{:?}", array),
+        DataType::Duration(unit) => format_iso_duration(duration_value(array, index, unit), unit),
+        DataType::Float16 => {
+            let array = array.as_any().downcast_ref::<Float16Array>().unwrap();
+            array.value(index).to_string()
+        }
+        DataType::LargeUtf8 => {
+            let array = array.as_any().downcast_ref::<LargeStringArray>().unwrap();
+            array.value(index).to_string()
+        }
+        DataType::Utf8View => {
+            let array = array.as_any().downcast_ref::<StringViewArray>().unwrap();
+            array.value(index).to_string()
+        }
+        DataType::Binary => {
+            let array = array.as_any().downcast_ref::<BinaryArray>().unwrap();
+            hex_string(array.value(index))
+        }
+        DataType::LargeBinary => {
+            let array = array.as_any().downcast_ref::<LargeBinaryArray>().unwrap();
+            hex_string(array.value(index))
+        }
+        DataType::BinaryView => {
+            let array = array.as_any().downcast_ref::<BinaryViewArray>().unwrap();
+            hex_string(array.value(index))
+        }
+        DataType::FixedSizeBinary(_) => {
+            let array = array.as_any().downcast_ref::<FixedSizeBinaryArray>().unwrap();
+            hex_string(array.value(index))
+        }
+        DataType::Time32(time_unit) => {
+            let (time, raw) = match time_unit {
+                TimeUnit::Second => {
+                    let array = array.as_any().downcast_ref::<Time32SecondArray>().unwrap();
+                    (array.value_as_time(index).map(|t| t.format("%H:%M:%S").to_string()), array.value(index))
+                }
+                _ => {
+                    let array = array.as_any().downcast_ref::<Time32MillisecondArray>().unwrap();
+                    (array.value_as_time(index).map(|t| t.format("%H:%M:%S%.3f").to_string()), array.value(index))
+                }
+            };
+            time.unwrap_or_else(|| raw.to_string())
+        }
+        DataType::Time64(time_unit) => {
+            let (time, raw) = match time_unit {
+                TimeUnit::Microsecond => {
+                    let array = array.as_any().downcast_ref::<Time64MicrosecondArray>().unwrap();
+                    (array.value_as_time(index).map(|t| t.format("%H:%M:%S%.6f").to_string()), array.value(index))
+                }
+                _ => {
+                    let array = array.as_any().downcast_ref::<Time64NanosecondArray>().unwrap();
+                    (array.value_as_time(index).map(|t| t.format("%H:%M:%S%.9f").to_string()), array.value(index))
+                }
+            };
+            time.unwrap_or_else(|| raw.to_string())
+        }
+        DataType::Interval(interval_unit) => match interval_unit {
+            IntervalUnit::YearMonth => {
+                let array = array.as_any().downcast_ref::<IntervalYearMonthArray>().unwrap();
+                format_iso_interval(array.value(index), 0, 0)
+            }
+            IntervalUnit::DayTime => {
+                let array = array.as_any().downcast_ref::<IntervalDayTimeArray>().unwrap();
+                let value = array.value(index);
+                format_iso_interval(0, value.days, value.milliseconds as i64 * 1_000_000)
+            }
+            IntervalUnit::MonthDayNano => {
+                let array = array.as_any().downcast_ref::<IntervalMonthDayNanoArray>().unwrap();
+                let value = array.value(index);
+                format_iso_interval(value.months, value.days, value.nanoseconds)
+            }
+        },
+        DataType::Decimal128(_, _) => {
+            let array = array.as_any().downcast_ref::<Decimal128Array>().unwrap();
+            array.value_as_string(index)
+        }
+        DataType::Decimal256(_, _) => {
+            let array = array.as_any().downcast_ref::<Decimal256Array>().unwrap();
+            array.value_as_string(index)
+        }
+        DataType::Dictionary(_, _) => {
+            downcast_dictionary_array!(
+                array => match array.key(index) {
+                    Some(key) => array_value_to_string(array.values(), key),
+                    None => String::new(),
+                },
+                _ => unreachable!()
+            )
+        }
+        DataType::RunEndEncoded(_, _) => {
+            downcast_run_array!(
+                array => array_value_to_string(array.values(), array.get_physical_index(index)),
+                _ => unreachable!()
+            )
+        }
+        DataType::Union(_, _) => {
+            let array = array.as_any().downcast_ref::<UnionArray>().unwrap();
+            array_value_to_string(array.child(array.type_id(index)), array.value_offset(index))
+        }
+        // 嵌套类型输出为JSON文本
+        DataType::List(_) | DataType::LargeList(_) | DataType::FixedSizeList(_, _)
+        | DataType::Struct(_) | DataType::Map(_, _) => array_value_to_json(array, index).to_string(),
+        // 其余类型（如ListView）使用Arrow自带的格式化
+        _ => ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default())
+            .map(|formatter| formatter.value(index).to_string())
+            .unwrap_or_default(),
     }
 }
 
 /// 将数组元素转换为JSON值
-fn array_value_to_json(array: &ArrayRef, index: usize) -> Value {
+pub fn array_value_to_json(array: &ArrayRef, index: usize) -> Value {
     if array.is_null(index) {
         return Value::Null;
     }
@@ -277,7 +448,10 @@ fn array_value_to_json(array: &ArrayRef, index: usize) -> Value {
         }
         DataType::Date32 => {
             let array = array.as_any().downcast_ref::<Date32Array>().unwrap();
-            json!(array.value(index).to_string())
+            match array.value_as_date(index) {
+                Some(d) => json!(d.format("%Y-%m-%d").to_string()),
+                None => json!(array.value(index).to_string()),
+            }
         }
         DataType::Date64 => {
             let array = array.as_any().downcast_ref::<Date64Array>().unwrap();
@@ -326,6 +500,67 @@ fn array_value_to_json(array: &ArrayRef, index: usize) -> Value {
                 }
             }
         }
-        _ => json!(format!("{:?}", array)),
+        DataType::Duration(unit) => json!(format_iso_duration(duration_value(array, index, unit), unit)),
+        DataType::Float16 => {
+            let array = array.as_any().downcast_ref::<Float16Array>().unwrap();
+            json!(array.value(index).to_f32())
+        }
+        DataType::List(_) => {
+            let array = array.as_any().downcast_ref::<ListArray>().unwrap();
+            list_to_json(&array.value(index))
+        }
+        DataType::LargeList(_) => {
+            let array = array.as_any().downcast_ref::<LargeListArray>().unwrap();
+            list_to_json(&array.value(index))
+        }
+        DataType::FixedSizeList(_, _) => {
+            let array = array.as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+            list_to_json(&array.value(index))
+        }
+        DataType::Struct(fields) => {
+            let array = array.as_any().downcast_ref::<StructArray>().unwrap();
+            let object = fields.iter()
+                .zip(array.columns())
+                .map(|(field, column)| (field.name().clone(), array_value_to_json(column, index)))
+                .collect();
+            Value::Object(object)
+        }
+        DataType::Map(_, _) => {
+            // 键转换为字符串，值保留原有类型
+            let array = array.as_any().downcast_ref::<MapArray>().unwrap();
+            let entries = array.value(index);
+            let (keys, values) = (entries.column(0), entries.column(1));
+            let object = (0..entries.len())
+                .map(|i| (array_value_to_string(keys, i), array_value_to_json(values, i)))
+                .collect();
+            Value::Object(object)
+        }
+        DataType::Dictionary(_, _) => {
+            downcast_dictionary_array!(
+                array => match array.key(index) {
+                    Some(key) => array_value_to_json(array.values(), key),
+                    None => Value::Null,
+                },
+                _ => unreachable!()
+            )
+        }
+        DataType::RunEndEncoded(_, _) => {
+            downcast_run_array!(
+                array => array_value_to_json(array.values(), array.get_physical_index(index)),
+                _ => unreachable!()
+            )
+        }
+        DataType::Union(_, _) => {
+            let array = array.as_any().downcast_ref::<UnionArray>().unwrap();
+            array_value_to_json(array.child(array.type_id(index)), array.value_offset(index))
+        }
+        // 字符串、二进制、时间、时间间隔和高精度小数使用与文本输出相同的表示，
+        // 高精度小数保留为字符串以免丢失精度
+        _ => json!(array_value_to_string(array, index)),
     }
+}
+
+/// 将列表元素转换为JSON数组
+fn list_to_json(values: &ArrayRef) -> Value {
+    Value::Array((0..values.len()).map(|i| array_value_to_json(values, i)).collect())
 } 
\ No newline at end of file
@@ -1,204 +1,282 @@
 use crate::cli::OutputFormat;
 use crate::error::{Result, TransmutaError};
 use crate::utils;
+use super::common::ConvertOptions;
+use super::infer::{self, CastFailure, CastFailureLog, TypedBatchBuilder};
+use super::pipeline::run_pipeline;
+use super::reader::RecordBatches;
+use super::writer::open_writer;
 use std::path::Path;
-use std::fs::File;
-use std::io::BufReader;
-use log::{info, warn};
-use arrow::array::*;
+use std::io::Read;
+use log::{info, warn, debug};
 use arrow::datatypes::*;
 use arrow::record_batch::RecordBatch;
 use indicatif::{ProgressBar, ProgressStyle};
-use rayon::prelude::*;
 use std::time::Instant;
 use std::sync::Arc;
-use csv::{ReaderBuilder, StringRecord};
+use csv::{ReaderBuilder, StringRecord, StringRecordsIntoIter};
+
+/// 按批次读取CSV数据的读取器，创建时读取标题并采样推断列类型
+pub struct CsvBatchReader<R: Read> {
+    records: StringRecordsIntoIter<R>,
+    /// 采样时已读取、尚未输出的记录
+    sampled: std::vec::IntoIter<StringRecord>,
+    builder: TypedBatchBuilder,
+    /// 每个输出列在原始记录中的位置
+    columns: Vec<usize>,
+    batch_size: usize,
+    processed_records: usize,
+    finished: bool,
+}
+
+impl<R: Read> CsvBatchReader<R> {
+    pub fn try_new(reader: R, has_header: bool, options: &ConvertOptions) -> Result<Self> {
+        // 创建CSV读取器
+        let mut csv_reader = ReaderBuilder::new()
+            .delimiter(options.delimiter as u8)
+            .has_headers(has_header)
+            .from_reader(reader);
+        
+        let headers = if has_header {
+            csv_reader.headers()?.clone()
+        } else {
+            StringRecord::new()
+        };
+        
+        // 读取采样行用于推断列类型，采样行之后仍会参与转换；没有标题时至少读取一行以确定列数
+        let mut records = csv_reader.into_records();
+        let sample_size = options.infer_rows.unwrap_or(0).max(usize::from(!has_header));
+        let mut sampled = Vec::new();
+        for result in records.by_ref().take(sample_size) {
+            sampled.push(result?);
+        }
+        
+        // 如果没有标题，根据第一行数据的列数创建默认标题
+        let headers: Vec<String> = if has_header {
+            headers.iter().map(String::from).collect()
+        } else {
+            match sampled.first() {
+                Some(first_row) => (0..first_row.len()).map(|i| format!("Column{}", i + 1)).collect(),
+                None => return Err(TransmutaError::DataProcessingError("CSV文件为空".to_string())),
+            }
+        };
+        
+        // 只推断和解析选择的列，--schema按输出的列名匹配
+        let projection = options.columns.resolve(&headers)?;
+        let headers = projection.names;
+        let columns = projection.indices;
+        
+        let mut column_types = match options.infer_rows {
+            Some(infer_rows) => infer::infer_column_types(
+                headers.len(),
+                sampled.iter().take(infer_rows).map(|record| project(record, &columns)),
+            ),
+            None => vec![DataType::Utf8; headers.len()],
+        };
+        let declared = match &options.schema {
+            Some(declared_schema) => infer::apply_declared_types(
+                headers.iter().map(String::as_str), &mut column_types, declared_schema,
+            )?,
+            None => vec![false; headers.len()],
+        };
+        
+        // 创建schema
+        let fields: Vec<Field> = headers.iter()
+            .zip(column_types.iter())
+            .map(|(name, data_type)| Field::new(name, data_type.clone(), true))
+            .collect();
+        
+        let schema = Arc::new(Schema::new(fields));
+        debug!("列类型: {}", schema.fields().iter()
+            .map(|f| format!("{}({})", f.name(), f.data_type()))
+            .collect::<Vec<_>>()
+            .join(", "));
+        
+        Ok(CsvBatchReader {
+            records,
+            sampled: sampled.into_iter(),
+            builder: TypedBatchBuilder::new(schema).with_cast_options(declared, options.on_cast_error),
+            columns,
+            batch_size: options.batch_size,
+            processed_records: 0,
+            finished: false,
+        })
+    }
+    
+    pub fn schema(&self) -> SchemaRef {
+        self.builder.schema()
+    }
+    
+    /// 按推断或声明的类型构建批次的构建器
+    pub fn batch_builder(&self) -> TypedBatchBuilder {
+        self.builder.clone()
+    }
+    
+    /// 读取下一块原始记录及其第一行的行号，没有更多数据时返回None
+    fn next_chunk(&mut self) -> Result<Option<(usize, Vec<StringRecord>)>> {
+        let first_row_number = self.processed_records + 1;
+        let mut chunk = Vec::with_capacity(self.batch_size);
+        
+        while chunk.len() < self.batch_size {
+            match self.sampled.next() {
+                Some(record) => chunk.push(record),
+                None => match self.records.next() {
+                    Some(result) => chunk.push(result?),
+                    // 没有更多数据了
+                    None => break,
+                },
+            }
+        }
+        
+        if chunk.is_empty() {
+            return Ok(None);
+        }
+        
+        self.processed_records += chunk.len();
+        Ok(Some((first_row_number, chunk)))
+    }
+    
+    /// 转换为按顺序产生原始记录块的迭代器，用于并行解析
+    pub fn into_chunks(mut self) -> impl Iterator<Item = Result<(usize, Vec<StringRecord>)>> {
+        std::iter::from_fn(move || {
+            if self.finished {
+                return None;
+            }
+            let chunk = self.next_chunk().transpose();
+            self.finished = !matches!(chunk, Some(Ok(_)));
+            chunk
+        })
+    }
+}
+
+impl<R: Read> Iterator for CsvBatchReader<R> {
+    type Item = Result<RecordBatch>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.next_chunk() {
+            Ok(Some((first_row_number, chunk))) => {
+                Some(build_batch(&self.builder, &self.columns, first_row_number, &chunk)
+                    .map(|(batch, _)| batch))
+            }
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// 按输出列的顺序取出记录中的值，记录中缺少的列为空字符串
+fn project<'a>(record: &'a StringRecord, columns: &'a [usize]) -> impl Iterator<Item = &'a str> + 'a {
+    columns.iter().map(|&index| record.get(index).unwrap_or(""))
+}
+
+/// 把一块原始记录中选择的列构建为批次
+fn build_batch(
+    builder: &TypedBatchBuilder,
+    columns: &[usize],
+    first_row_number: usize,
+    records: &[StringRecord],
+) -> Result<(RecordBatch, Vec<CastFailure>)> {
+    builder.build(records.iter().map(|record| project(record, columns)), first_row_number)
+}
 
 /// 转换CSV文件到其他格式
 pub fn convert_csv(
     input_path: &Path,
     output_path: &Path,
     format: &OutputFormat,
-    batch_size: usize,
-    delimiter: char,
-    threads: Option<usize>,
     has_header: bool,
+    options: &ConvertOptions,
 ) -> Result<()> {
     let start_time = Instant::now();
     
-    // 检查输入文件扩展名
-    let ext = utils::get_file_extension(input_path)?;
-    if ext != "csv" {
-        warn!("输入文件扩展名不是.csv: {}", ext);
+    if utils::is_stdio(input_path) {
+        info!("开始从标准输入读取CSV数据");
+    } else {
+        // 检查输入文件扩展名
+        let ext = utils::get_file_extension(input_path)?;
+        if ext != "csv" {
+            warn!("输入文件扩展名不是.csv: {}", ext);
+        }
+        
+        info!("开始处理CSV文件: {}", input_path.display());
     }
     
-    info!("开始处理CSV文件: {}", input_path.display());
-    
-    // 打开CSV文件
-    let file = File::open(input_path)?;
-    let reader = BufReader::new(file);
-    
-    // 创建CSV读取器
-    let mut csv_reader = ReaderBuilder::new()
-        .delimiter(delimiter as u8)
-        .has_headers(has_header)
-        .from_reader(reader);
-    
-    // 获取标题
-    let headers = if has_header {
-        csv_reader.headers()?.clone()
-    } else {
-        // 如果没有标题，读取第一行数据，然后为其创建默认标题
-        if let Some(result) = csv_reader.records().next() {
-            let first_row = result?;
-            let col_count = first_row.len();
-            let default_headers = StringRecord::from(
-                (0..col_count).map(|i| format!("Column{}", i + 1)).collect::<Vec<String>>()
-            );
-            default_headers
-        } else {
-            return Err(TransmutaError::DataProcessingError("CSV文件为空".to_string()));
+    // 按已读取的字节数显示进度，标准输入无法预知大小，只显示已读取的字节数
+    let pb = match utils::input_size(input_path) {
+        Some(total_bytes) => {
+            let pb = ProgressBar::new(total_bytes);
+            pb.set_style(ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+                .unwrap()
+                .progress_chars("#>-"));
+            pb
+        }
+        None => {
+            let pb = ProgressBar::new_spinner();
+            pb.set_style(ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {bytes} ({bytes_per_sec})")
+                .unwrap());
+            pb
         }
     };
     
-    // 重新打开文件，因为我们可能已经读取了一些数据
-    let file = File::open(input_path)?;
-    let reader = BufReader::new(file);
-    let mut csv_reader = ReaderBuilder::new()
-        .delimiter(delimiter as u8)
-        .has_headers(has_header)
-        .from_reader(reader);
-    
-    // 如果之前读取了一行数据（没有标题的情况），需要把文件指针重置
-    if !has_header {
-        // 跳过第一行
-        if csv_reader.records().next().is_none() {
-            return Err(TransmutaError::DataProcessingError("CSV文件为空".to_string()));
-        }
-    }
+    // 只读取一遍输入：读取标题并采样推断列类型，之后按批次流式转换
+    let input = pb.wrap_read(utils::create_buf_reader(input_path)?);
+    let reader = CsvBatchReader::try_new(input, has_header, options)?;
+    let builder = reader.batch_builder();
+    let columns = reader.columns.clone();
     
-    // 计算文件总行数（这可能会遍历整个文件，对于大文件可能效率不高）
-    let file = File::open(input_path)?;
-    let reader = BufReader::new(file);
-    let count_reader = ReaderBuilder::new()
-        .delimiter(delimiter as u8)
-        .has_headers(has_header)
-        .from_reader(reader);
-    
-    let total_rows = count_reader.into_records().count();
-    info!("CSV文件共有{}行数据", total_rows);
-    
-    // 创建进度条
-    let pb = ProgressBar::new(total_rows as u64);
-    pb.set_style(ProgressStyle::default_bar()
-        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
-        .unwrap()
-        .progress_chars("#>-"));
-    
-    // 计算处理批次
-    let batch_count = (total_rows + batch_size - 1) / batch_size;
-    info!("将数据分为{}个批次处理，每批次{}行", batch_count, batch_size);
-    
-    // 重新打开文件
-    let file = File::open(input_path)?;
-    let reader = BufReader::new(file);
-    let mut csv_reader = ReaderBuilder::new()
-        .delimiter(delimiter as u8)
-        .has_headers(has_header)
-        .from_reader(reader);
-    
-    // 跳过标题行
-    if has_header {
-        csv_reader.headers()?;
-    }
+    // 设置线程数
+    let thread_count = utils::get_thread_count(options.threads);
     
-    // 创建schema
-    let fields: Vec<Field> = headers.iter()
-        .map(|name| Field::new(name, DataType::Utf8, true))
-        .collect();
+    // 在写出任何数据之前检查过滤条件
+    options.validate_filter(&reader.schema())?;
     
-    let schema = Arc::new(Schema::new(fields));
+    // 打开输出文件，所有批次写入同一个写入器
+    let mut writer = open_writer(output_path, format, reader.schema(), &options.writer_options())?;
+    let mut cast_failures = CastFailureLog::try_new(options.cast_report.as_deref())?;
     
-    // 设置线程数
-    let thread_count = utils::get_thread_count(threads);
-    
-    // 处理每个批次
-    let mut records = csv_reader.records();
     let mut processed_records = 0;
     
-    for batch_idx in 0..batch_count {
-        // 创建列构建器
-        let mut string_builders: Vec<StringBuilder> = headers.iter()
-            .map(|_| StringBuilder::new())
-            .collect();
-        
-        // 读取批次数据
-        let mut batch_records = 0;
-        
-        while batch_records < batch_size {
-            if let Some(result) = records.next() {
-                let record = result?;
-                
-                // 添加每列数据
-                for (col_idx, field) in record.iter().enumerate() {
-                    if col_idx < string_builders.len() {
-                        string_builders[col_idx].append_value(field);
-                    }
-                }
-                
-                // 如果某行数据列数少于标题列数，填充空值
-                for col_idx in record.len()..headers.len() {
-                    string_builders[col_idx].append_value("");
-                }
-                
-                batch_records += 1;
-                processed_records += 1;
-                pb.set_position(processed_records as u64);
-            } else {
-                // 没有更多数据了
-                break;
-            }
-        }
-        
-        if batch_records == 0 {
-            // 这个批次没有任何数据，跳过
-            continue;
-        }
-        
-        // 创建数组
-        let arrays: Vec<Arc<dyn Array>> = string_builders.into_iter()
-            .map(|mut builder| Arc::new(builder.finish()) as Arc<dyn Array>)
-            .collect();
-        
-        // 创建RecordBatch
-        let record_batch = RecordBatch::try_new(schema.clone(), arrays)?;
-        
-        // 确定输出路径
-        let mut output_file_path = output_path.to_path_buf();
-        
-        // 为多批次生成不同的文件名
-        if batch_count > 1 {
-            if let Some(file_name) = output_path.file_stem() {
-                let mut new_file_name = file_name.to_string_lossy().to_string();
-                new_file_name.push_str(&format!("_part{:04}", batch_idx + 1));
-                
-                if let Some(ext) = output_path.extension() {
-                    new_file_name.push('.');
-                    new_file_name.push_str(&ext.to_string_lossy());
-                }
-                
-                output_file_path = output_path.with_file_name(new_file_name);
-            }
-        }
-        
-        // 保存到指定格式
-        super::common::save_data(&record_batch, &output_file_path, format, delimiter)?;
-    }
+    // 读取线程产生原始记录块，线程池并行解析，按原始顺序写入输出文件
+    run_pipeline(
+        reader.into_chunks(),
+        thread_count,
+        |(first_row_number, records): (usize, Vec<StringRecord>)| {
+            let (record_batch, failures) = build_batch(&builder, &columns, first_row_number, &records)?;
+            Ok((options.filter_batch(record_batch)?, failures))
+        },
+        |(record_batch, failures)| {
+            cast_failures.record(&failures)?;
+            writer.write(&record_batch)?;
+            processed_records += record_batch.num_rows();
+            Ok(())
+        },
+    )?;
     
+    writer.close()?;
+    cast_failures.finish()?;
     pb.finish_with_message("CSV文件转换完成");
+    info!("共转换{}行数据", processed_records);
     
     let elapsed = start_time.elapsed();
     info!("总处理时间: {:.2}秒", elapsed.as_secs_f64());
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// 按批次逐个读取CSV文件的数据
+pub fn read_csv(input_path: &Path, has_header: bool, options: &ConvertOptions) -> Result<(SchemaRef, RecordBatches)> {
+    let reader = CsvBatchReader::try_new(utils::create_buf_reader(input_path)?, has_header, options)?;
+    let schema = reader.schema();
+    Ok((schema, Box::new(reader)))
+}
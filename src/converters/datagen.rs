@@ -1,17 +1,21 @@
-use crate::cli::{OutputFormat, SchemaFormat};
+use crate::cli::{Locale, OutputFormat, SchemaFormat};
 use crate::error::{Result, TransmutaError};
+use crate::utils;
+use super::generator::{ChildRows, ColumnGenerator, UniqueValues};
+use super::pipeline::run_pipeline;
+use super::writer::{open_writer, WriterOptions};
 use std::path::Path;
-use std::fs::File;
-use std::io::BufReader;
 use log::{info, debug};
+use indicatif::{ProgressBar, ProgressStyle};
 use arrow::array::*;
 use arrow::datatypes::*;
-use arrow::datatypes::IntervalMonthDayNano;
+use arrow::compute::concat;
 use arrow::record_batch::RecordBatch;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
-use rand::{Rng, SeedableRng};
-use rand::distributions::Alphanumeric;
+use serde_json::Value;
+use rand::SeedableRng;
 use rand::rngs::StdRng;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -89,6 +93,114 @@ pub enum DataType {
     Uuid,           // 通用唯一标识符
     #[serde(rename = "null")]
     Null,           // 空值类型
+    
+    // 语义化类型，按locale生成接近真实的字符串
+    #[serde(rename = "name")]
+    Name,           // 姓名
+    #[serde(rename = "email")]
+    Email,          // 电子邮箱
+    #[serde(rename = "phone")]
+    Phone,          // 电话号码
+    #[serde(rename = "address")]
+    Address,        // 地址
+    #[serde(rename = "city")]
+    City,           // 城市
+    #[serde(rename = "company")]
+    Company,        // 公司名称
+    #[serde(rename = "url")]
+    Url,            // 网址
+    #[serde(rename = "ipv4")]
+    Ipv4,           // IPv4地址
+    #[serde(rename = "ipv6")]
+    Ipv6,           // IPv6地址
+    #[serde(rename = "iban")]
+    Iban,           // 国际银行账号，校验位有效
+    #[serde(rename = "credit_card", alias = "creditcard")]
+    CreditCard,     // 信用卡号，满足Luhn校验
+    #[serde(rename = "lorem")]
+    Lorem,          // 占位文本
+}
+
+impl DataType {
+    /// 是否为按locale生成的语义化类型
+    pub fn is_semantic(&self) -> bool {
+        matches!(
+            self,
+            DataType::Name | DataType::Email | DataType::Phone | DataType::Address | DataType::City
+                | DataType::Company | DataType::Url | DataType::Ipv4 | DataType::Ipv6 | DataType::Iban
+                | DataType::CreditCard | DataType::Lorem
+        )
+    }
+}
+
+/// 字符串和二进制数据的长度或references列的cardinality，可以是固定值，也可以是{"min": 5, "max": 20}形式的范围
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum LengthSpec {
+    Exact(usize),
+    Range { min: usize, max: usize },
+}
+
+impl LengthSpec {
+    /// 最小和最大长度
+    pub fn bounds(&self) -> (usize, usize) {
+        match *self {
+            LengthSpec::Exact(len) => (len, len),
+            LengthSpec::Range { min, max } => (min, max),
+        }
+    }
+}
+
+/// 取值分布的类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DistributionKind {
+    #[default]
+    Uniform,
+    Normal,
+    Zipf,
+}
+
+/// 取值的分布，可以只写类型名（如"normal"），也可以写为带参数的对象
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(from = "DistributionInput")]
+pub struct DistributionSpec {
+    #[serde(rename = "type")]
+    pub kind: DistributionKind,
+    /// 正态分布的均值，写法与min、max相同，默认为两者的中点
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean: Option<Value>,
+    /// 正态分布的标准差，以列的底层单位表示（如日期为天），默认为取值范围的六分之一
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub std_dev: Option<f64>,
+    /// Zipf分布的指数，默认为1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exponent: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DistributionInput {
+    Kind(DistributionKind),
+    Spec {
+        #[serde(rename = "type")]
+        kind: DistributionKind,
+        #[serde(default)]
+        mean: Option<Value>,
+        #[serde(default)]
+        std_dev: Option<f64>,
+        #[serde(default)]
+        exponent: Option<f64>,
+    },
+}
+
+impl From<DistributionInput> for DistributionSpec {
+    fn from(input: DistributionInput) -> Self {
+        match input {
+            DistributionInput::Kind(kind) => DistributionSpec { kind, mean: None, std_dev: None, exponent: None },
+            DistributionInput::Spec { kind, mean, std_dev, exponent } => DistributionSpec { kind, mean, std_dev, exponent },
+        }
+    }
 }
 
 // 列定义
@@ -96,18 +208,191 @@ pub enum DataType {
 pub struct ColumnDefinition {
     pub name: String,
     pub data_type: DataType,
+    /// 高精度小数的有效位数，只用于decimal类型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precision: Option<u8>,
+    /// 高精度小数的小数位数，只用于decimal类型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<i8>,
+    /// 数值、日期和时间类型的最小值（含）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<Value>,
+    /// 数值、日期和时间类型的最大值（含）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Value>,
+    /// 字符串和二进制数据的长度，fixedsizebinary为固定的字节数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<LengthSpec>,
+    /// 空值所占的比例，0到1之间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub null_ratio: Option<f64>,
+    /// 是否生成不重复的值（空值除外）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unique: bool,
+    /// 只从这些值中选取
+    #[serde(rename = "enum", default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<Value>>,
+    /// enum中每个值的权重，默认等权重
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<Vec<f64>>,
+    /// 字符串需要匹配的正则表达式
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    /// 数值、日期和时间的取值分布，默认为均匀分布
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<DistributionSpec>,
+    /// 语义化类型的语言区域，未指定时使用--locale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<Locale>,
+    /// 多表列定义中引用的其他表的列，形如"customers.id"，值从该列已生成的值中选取
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<String>,
+    /// 被引用表的每一行对应的行数，指定后本表的行数由被引用表决定
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cardinality: Option<LengthSpec>,
+}
+
+/// 未指定小数位数时高精度小数的默认小数位数
+const DEFAULT_DECIMAL_SCALE: i8 = 10;
+
+impl ColumnDefinition {
+    /// 只有列名和类型、其他选项都使用默认值的列定义
+    pub fn new(name: String, data_type: DataType) -> Self {
+        ColumnDefinition {
+            name,
+            data_type,
+            precision: None,
+            scale: None,
+            min: None,
+            max: None,
+            length: None,
+            null_ratio: None,
+            unique: false,
+            values: None,
+            weights: None,
+            regex: None,
+            distribution: None,
+            locale: None,
+            references: None,
+            cardinality: None,
+        }
+    }
+    
+    /// fixedsizebinary的字节数，未指定length时为16字节
+    pub fn fixed_size_binary_width(&self) -> usize {
+        match self.length {
+            Some(length) => length.bounds().0,
+            None => 16,
+        }
+    }
+    
+    /// 高精度小数的有效位数和小数位数，未指定时使用对应Arrow类型的最大有效位数
+    fn decimal_precision_and_scale(&self) -> (u8, i8) {
+        let max_precision = match self.data_type {
+            DataType::Decimal256 => DECIMAL256_MAX_PRECISION,
+            _ => DECIMAL128_MAX_PRECISION,
+        };
+        let precision = self.precision.unwrap_or(max_precision);
+        (precision, self.scale.unwrap_or(DEFAULT_DECIMAL_SCALE.min(precision as i8)))
+    }
+    
+    /// 检查precision和scale是否只用于decimal类型且在允许范围内
+    fn validate(&self) -> Result<()> {
+        let max_precision = match self.data_type {
+            DataType::Decimal128 => DECIMAL128_MAX_PRECISION,
+            DataType::Decimal | DataType::Decimal256 => DECIMAL256_MAX_PRECISION,
+            _ => {
+                if self.precision.is_some() || self.scale.is_some() {
+                    return Err(TransmutaError::InvalidArgument(format!(
+                        "列'{}'不是decimal类型，不能指定precision和scale", self.name
+                    )));
+                }
+                return Ok(());
+            }
+        };
+        
+        let (precision, scale) = self.decimal_precision_and_scale();
+        if precision == 0 || precision > max_precision {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'的precision必须在1到{}之间: {}", self.name, max_precision, precision
+            )));
+        }
+        if scale < 0 || scale as u8 > precision {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'的scale必须在0到precision({})之间: {}", self.name, precision, scale
+            )));
+        }
+        Ok(())
+    }
+    
+    /// 对应的Arrow类型，decimal的有效位数超过38位时使用Decimal256
+    pub fn arrow_type(&self) -> arrow::datatypes::DataType {
+        match self.data_type {
+            // 基本类型
+            DataType::String => arrow::datatypes::DataType::Utf8,
+            DataType::Integer => arrow::datatypes::DataType::Int32,
+            DataType::Float => arrow::datatypes::DataType::Float64,
+            DataType::Boolean => arrow::datatypes::DataType::Boolean,
+            
+            // 精确整数类型
+            DataType::Int8 => arrow::datatypes::DataType::Int8,
+            DataType::Int16 => arrow::datatypes::DataType::Int16,
+            DataType::Int32 => arrow::datatypes::DataType::Int32,
+            DataType::Int64 => arrow::datatypes::DataType::Int64,
+            DataType::UInt8 => arrow::datatypes::DataType::UInt8,
+            DataType::UInt16 => arrow::datatypes::DataType::UInt16,
+            DataType::UInt32 => arrow::datatypes::DataType::UInt32,
+            DataType::UInt64 => arrow::datatypes::DataType::UInt64,
+            
+            // 精确浮点数类型
+            DataType::Float32 => arrow::datatypes::DataType::Float32,
+            DataType::Float64 => arrow::datatypes::DataType::Float64,
+            
+            // 高精度数值类型
+            DataType::Decimal | DataType::Decimal128 | DataType::Decimal256 => {
+                let (precision, scale) = self.decimal_precision_and_scale();
+                match self.data_type {
+                    DataType::Decimal256 => arrow::datatypes::DataType::Decimal256(precision, scale),
+                    _ if precision > DECIMAL128_MAX_PRECISION => arrow::datatypes::DataType::Decimal256(precision, scale),
+                    _ => arrow::datatypes::DataType::Decimal128(precision, scale),
+                }
+            }
+            
+            // 日期和时间类型
+            DataType::Date => arrow::datatypes::DataType::Date32,
+            DataType::Date32 => arrow::datatypes::DataType::Date32,
+            DataType::Timestamp => arrow::datatypes::DataType::Timestamp(TimeUnit::Millisecond, None),
+            DataType::Time32 => arrow::datatypes::DataType::Time32(TimeUnit::Millisecond),
+            DataType::Time64 => arrow::datatypes::DataType::Time64(TimeUnit::Nanosecond),
+            DataType::Interval => arrow::datatypes::DataType::Interval(IntervalUnit::MonthDayNano),
+            DataType::Duration => arrow::datatypes::DataType::Duration(TimeUnit::Nanosecond),
+            
+            // 二进制数据类型
+            DataType::Binary => arrow::datatypes::DataType::Binary,
+            DataType::FixedSizeBinary => arrow::datatypes::DataType::FixedSizeBinary(self.fixed_size_binary_width() as i32),
+            
+            // 特殊类型
+            DataType::Uuid => arrow::datatypes::DataType::Utf8,
+            DataType::Null => arrow::datatypes::DataType::Null,
+            
+            // 语义化类型都是字符串
+            DataType::Name | DataType::Email | DataType::Phone | DataType::Address | DataType::City
+                | DataType::Company | DataType::Url | DataType::Ipv4 | DataType::Ipv6 | DataType::Iban
+                | DataType::CreditCard | DataType::Lorem => arrow::datatypes::DataType::Utf8,
+        }
+    }
 }
 
 // 从CSV文件读取列定义
 fn read_schema_from_csv(path: &Path, delimiter: char) -> Result<Vec<ColumnDefinition>> {
     info!("从CSV文件读取列定义: {}", path.display());
     
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    let reader = utils::create_buf_reader(path)?;
     
     let mut csv_reader = csv::ReaderBuilder::new()
         .delimiter(delimiter as u8)
         .has_headers(false)
+        .flexible(true)
         .from_reader(reader);
     
     let mut column_defs = Vec::new();
@@ -167,12 +452,40 @@ fn read_schema_from_csv(path: &Path, delimiter: char) -> Result<Vec<ColumnDefini
             "uuid" => DataType::Uuid,
             "null" => DataType::Null,
             
+            // 语义化类型
+            "name" => DataType::Name,
+            "email" => DataType::Email,
+            "phone" => DataType::Phone,
+            "address" => DataType::Address,
+            "city" => DataType::City,
+            "company" => DataType::Company,
+            "url" => DataType::Url,
+            "ipv4" => DataType::Ipv4,
+            "ipv6" => DataType::Ipv6,
+            "iban" => DataType::Iban,
+            "credit_card" | "creditcard" => DataType::CreditCard,
+            "lorem" => DataType::Lorem,
+            
             _ => return Err(TransmutaError::DataProcessingError(format!(
                 "第{}行不支持的数据类型: {}", row_idx + 1, type_str
             ))),
         };
         
-        column_defs.push(ColumnDefinition { name, data_type });
+        // 可选的第三、四列为decimal类型的precision和scale
+        let precision = match record.get(2).map(str::trim).filter(|v| !v.is_empty()) {
+            Some(v) => Some(v.parse::<u8>().map_err(|_| TransmutaError::DataProcessingError(format!(
+                "第{}行的precision不是有效的数字: {}", row_idx + 1, v
+            )))?),
+            None => None,
+        };
+        let scale = match record.get(3).map(str::trim).filter(|v| !v.is_empty()) {
+            Some(v) => Some(v.parse::<i8>().map_err(|_| TransmutaError::DataProcessingError(format!(
+                "第{}行的scale不是有效的数字: {}", row_idx + 1, v
+            )))?),
+            None => None,
+        };
+        
+        column_defs.push(ColumnDefinition { precision, scale, ..ColumnDefinition::new(name, data_type) });
     }
     
     if column_defs.is_empty() {
@@ -182,20 +495,88 @@ fn read_schema_from_csv(path: &Path, delimiter: char) -> Result<Vec<ColumnDefini
     Ok(column_defs)
 }
 
-// 从JSON文件读取列定义
-fn read_schema_from_json(path: &Path) -> Result<Vec<ColumnDefinition>> {
+/// 多表列定义中的一张表
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TableDefinition {
+    pub name: String,
+    /// 行数，未指定时使用--rows；有列指定了cardinality时由被引用表决定
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<usize>,
+    pub columns: Vec<ColumnDefinition>,
+}
+
+/// 多表列定义文件，形如{"tables": [...]}
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct MultiTableSchema {
+    tables: Vec<TableDefinition>,
+}
+
+/// 列定义文件的内容
+#[derive(Debug, Clone)]
+pub enum SchemaDefinition {
+    /// 单表的列定义
+    Columns(Vec<ColumnDefinition>),
+    /// 多表的列定义，表之间可以通过references关联
+    Tables(Vec<TableDefinition>),
+}
+
+// 从JSON文件读取列定义，顶层为数组时是单表的列定义，为对象时是多表的列定义
+fn read_schema_from_json(path: &Path) -> Result<SchemaDefinition> {
     info!("从JSON文件读取列定义: {}", path.display());
     
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    let reader = utils::create_buf_reader(path)?;
     
-    let column_defs: Vec<ColumnDefinition> = serde_json::from_reader(reader)?;
+    let value: Value = serde_json::from_reader(reader)?;
+    
+    if value.is_object() {
+        let schema: MultiTableSchema = serde_json::from_value(value)?;
+        if schema.tables.is_empty() {
+            return Err(TransmutaError::DataProcessingError("表定义为空".to_string()));
+        }
+        if let Some(table) = schema.tables.iter().find(|table| table.columns.is_empty()) {
+            return Err(TransmutaError::DataProcessingError(format!("表'{}'的列定义为空", table.name)));
+        }
+        return Ok(SchemaDefinition::Tables(schema.tables));
+    }
+    
+    let column_defs: Vec<ColumnDefinition> = serde_json::from_value(value)?;
     
     if column_defs.is_empty() {
         return Err(TransmutaError::DataProcessingError("列定义为空".to_string()));
     }
     
-    Ok(column_defs)
+    Ok(SchemaDefinition::Columns(column_defs))
+}
+
+/// 按指定格式读取列定义文件并检查每列的定义，CSV格式使用给定的分隔符
+pub fn read_schema_definition(path: &Path, schema_format: &SchemaFormat, delimiter: char) -> Result<SchemaDefinition> {
+    let definition = match schema_format {
+        SchemaFormat::Csv => SchemaDefinition::Columns(read_schema_from_csv(path, delimiter)?),
+        SchemaFormat::Json => read_schema_from_json(path)?,
+    };
+    match &definition {
+        SchemaDefinition::Columns(column_defs) => {
+            for col in column_defs {
+                col.validate()?;
+            }
+        }
+        SchemaDefinition::Tables(tables) => {
+            for col in tables.iter().flat_map(|table| &table.columns) {
+                col.validate()?;
+            }
+        }
+    }
+    Ok(definition)
+}
+
+/// 按指定格式读取单表的列定义文件，CSV格式使用给定的分隔符
+pub fn read_column_definitions(path: &Path, schema_format: &SchemaFormat, delimiter: char) -> Result<Vec<ColumnDefinition>> {
+    match read_schema_definition(path, schema_format, delimiter)? {
+        SchemaDefinition::Columns(column_defs) => Ok(column_defs),
+        SchemaDefinition::Tables(_) => Err(TransmutaError::InvalidArgument(
+            "多表列定义只能用于data-gen".to_string()
+        )),
+    }
 }
 
 // 获取当前时间戳作为默认种子
@@ -206,480 +587,390 @@ fn get_default_seed() -> u64 {
         .as_secs()
 }
 
-// 生成随机字符串
-fn generate_random_string(rng: &mut StdRng, min_len: usize, max_len: usize) -> String {
-    let len = rng.gen_range(min_len..=max_len);
-    rng.sample_iter(&Alphanumeric)
-        .take(len)
-        .map(char::from)
-        .collect()
-}
-
-// 生成随机整数
-fn generate_random_integer(rng: &mut StdRng, min: i32, max: i32) -> i32 {
-    rng.gen_range(min..=max)
-}
-
-// 生成随机浮点数
-fn generate_random_float(rng: &mut StdRng, min: f64, max: f64) -> f64 {
-    rng.gen_range(min..=max)
+/// 由种子和序号派生新的种子（SplitMix64），用于每张表和每个数据块，
+/// 使每块的数据只取决于总种子和块序号，与线程数和处理顺序无关
+fn derive_seed(seed: u64, index: u64) -> u64 {
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
-// 生成随机布尔值
-fn generate_random_boolean(rng: &mut StdRng) -> bool {
-    rng.gen()
+/// 数据生成的选项
+#[derive(Debug, Clone)]
+pub struct GenerateOptions {
+    /// 生成的行数
+    pub rows: usize,
+    /// 每个批次生成的行数
+    pub batch_size: usize,
+    /// 线程数，为None时使用CPU核心数
+    pub threads: Option<usize>,
+    /// 随机数种子，未指定时使用当前时间
+    pub seed: Option<u64>,
+    /// 语义化类型默认的语言区域
+    pub locale: Locale,
 }
 
-// 生成随机8位整数
-fn generate_random_int8(rng: &mut StdRng) -> i8 {
-    rng.gen_range(i8::MIN..=i8::MAX)
-}
-
-// 生成随机16位整数
-fn generate_random_int16(rng: &mut StdRng) -> i16 {
-    rng.gen_range(i16::MIN..=i16::MAX)
-}
-
-// 生成随机32位整数
-fn generate_random_int32(rng: &mut StdRng) -> i32 {
-    rng.gen_range(i32::MIN/2..=i32::MAX/2) // 使用一半范围以避免极端值
-}
-
-// 生成随机64位整数
-fn generate_random_int64(rng: &mut StdRng) -> i64 {
-    rng.gen_range(i64::MIN/1000..=i64::MAX/1000) // 使用较小范围以避免极端值
-}
-
-// 生成随机无符号8位整数
-fn generate_random_uint8(rng: &mut StdRng) -> u8 {
-    rng.gen()
-}
-
-// 生成随机无符号16位整数
-fn generate_random_uint16(rng: &mut StdRng) -> u16 {
-    rng.gen()
-}
-
-// 生成随机无符号32位整数
-fn generate_random_uint32(rng: &mut StdRng) -> u32 {
-    rng.gen_range(0..=u32::MAX/2) // 使用一半范围以避免极端值
-}
-
-// 生成随机无符号64位整数
-fn generate_random_uint64(rng: &mut StdRng) -> u64 {
-    rng.gen_range(0..=u64::MAX/1000) // 使用较小范围以避免极端值
-}
-
-// 生成随机32位浮点数
-fn generate_random_float32(rng: &mut StdRng) -> f32 {
-    rng.gen_range(-1000.0..=1000.0)
-}
-
-// 生成随机64位浮点数
-fn generate_random_float64(rng: &mut StdRng) -> f64 {
-    rng.gen_range(-1000000.0..=1000000.0)
-}
-
-// 生成随机小数（使用字符串表示，模拟Decimal类型）
-fn generate_random_decimal(rng: &mut StdRng, precision: usize) -> String {
-    let whole_part = rng.gen_range(0..10000);
-    let decimal_part = rng.gen_range(0..10u32.pow(precision as u32));
-    format!("{}.{:0width$}", whole_part, decimal_part, width = precision)
-}
-
-// 生成随机日期（从2000-01-01到现在）
-fn generate_random_date(rng: &mut StdRng) -> i32 {
-    // 2000-01-01对应的天数
-    let min_days = 10957;
-    // 当前日期对应的天数（近似值）
-    let max_days = 19000;
-    rng.gen_range(min_days..=max_days)
-}
-
-// 生成随机32位日期（从2000-01-01到现在）
-fn generate_random_date32(rng: &mut StdRng) -> i32 {
-    generate_random_date(rng)
-}
-
-// 生成随机时间戳（从2000-01-01到现在，毫秒级）
-fn generate_random_timestamp(rng: &mut StdRng) -> i64 {
-    // 2000-01-01 00:00:00对应的毫秒数
-    let min_ms = 946684800000;
-    // 当前时间对应的毫秒数
-    let max_ms = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis() as i64;
-    rng.gen_range(min_ms..=max_ms)
+/// 单张表的生成计划
+struct TablePlan<'a> {
+    columns: &'a [ColumnDefinition],
+    /// 没有列指定cardinality时的行数
+    rows: usize,
+    /// 被其他表引用、生成后需要保留的列
+    retained: HashSet<&'a str>,
+    /// 该表的随机数种子
+    seed: u64,
 }
 
-// 生成随机时间（32位，秒或毫秒精度）
-fn generate_random_time32(rng: &mut StdRng, is_millis: bool) -> i32 {
-    if is_millis {
-        // 毫秒精度，范围为0到86400000（一天的毫秒数）
-        rng.gen_range(0..86400000)
-    } else {
-        // 秒精度，范围为0到86400（一天的秒数）
-        rng.gen_range(0..86400)
-    }
+/// 表中一列的生成方式
+enum TableColumn {
+    /// 按列定义随机生成，包括未指定cardinality的引用列
+    Random(ColumnGenerator),
+    /// 指定了cardinality的引用列，同时决定表的行数
+    Child(ChildRows),
 }
 
-// 生成随机时间（64位，微秒或纳秒精度）
-fn generate_random_time64(rng: &mut StdRng, is_nanos: bool) -> i64 {
-    if is_nanos {
-        // 纳秒精度，范围为0到86400000000000（一天的纳秒数）
-        rng.gen_range(0..86400000000000)
-    } else {
-        // 微秒精度，范围为0到86400000000（一天的微秒数）
-        rng.gen_range(0..86400000000)
+/// 根据列定义按批次生成随机数据，流式写入输出文件
+///
+/// 每个批次使用由种子和批次序号派生的随机数生成器，在线程池中并行生成，按顺序写出，
+/// 因此相同的种子和批次大小总是得到相同的输出，与线程数无关。unique列需要检查此前
+/// 所有批次生成过的值，在写入端按批次顺序生成。多表列定义中的每张表分别输出到
+/// 以表名为后缀的文件，例如 data.csv -> data_customers.csv、data_orders.csv。
+pub fn generate_data(
+    schema_path: &Path,
+    schema_format: &SchemaFormat,
+    output_path: &Path,
+    format: &OutputFormat,
+    options: &GenerateOptions,
+    writer_options: &WriterOptions,
+) -> Result<()> {
+    let start_time = std::time::Instant::now();
+    
+    // 读取列定义
+    let definition = read_schema_definition(schema_path, schema_format, writer_options.delimiter)?;
+    
+    let seed_value = options.seed.unwrap_or_else(get_default_seed);
+    info!("使用随机种子: {}", seed_value);
+    
+    match definition {
+        SchemaDefinition::Columns(column_defs) => {
+            if let Some(col) = column_defs.iter().find(|col| col.references.is_some()) {
+                return Err(TransmutaError::InvalidArgument(format!(
+                    "列'{}'的references只能在多表列定义中使用", col.name
+                )));
+            }
+            let plan = TablePlan {
+                columns: &column_defs,
+                rows: options.rows,
+                retained: HashSet::new(),
+                seed: seed_value,
+            };
+            generate_table(&plan, &HashMap::new(), output_path, format, options, writer_options)?;
+        }
+        SchemaDefinition::Tables(tables) => {
+            if utils::is_stdio(output_path) {
+                return Err(TransmutaError::InvalidArgument("多表数据不能输出到标准输出".to_string()));
+            }
+            
+            // 被引用的列需要在生成后保留，供引用它的表选取
+            let order = table_order(&tables)?;
+            let mut retained: HashMap<usize, HashSet<&str>> = HashMap::new();
+            for col in tables.iter().flat_map(|table| &table.columns) {
+                if let Some(reference) = &col.references {
+                    let (table_index, referenced) = resolve_reference(&tables, col, reference)?;
+                    retained.entry(table_index).or_default().insert(referenced.name.as_str());
+                }
+            }
+            
+            // 已生成的被引用列，键为"表名.列名"
+            let mut keys: HashMap<String, ArrayRef> = HashMap::new();
+            for table_index in order {
+                let table = &tables[table_index];
+                let plan = TablePlan {
+                    columns: &table.columns,
+                    rows: table.rows.unwrap_or(options.rows),
+                    retained: retained.remove(&table_index).unwrap_or_default(),
+                    seed: derive_seed(seed_value, table_index as u64),
+                };
+                let table_path = utils::suffixed_file_path(output_path, &utils::sanitize_file_name(&table.name));
+                info!("生成表'{}': {}", table.name, table_path.display());
+                
+                let generated = generate_table(&plan, &keys, &table_path, format, options, writer_options)?;
+                for (column, array) in generated {
+                    keys.insert(format!("{}.{}", table.name, column), array);
+                }
+            }
+        }
     }
+    
+    let elapsed = start_time.elapsed();
+    info!("总处理时间: {:.2}秒", elapsed.as_secs_f64());
+    
+    Ok(())
 }
 
-// 生成随机时间间隔
-fn generate_random_interval(rng: &mut StdRng) -> IntervalMonthDayNano {
-    // 月，日，毫秒
-    let months = rng.gen_range(-1200..1200); // -100年到+100年
-    let days = rng.gen_range(-3650..3650);   // -10年到+10年
-    let millis = rng.gen_range(-86400000..86400000); // -1天到+1天
-    // 将毫秒转换为纳秒
-    let nanos = millis as i64 * 1_000_000;
-    IntervalMonthDayNano::new(months, days, nanos)
-}
-
-// 生成随机持续时间（纳秒）
-fn generate_random_duration(rng: &mut StdRng) -> i64 {
-    // 生成从0到约1年的纳秒
-    rng.gen_range(0..31536000000000000)
-}
-
-// 生成随机二进制数据
-fn generate_random_binary(rng: &mut StdRng, min_len: usize, max_len: usize) -> Vec<u8> {
-    let len = rng.gen_range(min_len..=max_len);
-    let mut bytes = vec![0u8; len];
-    rng.fill(&mut bytes[..]);
-    bytes
-}
-
-// 生成固定大小的随机二进制数据
-fn generate_random_fixed_size_binary(rng: &mut StdRng, size: usize) -> Vec<u8> {
-    let mut bytes = vec![0u8; size];
-    rng.fill(&mut bytes[..]);
-    bytes
+/// 解析"表名.列名"形式的引用，返回被引用表的序号和列定义
+fn resolve_reference<'a>(
+    tables: &'a [TableDefinition],
+    col: &ColumnDefinition,
+    reference: &str,
+) -> Result<(usize, &'a ColumnDefinition)> {
+    tables.iter()
+        .enumerate()
+        .find_map(|(index, table)| {
+            let column = reference.strip_prefix(table.name.as_str())?.strip_prefix('.')?;
+            table.columns.iter()
+                .find(|referenced| referenced.name == column)
+                .map(|referenced| (index, referenced))
+        })
+        .ok_or_else(|| TransmutaError::InvalidArgument(format!(
+            "列'{}'引用的{}不存在，references应为\"表名.列名\"", col.name, reference
+        )))
 }
 
-// 生成随机UUID
-fn generate_random_uuid(rng: &mut StdRng) -> String {
-    use std::fmt::Write;
-    
-    let mut bytes = [0u8; 16];
-    rng.fill(&mut bytes[..]);
-    
-    // 设置版本(v4)和变体位
-    bytes[6] = (bytes[6] & 0x0f) | 0x40; // 版本4
-    bytes[8] = (bytes[8] & 0x3f) | 0x80; // 变体1
-    
-    let mut uuid = String::with_capacity(36);
+/// 检查表之间的引用关系，返回生成顺序：被引用的表排在引用它的表之前，其余按定义文件中的顺序
+fn table_order(tables: &[TableDefinition]) -> Result<Vec<usize>> {
+    let mut names = HashSet::new();
+    let mut dependencies: Vec<HashSet<usize>> = vec![HashSet::new(); tables.len()];
+    
+    for (index, table) in tables.iter().enumerate() {
+        if !names.insert(table.name.as_str()) {
+            return Err(TransmutaError::InvalidArgument(format!("表名'{}'重复", table.name)));
+        }
+        
+        let child_columns = table.columns.iter().filter(|col| col.cardinality.is_some()).count();
+        if child_columns > 1 {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "表'{}'最多只能有一列指定cardinality", table.name
+            )));
+        }
+        if child_columns == 1 && table.rows.is_some() {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "表'{}'有列指定了cardinality，行数由被引用表决定，不能再指定rows", table.name
+            )));
+        }
+        
+        for col in &table.columns {
+            let Some(reference) = &col.references else {
+                continue;
+            };
+            let (referenced_table, referenced) = resolve_reference(tables, col, reference)?;
+            if referenced_table == index {
+                return Err(TransmutaError::InvalidArgument(format!(
+                    "列'{}'不能引用所在的表'{}'", col.name, table.name
+                )));
+            }
+            if col.arrow_type() != referenced.arrow_type() {
+                return Err(TransmutaError::InvalidArgument(format!(
+                    "列'{}'的类型{}与引用的{}的类型{}不一致",
+                    col.name, col.arrow_type(), reference, referenced.arrow_type()
+                )));
+            }
+            dependencies[index].insert(referenced_table);
+        }
+    }
     
-    for (i, b) in bytes.iter().enumerate() {
-        // 在特定位置添加连字符
-        if i == 4 || i == 6 || i == 8 || i == 10 {
-            uuid.push('-');
+    let mut order = Vec::with_capacity(tables.len());
+    let mut done = vec![false; tables.len()];
+    while order.len() < tables.len() {
+        let next = (0..tables.len())
+            .find(|&index| !done[index] && dependencies[index].iter().all(|&dependency| done[dependency]));
+        match next {
+            Some(index) => {
+                done[index] = true;
+                order.push(index);
+            }
+            None => return Err(TransmutaError::InvalidArgument("表之间存在循环引用".to_string())),
         }
-        write!(&mut uuid, "{:02x}", b).unwrap();
     }
     
-    uuid
+    Ok(order)
 }
 
-/// 根据列定义生成随机数据
-pub fn generate_data(
-    schema_path: &Path,
-    schema_format: &SchemaFormat,
+/// 按生成计划分批生成一张表并流式写入输出文件，返回需要保留的列
+fn generate_table(
+    plan: &TablePlan,
+    keys: &HashMap<String, ArrayRef>,
     output_path: &Path,
     format: &OutputFormat,
-    rows: usize,
-    delimiter: char,
-    seed: Option<u64>,
-) -> Result<()> {
-    // 读取列定义
-    let column_defs = match schema_format {
-        SchemaFormat::Csv => read_schema_from_csv(schema_path, delimiter)?,
-        SchemaFormat::Json => read_schema_from_json(schema_path)?,
-    };
-    
-    info!("读取了{}个列定义", column_defs.len());
-    for (i, col) in column_defs.iter().enumerate() {
+    options: &GenerateOptions,
+    writer_options: &WriterOptions,
+) -> Result<HashMap<String, ArrayRef>> {
+    info!("读取了{}个列定义", plan.columns.len());
+    for (i, col) in plan.columns.iter().enumerate() {
         debug!("列 {}: {} ({})", i + 1, col.name, format!("{:?}", col.data_type));
     }
     
     // 创建Arrow Schema
-    let fields: Vec<Field> = column_defs.iter()
+    let fields: Vec<Field> = plan.columns.iter()
         .map(|col| {
-            let arrow_type = match col.data_type {
-                // 基本类型
-                DataType::String => arrow::datatypes::DataType::Utf8,
-                DataType::Integer => arrow::datatypes::DataType::Int32,
-                DataType::Float => arrow::datatypes::DataType::Float64,
-                DataType::Boolean => arrow::datatypes::DataType::Boolean,
-                
-                // 精确整数类型
-                DataType::Int8 => arrow::datatypes::DataType::Int8,
-                DataType::Int16 => arrow::datatypes::DataType::Int16,
-                DataType::Int32 => arrow::datatypes::DataType::Int32,
-                DataType::Int64 => arrow::datatypes::DataType::Int64,
-                DataType::UInt8 => arrow::datatypes::DataType::UInt8,
-                DataType::UInt16 => arrow::datatypes::DataType::UInt16,
-                DataType::UInt32 => arrow::datatypes::DataType::UInt32,
-                DataType::UInt64 => arrow::datatypes::DataType::UInt64,
-                
-                // 精确浮点数类型
-                DataType::Float32 => arrow::datatypes::DataType::Float32,
-                DataType::Float64 => arrow::datatypes::DataType::Float64,
-                
-                // 高精度数值类型 (用字符串表示)
-                DataType::Decimal | DataType::Decimal128 | DataType::Decimal256 => arrow::datatypes::DataType::Utf8,
-                
-                // 日期和时间类型
-                DataType::Date => arrow::datatypes::DataType::Date32,
-                DataType::Date32 => arrow::datatypes::DataType::Date32,
-                DataType::Timestamp => arrow::datatypes::DataType::Timestamp(TimeUnit::Millisecond, None),
-                DataType::Time32 => arrow::datatypes::DataType::Time32(TimeUnit::Millisecond),
-                DataType::Time64 => arrow::datatypes::DataType::Time64(TimeUnit::Nanosecond),
-                DataType::Interval => arrow::datatypes::DataType::Interval(IntervalUnit::MonthDayNano),
-                DataType::Duration => arrow::datatypes::DataType::Duration(TimeUnit::Nanosecond),
-                
-                // 二进制数据类型
-                DataType::Binary => arrow::datatypes::DataType::Binary,
-                DataType::FixedSizeBinary => arrow::datatypes::DataType::FixedSizeBinary(16), // 默认16字节
-                
-                // 特殊类型
-                DataType::Uuid => arrow::datatypes::DataType::Utf8,
-                DataType::Null => arrow::datatypes::DataType::Null,
-            };
-            Field::new(&col.name, arrow_type, true)
+            Field::new(&col.name, col.arrow_type(), true)
         })
         .collect();
     
     let schema = Arc::new(Schema::new(fields));
     
-    // 初始化随机数生成器
-    let seed_value = seed.unwrap_or_else(get_default_seed);
-    info!("使用随机种子: {}", seed_value);
-    let mut rng = StdRng::seed_from_u64(seed_value);
+    // 引用列从被引用列已生成的值中选取，指定了cardinality的引用列决定表的行数
+    let mut rng = StdRng::seed_from_u64(plan.seed);
+    let mut rows = plan.rows;
+    let mut columns = Vec::with_capacity(plan.columns.len());
+    for col in plan.columns {
+        let referenced_keys = || keys.get(col.references.as_deref().unwrap_or_default()).cloned()
+            .ok_or_else(|| TransmutaError::InvalidArgument(format!(
+                "列'{}'引用的{}尚未生成", col.name, col.references.as_deref().unwrap_or_default()
+            )));
+        let column = match (&col.references, &col.cardinality) {
+            (Some(_), Some(_)) => {
+                let child = ChildRows::try_new(col, referenced_keys()?, &mut rng)?;
+                rows = usize::try_from(child.rows()).map_err(|_| TransmutaError::InvalidArgument(format!(
+                    "列'{}'按cardinality生成的行数过多", col.name
+                )))?;
+                info!("按列'{}'的cardinality生成{}行", col.name, rows);
+                TableColumn::Child(child)
+            }
+            (Some(_), None) => TableColumn::Random(ColumnGenerator::try_new_reference(col, referenced_keys()?)?),
+            (None, _) => TableColumn::Random(ColumnGenerator::try_new(col, options.locale)?),
+        };
+        columns.push(column);
+    }
     
-    // 创建并填充数组
-    let mut arrays: Vec<Arc<dyn Array>> = Vec::new();
+    let mut unique_values: Vec<Option<UniqueValues>> = columns.iter()
+        .map(|column| match column {
+            TableColumn::Random(generator) if generator.is_unique() => Some(UniqueValues::default()),
+            _ => None,
+        })
+        .collect();
+    let mut retained: Vec<Option<Vec<ArrayRef>>> = plan.columns.iter()
+        .map(|col| plan.retained.contains(col.name.as_str()).then(Vec::new))
+        .collect();
     
-    for col in &column_defs {
-        match col.data_type {
-            // 基本类型
-            DataType::String => {
-                let mut builder = StringBuilder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_string(&mut rng, 5, 20));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Integer => {
-                let mut builder = Int32Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_integer(&mut rng, -1000, 1000));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Float => {
-                let mut builder = Float64Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_float(&mut rng, -1000.0, 1000.0));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Boolean => {
-                let mut builder = BooleanBuilder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_boolean(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            
-            // 精确整数类型
-            DataType::Int8 => {
-                let mut builder = Int8Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_int8(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Int16 => {
-                let mut builder = Int16Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_int16(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Int32 => {
-                let mut builder = Int32Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_int32(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Int64 => {
-                let mut builder = Int64Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_int64(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::UInt8 => {
-                let mut builder = UInt8Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_uint8(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::UInt16 => {
-                let mut builder = UInt16Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_uint16(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::UInt32 => {
-                let mut builder = UInt32Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_uint32(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::UInt64 => {
-                let mut builder = UInt64Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_uint64(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            
-            // 精确浮点数类型
-            DataType::Float32 => {
-                let mut builder = Float32Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_float32(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Float64 => {
-                let mut builder = Float64Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_float64(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            
-            // 高精度数值类型 (用字符串表示)
-            DataType::Decimal | DataType::Decimal128 | DataType::Decimal256 => {
-                let mut builder = StringBuilder::new();
-                for _ in 0..rows {
-                    builder.append_value(&generate_random_decimal(&mut rng, 6));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            
-            // 日期和时间类型
-            DataType::Date => {
-                let mut builder = Date32Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_date(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Date32 => {
-                let mut builder = Date32Builder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_date32(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Timestamp => {
-                let mut builder = TimestampMillisecondBuilder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_timestamp(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Time32 => {
-                let mut builder = Time32MillisecondBuilder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_time32(&mut rng, true)); // 使用毫秒精度
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Time64 => {
-                let mut builder = Time64NanosecondBuilder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_time64(&mut rng, true)); // 使用纳秒精度
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Interval => {
-                // Interval类型使用三个整数表示：月、日、纳秒
-                let mut builder = IntervalMonthDayNanoBuilder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_interval(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Duration => {
-                let mut builder = DurationNanosecondBuilder::new();
-                for _ in 0..rows {
-                    builder.append_value(generate_random_duration(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
+    let batch_size = options.batch_size.max(1);
+    let thread_count = utils::get_thread_count(options.threads);
+    
+    let pb = ProgressBar::new(rows as u64);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} 行 ({eta})")
+        .unwrap()
+        .progress_chars("#>-"));
+    
+    let mut writer = open_writer(output_path, format, schema.clone(), writer_options)?;
+    
+    // 每个数据块为批次序号、起始行和行数
+    let chunks = (0..rows.div_ceil(batch_size)).map(|chunk| {
+        let start = chunk * batch_size;
+        Ok((chunk, start, batch_size.min(rows - start)))
+    });
+    
+    run_pipeline(
+        chunks,
+        thread_count,
+        |(chunk, start, chunk_rows): (usize, usize, usize)| {
+            // 并行生成非unique列，unique列留到写入端生成
+            let mut rng = StdRng::seed_from_u64(derive_seed(plan.seed, chunk as u64));
+            let arrays = columns.iter()
+                .map(|column| match column {
+                    TableColumn::Child(child) => child.generate(start as u64, chunk_rows).map(Some),
+                    TableColumn::Random(generator) if generator.is_unique() => Ok(None),
+                    TableColumn::Random(generator) => generator.generate(chunk_rows, &mut rng, None).map(Some),
+                })
+                .collect::<Result<Vec<Option<ArrayRef>>>>()?;
+            Ok((chunk, chunk_rows, arrays))
+        },
+        |(chunk, chunk_rows, arrays)| {
+            // unique列使用另一个派生的种子，按批次顺序生成
+            let mut rng = StdRng::seed_from_u64(derive_seed(!plan.seed, chunk as u64));
+            let arrays = arrays.into_iter()
+                .zip(&columns)
+                .zip(unique_values.iter_mut())
+                .map(|((array, column), seen)| match (array, column) {
+                    (Some(array), _) => Ok(array),
+                    (None, TableColumn::Random(generator)) => generator.generate(chunk_rows, &mut rng, seen.as_mut()),
+                    (None, TableColumn::Child(_)) => unreachable!("引用列已在并行阶段生成"),
+                })
+                .collect::<Result<Vec<ArrayRef>>>()?;
             
-            // 二进制数据类型
-            DataType::Binary => {
-                let mut builder = BinaryBuilder::new();
-                for _ in 0..rows {
-                    builder.append_value(&generate_random_binary(&mut rng, 4, 20));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::FixedSizeBinary => {
-                let mut builder = FixedSizeBinaryBuilder::new(16); // 默认16字节
-                for _ in 0..rows {
-                    let data = generate_random_fixed_size_binary(&mut rng, 16);
-                    builder.append_value(&data).unwrap();
+            for (array, kept) in arrays.iter().zip(retained.iter_mut()) {
+                if let Some(kept) = kept {
+                    kept.push(array.clone());
                 }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
+            }
             
-            // 特殊类型
-            DataType::Uuid => {
-                let mut builder = StringBuilder::new();
-                for _ in 0..rows {
-                    builder.append_value(&generate_random_uuid(&mut rng));
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-            DataType::Null => {
-                // Null类型，所有值都是null
-                let mut builder = NullBuilder::new();
-                for _ in 0..rows {
-                    builder.append_null();
-                }
-                arrays.push(Arc::new(builder.finish()) as Arc<dyn Array>);
-            },
-        }
-    }
-    
-    // 创建RecordBatch
-    let record_batch = RecordBatch::try_new(schema, arrays)?;
-    
+            let record_batch = RecordBatch::try_new(schema.clone(), arrays)?;
+            writer.write(&record_batch)?;
+            pb.inc(chunk_rows as u64);
+            Ok(())
+        },
+    )?;
+    
+    writer.close()?;
+    pb.finish_with_message("数据生成完成");
     info!("生成了{}行随机数据", rows);
     
-    // 保存到指定格式
-    super::common::save_data(&record_batch, output_path, format, delimiter)?;
+    // 合并需要保留的列
+    let mut generated = HashMap::new();
+    for (col, kept) in plan.columns.iter().zip(retained) {
+        if let Some(kept) = kept {
+            let array = if kept.is_empty() {
+                new_empty_array(&col.arrow_type())
+            } else {
+                concat(&kept.iter().map(|array| array.as_ref()).collect::<Vec<_>>())?
+            };
+            generated.insert(col.name.clone(), array);
+        }
+    }
     
-    Ok(())
-} 
\ No newline at end of file
+    Ok(generated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// 测试用的临时目录，每个测试使用不同的名称
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("transmuta-datagen-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// id的取值范围只比行数略大，后面的批次必须避开前面批次生成过的值
+    fn columns() -> Vec<ColumnDefinition> {
+        let mut id = ColumnDefinition::new("id".to_string(), DataType::Int64);
+        id.unique = true;
+        id.min = Some(Value::from(1));
+        id.max = Some(Value::from(2_100));
+        let mut email = ColumnDefinition::new("email".to_string(), DataType::Email);
+        email.unique = true;
+        let mut score = ColumnDefinition::new("score".to_string(), DataType::Float64);
+        score.null_ratio = Some(0.2);
+        vec![id, email, ColumnDefinition::new("name".to_string(), DataType::Name), score]
+    }
+
+    /// 用指定线程数生成2000行CSV，每批97行，返回输出的内容
+    fn generate(dir: &Path, threads: usize) -> String {
+        let columns = columns();
+        let plan = TablePlan { columns: &columns, rows: 2_000, retained: HashSet::new(), seed: 42 };
+        let options = GenerateOptions { rows: 2_000, batch_size: 97, threads: Some(threads), seed: Some(42), locale: Locale::Zh };
+        let output = dir.join(format!("threads{}.csv", threads));
+        generate_table(&plan, &HashMap::new(), &output, &OutputFormat::Csv, &options, &WriterOptions::new(',')).unwrap();
+        std::fs::read_to_string(output).unwrap()
+    }
+
+    #[test]
+    fn output_is_independent_of_thread_count() {
+        let dir = temp_dir("threads");
+        let single = generate(&dir, 1);
+        assert_eq!(generate(&dir, 8), single);
+        assert_eq!(generate(&dir, 3), single);
+
+        let rows: Vec<Vec<&str>> = single.lines().skip(1).map(|line| line.split(',').collect()).collect();
+        assert_eq!(rows.len(), 2_000);
+        for col_idx in 0..2 {
+            let values: HashSet<&str> = rows.iter().map(|row| row[col_idx]).collect();
+            assert_eq!(values.len(), rows.len(), "第{}列有重复值", col_idx + 1);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
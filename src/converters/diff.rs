@@ -1,8 +1,16 @@
+use crate::cli::OutputFormat;
+use crate::utils;
+use super::common::{array_value_to_string, ConvertOptions};
+use super::reader::read_batches;
+use super::writer::{open_writer, WriterOptions};
 use anyhow::{Result, anyhow};
-use log::{info, warn};
-use std::collections::HashSet;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
+use arrow::array::{ArrayRef, StringBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::io::{BufRead, Write};
 use std::path::Path;
 
 /// DiffOutputMode 定义了 diff 操作的输出模式
@@ -43,9 +51,14 @@ pub struct DiffOptions<'a> {
 
 /// 标准化字段名称，应用忽略选项
 fn normalize_field(field: &str, options: &DiffOptions) -> String {
-    let mut result = field.to_string();
+    normalize_text(field, options.ignore_case, options.ignore_whitespace)
+}
+
+/// 标准化文本：去除空白字符（或仅修剪两端）并按需转换为小写
+fn normalize_text(text: &str, ignore_case: bool, ignore_whitespace: bool) -> String {
+    let mut result = text.to_string();
     
-    if options.ignore_whitespace {
+    if ignore_whitespace {
         // 移除所有空白字符
         result = result.chars()
             .filter(|c| !c.is_whitespace())
@@ -55,7 +68,7 @@ fn normalize_field(field: &str, options: &DiffOptions) -> String {
         result = result.trim().to_string();
     }
     
-    if options.ignore_case {
+    if ignore_case {
         // 转换为小写
         result = result.to_lowercase();
     }
@@ -65,10 +78,8 @@ fn normalize_field(field: &str, options: &DiffOptions) -> String {
 
 /// 从文件读取字段，返回排序后的字段集合
 fn read_fields_from_file<'a>(file_path: &Path, options: &DiffOptions<'a>) -> Result<Vec<String>> {
-    let file = File::open(file_path)
+    let reader = utils::create_buf_reader(file_path)
         .map_err(|e| anyhow!("无法打开文件 {}: {}", file_path.display(), e))?;
-    
-    let reader = BufReader::new(file);
     let mut fields = Vec::new();
     
     if options.one_field_per_line {
@@ -160,11 +171,12 @@ fn compute_diff(fields1: &[String], fields2: &[String]) -> (Vec<String>, Vec<Str
 
 /// 将字段集合写入输出文件
 fn write_fields_to_file(fields: &[String], output_path: &Path, delimiter: char) -> Result<()> {
-    let mut file = File::create(output_path)
+    let mut file = utils::create_buf_writer(output_path)
         .map_err(|e| anyhow!("无法创建输出文件 {}: {}", output_path.display(), e))?;
     
     let output = fields.join(&delimiter.to_string());
     file.write_all(output.as_bytes())
+        .and_then(|_| file.flush())
         .map_err(|e| anyhow!("写入输出文件 {} 失败: {}", output_path.display(), e))?;
     
     Ok(())
@@ -181,7 +193,7 @@ fn generate_diff_report(
     common_count: usize,
     output_path: &Path,
 ) -> Result<()> {
-    let mut file = File::create(output_path)
+    let mut file = utils::create_buf_writer(output_path)
         .map_err(|e| anyhow!("无法创建报告文件 {}: {}", output_path.display(), e))?;
     
     // 获取文件名
@@ -250,6 +262,7 @@ fn generate_diff_report(
         writeln!(file, "- {}", field)?;
     }
     
+    file.flush()?;
     info!("详细的差异报告已写入: {}", output_path.display());
     
     Ok(())
@@ -345,4 +358,408 @@ pub fn diff_fields<'a>(
     info!("输出字段数: {}", output_fields.len());
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// 用于记录比较的选项
+#[derive(Debug, Clone)]
+pub struct RecordDiffOptions<'a> {
+    /// 用于匹配记录的键列
+    pub key_columns: Vec<String>,
+    /// 比较时忽略的列
+    pub ignore_columns: Vec<String>,
+    /// 数值比较的容差
+    pub tolerance: f64,
+    /// 是否忽略大小写
+    pub ignore_case: bool,
+    /// 是否忽略空白字符
+    pub ignore_whitespace: bool,
+    /// CSV输入和输出的分隔符
+    pub delimiter: char,
+    /// 差异结果的输出格式
+    pub format: OutputFormat,
+    /// 详细报告输出路径
+    pub report_path: Option<&'a Path>,
+}
+
+/// 读取到内存中的数据表，所有值以字符串表示，空值为空字符串
+struct Table {
+    columns: Vec<String>,
+    /// 每个单元格的文本形式，空值为None，与空字符串区分
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl Table {
+    fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == name)
+    }
+}
+
+/// 通过对应格式的读取器加载数据文件
+fn load_table(path: &Path, delimiter: char) -> Result<Table> {
+    // 不推断类型，数值在比较时再解析，避免两侧推断结果不同
+    let options = ConvertOptions {
+        delimiter,
+        ..Default::default()
+    };
+    let (schema, batches) = read_batches(path, &options)
+        .map_err(|e| anyhow!("读取文件 {} 失败: {}", path.display(), e))?;
+    
+    let columns: Vec<String> = schema.fields().iter().map(|f| f.name().clone()).collect();
+    let mut rows = Vec::new();
+    for batch in &batches {
+        for row_idx in 0..batch.num_rows() {
+            rows.push(batch.columns().iter()
+                .map(|column| column.is_valid(row_idx).then(|| array_value_to_string(column, row_idx)))
+                .collect());
+        }
+    }
+    
+    Ok(Table { columns, rows })
+}
+
+/// 比较两个值：空值只与空值相等（空值与空字符串不同）；都是有限数值且至少一侧带小数部分或指数时按容差比较，
+/// 否则按标准化后的文本比较。整数按文本精确比较，因此`00123`与`123`不同，超出浮点数精度的大整数也不会被视为相等
+fn values_equal(left: Option<&str>, right: Option<&str>, options: &RecordDiffOptions) -> bool {
+    let (left, right) = match (left, right) {
+        (Some(left), Some(right)) => (left, right),
+        (None, None) => return true,
+        _ => return false,
+    };
+    let (left_trimmed, right_trimmed) = (left.trim(), right.trim());
+    let is_fractional = |value: &str| value.contains(['.', 'e', 'E']);
+    if is_fractional(left_trimmed) || is_fractional(right_trimmed) {
+        if let (Ok(a), Ok(b)) = (left_trimmed.parse::<f64>(), right_trimmed.parse::<f64>()) {
+            if a.is_finite() && b.is_finite() {
+                return (a - b).abs() <= options.tolerance;
+            }
+        }
+    }
+    
+    normalize_text(left, options.ignore_case, options.ignore_whitespace)
+        == normalize_text(right, options.ignore_case, options.ignore_whitespace)
+}
+
+/// 生成记录的键，多个键列的标准化值以不可见分隔符连接，空值用NUL字符表示以便与空字符串区分
+fn record_key(row: &[Option<String>], key_indices: &[usize], options: &RecordDiffOptions) -> String {
+    key_indices.iter()
+        .map(|&idx| match &row[idx] {
+            Some(value) => normalize_text(value, options.ignore_case, options.ignore_whitespace),
+            None => "\u{0}".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\u{1f}")
+}
+
+/// 按键建立记录索引，键重复时报错
+fn index_by_key(table: &Table, key_indices: &[usize], file_name: &str, options: &RecordDiffOptions) -> Result<HashMap<String, usize>> {
+    let mut index = HashMap::with_capacity(table.rows.len());
+    for (row_idx, row) in table.rows.iter().enumerate() {
+        let key = record_key(row, key_indices, options);
+        if let Some(first_idx) = index.insert(key, row_idx) {
+            let values: Vec<&str> = key_indices.iter().map(|&idx| row[idx].as_deref().unwrap_or("NULL")).collect();
+            return Err(anyhow!(
+                "{}中第{}条和第{}条记录的键重复: {}", file_name, first_idx + 1, row_idx + 1, values.join(", ")
+            ));
+        }
+    }
+    Ok(index)
+}
+
+/// 记录差异的类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RecordDiffKind {
+    OnlyLeft,
+    OnlyRight,
+    Changed,
+}
+
+impl RecordDiffKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RecordDiffKind::OnlyLeft => "only_left",
+            RecordDiffKind::OnlyRight => "only_right",
+            RecordDiffKind::Changed => "changed",
+        }
+    }
+}
+
+/// 一条差异结果：仅在一侧存在的记录，或存在差异的一个单元格
+struct RecordDiffEntry {
+    kind: RecordDiffKind,
+    key_values: Vec<Option<String>>,
+    column: Option<String>,
+    left_value: Option<String>,
+    right_value: Option<String>,
+}
+
+/// 记录比较的统计信息
+#[derive(Debug, Default)]
+struct RecordDiffSummary {
+    left_rows: usize,
+    right_rows: usize,
+    common_rows: usize,
+    changed_rows: usize,
+    changed_cells: usize,
+    only_left_rows: usize,
+    only_right_rows: usize,
+    compared_columns: Vec<String>,
+    only_left_columns: Vec<String>,
+    only_right_columns: Vec<String>,
+    /// 每列的差异单元格数，按比较列的顺序
+    column_diff_counts: Vec<usize>,
+}
+
+/// 将差异结果写入输出文件，键列输出为key_前缀的列，避免与diff_type、column等固定列重名
+fn write_record_diff(entries: &[RecordDiffEntry], key_columns: &[String], output_path: &Path, options: &RecordDiffOptions) -> Result<()> {
+    let mut fields = vec![Field::new("diff_type", DataType::Utf8, false)];
+    fields.extend(key_columns.iter().map(|name| Field::new(format!("key_{}", name), DataType::Utf8, true)));
+    fields.push(Field::new("column", DataType::Utf8, true));
+    fields.push(Field::new("left_value", DataType::Utf8, true));
+    fields.push(Field::new("right_value", DataType::Utf8, true));
+    let schema = Arc::new(Schema::new(fields));
+    
+    let mut kind_builder = StringBuilder::new();
+    let mut key_builders: Vec<StringBuilder> = key_columns.iter().map(|_| StringBuilder::new()).collect();
+    let mut column_builder = StringBuilder::new();
+    let mut left_builder = StringBuilder::new();
+    let mut right_builder = StringBuilder::new();
+    
+    for entry in entries {
+        kind_builder.append_value(entry.kind.as_str());
+        for (builder, value) in key_builders.iter_mut().zip(&entry.key_values) {
+            builder.append_option(value.as_deref());
+        }
+        column_builder.append_option(entry.column.as_deref());
+        left_builder.append_option(entry.left_value.as_deref());
+        right_builder.append_option(entry.right_value.as_deref());
+    }
+    
+    let mut arrays: Vec<ArrayRef> = vec![Arc::new(kind_builder.finish())];
+    arrays.extend(key_builders.into_iter().map(|mut b| Arc::new(b.finish()) as ArrayRef));
+    arrays.push(Arc::new(column_builder.finish()));
+    arrays.push(Arc::new(left_builder.finish()));
+    arrays.push(Arc::new(right_builder.finish()));
+    
+    let batch = RecordBatch::try_new(schema.clone(), arrays)?;
+    let mut writer = open_writer(output_path, &options.format, schema, &WriterOptions::new(options.delimiter))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    
+    Ok(())
+}
+
+/// 生成记录比较的详细报告
+fn generate_record_diff_report(
+    input_file1: &Path,
+    input_file2: &Path,
+    summary: &RecordDiffSummary,
+    options: &RecordDiffOptions,
+    output_path: &Path,
+) -> Result<()> {
+    let mut file = utils::create_buf_writer(output_path)
+        .map_err(|e| anyhow!("无法创建报告文件 {}: {}", output_path.display(), e))?;
+    
+    writeln!(file, "记录差异比较报告")?;
+    writeln!(file, "==================")?;
+    writeln!(file)?;
+    
+    writeln!(file, "文件路径:")?;
+    writeln!(file, "左侧: {}", input_file1.display())?;
+    writeln!(file, "右侧: {}", input_file2.display())?;
+    writeln!(file)?;
+    
+    writeln!(file, "比较设置")?;
+    writeln!(file, "--------")?;
+    writeln!(file, "键列: {}", options.key_columns.join(", "))?;
+    if !options.ignore_columns.is_empty() {
+        writeln!(file, "忽略的列: {}", options.ignore_columns.join(", "))?;
+    }
+    writeln!(file, "数值容差: {}", options.tolerance)?;
+    writeln!(file, "忽略大小写: {}", if options.ignore_case { "是" } else { "否" })?;
+    writeln!(file, "忽略空白字符: {}", if options.ignore_whitespace { "是" } else { "否" })?;
+    writeln!(file)?;
+    
+    writeln!(file, "差异统计")?;
+    writeln!(file, "--------")?;
+    writeln!(file, "左侧记录数: {}", summary.left_rows)?;
+    writeln!(file, "右侧记录数: {}", summary.right_rows)?;
+    writeln!(file, "两侧共有记录数: {}", summary.common_rows)?;
+    writeln!(file, "存在差异的记录数: {}", summary.changed_rows)?;
+    writeln!(file, "存在差异的单元格数: {}", summary.changed_cells)?;
+    writeln!(file, "仅在左侧的记录数: {}", summary.only_left_rows)?;
+    writeln!(file, "仅在右侧的记录数: {}", summary.only_right_rows)?;
+    writeln!(file)?;
+    
+    writeln!(file, "各列差异单元格数")?;
+    writeln!(file, "----------------")?;
+    for (column, count) in summary.compared_columns.iter().zip(&summary.column_diff_counts) {
+        writeln!(file, "- {}: {}", column, count)?;
+    }
+    if summary.compared_columns.is_empty() {
+        writeln!(file, "(无)")?;
+    }
+    
+    if !summary.only_left_columns.is_empty() || !summary.only_right_columns.is_empty() {
+        writeln!(file)?;
+        writeln!(file, "未参与比较的列")?;
+        writeln!(file, "--------------")?;
+        for column in &summary.only_left_columns {
+            writeln!(file, "- {}（仅在左侧）", column)?;
+        }
+        for column in &summary.only_right_columns {
+            writeln!(file, "- {}（仅在右侧）", column)?;
+        }
+    }
+    
+    file.flush()?;
+    info!("详细的差异报告已写入: {}", output_path.display());
+    
+    Ok(())
+}
+
+/// 按键列匹配两个数据文件的记录，输出仅在一侧存在的记录和存在差异的单元格
+pub fn diff_records<'a>(
+    input_file1: &Path,
+    input_file2: &Path,
+    output_path: &Path,
+    options: RecordDiffOptions<'a>,
+) -> Result<()> {
+    info!("正在按键列 {} 比较文件 {} 和 {} 的记录差异",
+          options.key_columns.join(", "), input_file1.display(), input_file2.display());
+    
+    let left = load_table(input_file1, options.delimiter)?;
+    let right = load_table(input_file2, options.delimiter)?;
+    info!("左侧记录数: {}, 右侧记录数: {}", left.rows.len(), right.rows.len());
+    
+    // 键列必须在两侧都存在
+    let mut left_key_indices = Vec::new();
+    let mut right_key_indices = Vec::new();
+    for key in &options.key_columns {
+        left_key_indices.push(left.column_index(key)
+            .ok_or_else(|| anyhow!("键列'{}'在文件 {} 中不存在", key, input_file1.display()))?);
+        right_key_indices.push(right.column_index(key)
+            .ok_or_else(|| anyhow!("键列'{}'在文件 {} 中不存在", key, input_file2.display()))?);
+    }
+    
+    for column in &options.ignore_columns {
+        if left.column_index(column).is_none() && right.column_index(column).is_none() {
+            warn!("忽略的列'{}'在两个文件中都不存在", column);
+        }
+    }
+    
+    // 确定参与比较的列：两侧共有、不是键列且未被忽略，按左侧的列顺序
+    let is_excluded = |column: &String| {
+        options.key_columns.contains(column) || options.ignore_columns.contains(column)
+    };
+    let mut summary = RecordDiffSummary {
+        left_rows: left.rows.len(),
+        right_rows: right.rows.len(),
+        ..Default::default()
+    };
+    let mut compared = Vec::new();
+    for (left_idx, column) in left.columns.iter().enumerate() {
+        if is_excluded(column) {
+            continue;
+        }
+        match right.column_index(column) {
+            Some(right_idx) => {
+                compared.push((left_idx, right_idx));
+                summary.compared_columns.push(column.clone());
+            }
+            None => summary.only_left_columns.push(column.clone()),
+        }
+    }
+    summary.only_right_columns = right.columns.iter()
+        .filter(|column| !is_excluded(column) && left.column_index(column).is_none())
+        .cloned()
+        .collect();
+    summary.column_diff_counts = vec![0; compared.len()];
+    
+    if !summary.only_left_columns.is_empty() {
+        warn!("仅在左侧存在的列不参与比较: {}", summary.only_left_columns.join(", "));
+    }
+    if !summary.only_right_columns.is_empty() {
+        warn!("仅在右侧存在的列不参与比较: {}", summary.only_right_columns.join(", "));
+    }
+    debug!("参与比较的列: {:?}", summary.compared_columns);
+    
+    // 以键建立索引
+    let left_index = index_by_key(&left, &left_key_indices, "左侧文件", &options)?;
+    let right_index = index_by_key(&right, &right_key_indices, "右侧文件", &options)?;
+    
+    let mut entries = Vec::new();
+    
+    // 按左侧顺序输出仅在左侧的记录和存在差异的记录
+    for left_row in &left.rows {
+        let key_values: Vec<Option<String>> = left_key_indices.iter().map(|&idx| left_row[idx].clone()).collect();
+        let key = record_key(left_row, &left_key_indices, &options);
+        
+        let right_row = match right_index.get(&key) {
+            Some(&right_idx) => &right.rows[right_idx],
+            None => {
+                summary.only_left_rows += 1;
+                entries.push(RecordDiffEntry {
+                    kind: RecordDiffKind::OnlyLeft,
+                    key_values,
+                    column: None,
+                    left_value: None,
+                    right_value: None,
+                });
+                continue;
+            }
+        };
+        
+        summary.common_rows += 1;
+        let mut row_changed = false;
+        for (col_pos, &(left_idx, right_idx)) in compared.iter().enumerate() {
+            let (left_value, right_value) = (&left_row[left_idx], &right_row[right_idx]);
+            if values_equal(left_value.as_deref(), right_value.as_deref(), &options) {
+                continue;
+            }
+            
+            row_changed = true;
+            summary.changed_cells += 1;
+            summary.column_diff_counts[col_pos] += 1;
+            entries.push(RecordDiffEntry {
+                kind: RecordDiffKind::Changed,
+                key_values: key_values.clone(),
+                column: Some(summary.compared_columns[col_pos].clone()),
+                left_value: left_value.clone(),
+                right_value: right_value.clone(),
+            });
+        }
+        if row_changed {
+            summary.changed_rows += 1;
+        }
+    }
+    
+    // 按右侧顺序输出仅在右侧的记录
+    for right_row in &right.rows {
+        let key = record_key(right_row, &right_key_indices, &options);
+        if !left_index.contains_key(&key) {
+            summary.only_right_rows += 1;
+            entries.push(RecordDiffEntry {
+                kind: RecordDiffKind::OnlyRight,
+                key_values: right_key_indices.iter().map(|&idx| right_row[idx].clone()).collect(),
+                column: None,
+                left_value: None,
+                right_value: None,
+            });
+        }
+    }
+    
+    info!("两侧共有记录数: {}", summary.common_rows);
+    info!("存在差异的记录数: {}（{}个单元格）", summary.changed_rows, summary.changed_cells);
+    info!("仅在左侧的记录数: {}", summary.only_left_rows);
+    info!("仅在右侧的记录数: {}", summary.only_right_rows);
+    
+    write_record_diff(&entries, &options.key_columns, output_path, &options)?;
+    
+    if let Some(report_path) = options.report_path {
+        generate_record_diff_report(input_file1, input_file2, &summary, &options, report_path)?;
+    }
+    
+    info!("记录差异比较完成，结果已写入 {}", output_path.display());
+    
+    Ok(())
+}
@@ -1,18 +1,78 @@
 use crate::cli::OutputFormat;
 use crate::error::{Result, TransmutaError};
 use crate::utils;
-use calamine::{open_workbook, Reader, Xlsx, DataType as ExcelDataType};
+use super::common::{format_iso_duration, ConvertOptions};
+use super::infer::{self, CastFailure, CastFailureLog, TypedBatchBuilder};
+use arrow::array::{ArrayRef, Date32Array, DurationMillisecondArray, TimestampMillisecondArray};
+use super::pipeline::run_pipeline;
+use super::reader::RecordBatches;
+use super::writer::open_writer;
+use calamine::{Ods, Range, Reader, Rows, Sheets, Xls, Xlsb, Xlsx, DataType as ExcelDataType};
 use std::path::Path;
-use log::{info, debug};
-use arrow::array::*;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use log::{info, warn, debug};
 use arrow::datatypes::*;
-use arrow::record_batch::RecordBatch;
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
 use indicatif::{ProgressBar, ProgressStyle};
-use rayon::prelude::*;
 use std::time::Instant;
 use std::sync::Arc;
+use chrono::{Days, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// 将Excel日期序列号转换为日期时间，精确到毫秒
+///
+/// 1900日期系统以1899-12-30为第0天，并沿用Lotus 1-2-3把1900年当作闰年的错误：
+/// 序列号60对应不存在的1900-02-29，60之前的序列号需要多加一天。
+/// 1904日期系统的序列号由calamine在读取时按工作簿的date1904设置换算为1900日期系统：
+/// xlsx读取workbook.xml的date1904属性，xls读取1904记录，xlsb读取BrtWbProp记录，
+/// 因此这里不能再次换算；ods中的日期以ISO文本保存，不经过序列号。
+fn excel_serial_to_datetime(serial: f64) -> Option<NaiveDateTime> {
+    if !serial.is_finite() || serial < 0.0 {
+        return None;
+    }
+    
+    let total_ms = (serial * MS_PER_DAY as f64).round() as i64;
+    let mut days = total_ms.div_euclid(MS_PER_DAY);
+    let ms_of_day = total_ms.rem_euclid(MS_PER_DAY);
+    
+    if days < 60 {
+        days += 1;
+    }
+    
+    let date = NaiveDate::from_ymd_opt(1899, 12, 30)?.checked_add_days(Days::new(days as u64))?;
+    let time = NaiveTime::from_num_seconds_from_midnight_opt(
+        (ms_of_day / 1000) as u32,
+        (ms_of_day % 1000) as u32 * 1_000_000,
+    )?;
+    Some(date.and_time(time))
+}
+
+/// 一天的毫秒数
+const MS_PER_DAY: i64 = 86_400_000;
+
+/// 格式化Excel日期时间单元格：纯日期输出YYYY-MM-DD，带时间输出YYYY-MM-DD HH:MM:SS[.fff]，
+/// 小于1的序列号只表示一天中的时间，输出HH:MM:SS[.fff]
+fn format_excel_datetime(serial: f64) -> String {
+    let datetime = match excel_serial_to_datetime(serial) {
+        Some(datetime) => datetime,
+        None => return serial.to_string(),
+    };
+    
+    let time = datetime.time();
+    let time_format = if time.nanosecond() == 0 { "%H:%M:%S" } else { "%H:%M:%S%.3f" };
+    
+    if serial < 1.0 {
+        time.format(time_format).to_string()
+    } else if time == NaiveTime::MIN {
+        datetime.format("%Y-%m-%d").to_string()
+    } else {
+        format!("{} {}", datetime.format("%Y-%m-%d"), time.format(time_format))
+    }
+}
 
 /// 将Excel单元格数据转换为字符串
+///
+/// 日期时间输出为ISO格式，持续时间输出为ISO 8601持续时间（如PT1H30M），以便类型推断识别
 fn cell_to_string(cell: &ExcelDataType) -> String {
     match cell {
         ExcelDataType::Empty => String::new(),
@@ -20,56 +80,364 @@ fn cell_to_string(cell: &ExcelDataType) -> String {
         ExcelDataType::Float(f) => f.to_string(),
         ExcelDataType::Int(i) => i.to_string(),
         ExcelDataType::Bool(b) => if *b { "true".to_string() } else { "false".to_string() },
-        ExcelDataType::DateTime(dt) => {
-            // 将Excel日期时间转换为字符串 (Excel日期是从1900-01-01开始的天数)
-            // 这里简化处理，实际应用中可能需要更精确的转换
-            let days_since_1900 = *dt;
-            format!("{:.6}", days_since_1900) // 以浮点数形式保存
-        },
+        ExcelDataType::DateTime(serial) => format_excel_datetime(*serial),
         ExcelDataType::Error(_) => "[ERROR]".to_string(),
-        ExcelDataType::Duration(d) => format!("{:.6}", d),
+        ExcelDataType::Duration(days) => {
+            let ms = (days * MS_PER_DAY as f64).round() as i64;
+            format_iso_duration(ms, &TimeUnit::Millisecond)
+        },
         ExcelDataType::DateTimeIso(s) => s.clone(),
         ExcelDataType::DurationIso(s) => s.clone(),
     }
 }
 
+/// 按输出列的顺序把一行中选择的单元格转换为字符串
+fn project<'a>(row: &'a [ExcelDataType], columns: &'a [usize]) -> impl Iterator<Item = String> + 'a {
+    columns.iter().map(move |&index| row.get(index).map_or_else(String::new, cell_to_string))
+}
+
+/// 数据行中所有非空单元格都是日期时间或都是持续时间的列，直接按单元格的值构建对应的Arrow类型，
+/// 不经过字符串和类型推断：不含时间部分的日期为Date32，含时间部分的为毫秒时间戳，持续时间为毫秒持续时间。
+/// 只表示一天中时间（序列号小于1）的单元格和其他单元格一样按字符串处理
+fn native_column_type<'a>(cells: impl Iterator<Item = &'a ExcelDataType>) -> Option<DataType> {
+    let (mut dates, mut datetimes, mut durations) = (0, 0, 0);
+    for cell in cells {
+        match cell {
+            ExcelDataType::Empty => {}
+            ExcelDataType::DateTime(serial) if *serial >= 1.0 => match excel_serial_to_datetime(*serial) {
+                Some(datetime) if datetime.time() == NaiveTime::MIN => dates += 1,
+                Some(_) => datetimes += 1,
+                None => return None,
+            },
+            ExcelDataType::Duration(_) => durations += 1,
+            _ => return None,
+        }
+    }
+    
+    match (dates, datetimes, durations) {
+        (0, 0, 0) => None,
+        (_, 0, 0) => Some(DataType::Date32),
+        (_, _, 0) => Some(DataType::Timestamp(TimeUnit::Millisecond, None)),
+        (0, 0, _) => Some(DataType::Duration(TimeUnit::Millisecond)),
+        _ => None,
+    }
+}
+
+/// 由日期时间或持续时间单元格构建native_column_type确定的类型的数组，其他单元格写为空值
+fn native_array<'a>(data_type: &DataType, cells: impl Iterator<Item = Option<&'a ExcelDataType>>) -> ArrayRef {
+    let serials = cells.map(|cell| match cell {
+        Some(ExcelDataType::DateTime(serial)) | Some(ExcelDataType::Duration(serial)) => Some(*serial),
+        _ => None,
+    });
+    match data_type {
+        DataType::Date32 => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+            Arc::new(serials
+                .map(|serial| serial.and_then(excel_serial_to_datetime))
+                .map(|datetime| datetime.map(|datetime| (datetime.date() - epoch).num_days() as i32))
+                .collect::<Date32Array>())
+        }
+        DataType::Timestamp(_, _) => Arc::new(serials
+            .map(|serial| serial.and_then(excel_serial_to_datetime))
+            .map(|datetime| datetime.map(|datetime| datetime.and_utc().timestamp_millis()))
+            .collect::<TimestampMillisecondArray>()),
+        _ => Arc::new(serials
+            .map(|days| days.map(|days| (days * MS_PER_DAY as f64).round() as i64))
+            .collect::<DurationMillisecondArray>()),
+    }
+}
+
+/// 把工作表的数据行构建为RecordBatch：日期时间和持续时间列直接构建，其余列转换为字符串后按列类型解析
+#[derive(Debug, Clone)]
+struct SheetBatchBuilder {
+    schema: SchemaRef,
+    /// 每个输出列在工作表行中的位置
+    columns: Vec<usize>,
+    /// 每个输出列直接构建的类型，为None的列按字符串解析
+    native_types: Vec<Option<DataType>>,
+    /// 按字符串解析的列在工作表行中的位置
+    text_columns: Vec<usize>,
+    /// 按字符串解析的列的构建器
+    text_builder: TypedBatchBuilder,
+}
+
+impl SheetBatchBuilder {
+    /// 把一块数据行构建为批次，同时返回无法转换的单元格。first_row_number为第一行在工作表中的行号
+    fn build(&self, first_row_number: usize, rows: &[&[ExcelDataType]]) -> Result<(RecordBatch, Vec<CastFailure>)> {
+        let (text_arrays, failures) = if self.text_columns.is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            let (batch, failures) = self.text_builder.build(
+                rows.iter().map(|row| project(row, &self.text_columns)),
+                first_row_number,
+            )?;
+            (batch.columns().to_vec(), failures)
+        };
+        
+        let mut text_arrays = text_arrays.into_iter();
+        let arrays: Vec<ArrayRef> = self.columns.iter().zip(&self.native_types)
+            .map(|(&column, native_type)| match native_type {
+                Some(data_type) => native_array(data_type, rows.iter().map(|row| row.get(column))),
+                None => text_arrays.next().expect("按字符串解析的列数与构建器的列数一致"),
+            })
+            .collect();
+        
+        let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+        Ok((RecordBatch::try_new_with_options(self.schema.clone(), arrays, &options)?, failures))
+    }
+}
+
+/// Excel工作表的选择方式
+#[derive(Debug, Clone)]
+pub enum SheetSelection {
+    /// 第一个工作表
+    First,
+    /// 按名称或序号（从1开始）选择一个工作表
+    Named(String),
+    /// 所有工作表，每个工作表输出一个文件
+    All,
+}
+
+/// 工作簿的实际文件类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorkbookKind {
+    /// Office Open XML工作簿（.xlsx、.xlsm）
+    Xlsx,
+    /// Excel 97-2003二进制工作簿（.xls）
+    Xls,
+    /// Excel二进制工作簿（.xlsb）
+    Xlsb,
+    /// OpenDocument电子表格（.ods）
+    Ods,
+}
+
+impl std::fmt::Display for WorkbookKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkbookKind::Xlsx => write!(f, "xlsx"),
+            WorkbookKind::Xls => write!(f, "xls"),
+            WorkbookKind::Xlsb => write!(f, "xlsb"),
+            WorkbookKind::Ods => write!(f, "ods"),
+        }
+    }
+}
+
+impl WorkbookKind {
+    /// 根据文件头部字节判断实际类型，扩展名只用于区分同为ZIP容器的xlsx和xlsb
+    pub fn detect(header: &[u8], ext: &str) -> Option<Self> {
+        // OLE复合文档（.xls）
+        const CFB_MAGIC: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+        // ZIP容器（.xlsx、.xlsm、.xlsb、.ods）
+        const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+        // ODS文件的第一个ZIP条目是未压缩的mimetype
+        const ODS_MIMETYPE: &[u8] = b"application/vnd.oasis.opendocument.spreadsheet";
+        
+        if header.starts_with(CFB_MAGIC) {
+            Some(WorkbookKind::Xls)
+        } else if header.starts_with(ZIP_MAGIC) {
+            if header.windows(ODS_MIMETYPE.len()).any(|w| w == ODS_MIMETYPE) {
+                Some(WorkbookKind::Ods)
+            } else if ext == "xlsb" {
+                Some(WorkbookKind::Xlsb)
+            } else {
+                Some(WorkbookKind::Xlsx)
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// 将calamine各后端的错误转换为带文件类型的ExcelError
+fn excel_error(kind: WorkbookKind, err: impl std::fmt::Display) -> TransmutaError {
+    TransmutaError::ExcelError(format!("读取{}文件失败: {}", kind, err))
+}
+
+/// 按实际文件类型打开工作簿
+fn open_workbook_as<RS: Read + Seek>(reader: RS, kind: WorkbookKind) -> Result<Sheets<RS>> {
+    let workbook = match kind {
+        WorkbookKind::Xlsx => Sheets::Xlsx(Xlsx::new(reader).map_err(|e| excel_error(kind, e))?),
+        WorkbookKind::Xls => Sheets::Xls(Xls::new(reader).map_err(|e| excel_error(kind, e))?),
+        WorkbookKind::Xlsb => Sheets::Xlsb(Xlsb::new(reader).map_err(|e| excel_error(kind, e))?),
+        WorkbookKind::Ods => Sheets::Ods(Ods::new(reader).map_err(|e| excel_error(kind, e))?),
+    };
+    Ok(workbook)
+}
+
+/// 可随机读取的工作簿数据源：文件或读入内存的标准输入
+trait WorkbookSource: Read + Seek {}
+
+impl<T: Read + Seek> WorkbookSource for T {}
+
+/// 检测文件类型并打开Excel文件，路径为"-"时把标准输入读入内存后打开
+fn open_excel(input_path: &Path) -> Result<(Sheets<Box<dyn WorkbookSource>>, WorkbookKind)> {
+    let (mut reader, ext): (Box<dyn WorkbookSource>, String) = if utils::is_stdio(input_path) {
+        let data = utils::read_stdin_to_memory()?;
+        // 标准输入没有扩展名，xlsb通过ZIP中的工作簿条目名识别
+        const XLSB_ENTRY: &[u8] = b"xl/workbook.bin";
+        let is_xlsb = data.get_ref().windows(XLSB_ENTRY.len()).any(|w| w == XLSB_ENTRY);
+        (Box::new(data), if is_xlsb { "xlsb".to_string() } else { String::new() })
+    } else {
+        let ext = utils::get_file_extension(input_path).unwrap_or_default();
+        if !["xlsx", "xlsm", "xlsb", "xls", "ods"].contains(&ext.as_str()) {
+            warn!("输入文件扩展名({})不是常见的Excel扩展名，将按文件内容识别格式", ext);
+        }
+        (Box::new(BufReader::new(File::open(input_path)?)), ext)
+    };
+    
+    // 读取文件头部用于识别类型
+    let mut header = Vec::with_capacity(128);
+    reader.by_ref().take(128).read_to_end(&mut header)?;
+    reader.seek(SeekFrom::Start(0))?;
+    
+    let kind = WorkbookKind::detect(&header, &ext).ok_or_else(|| TransmutaError::FileFormatError(format!(
+        "无法识别的Excel文件格式: {}（支持xlsx、xlsm、xlsb、xls、ods）", input_path.display()
+    )))?;
+    debug!("检测到工作簿类型: {}", kind);
+    
+    Ok((open_workbook_as(reader, kind)?, kind))
+}
+
+/// 将列序号（从0开始）转换为Excel列名，例如 0 -> A，27 -> AB
+fn column_name(mut col: u32) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (col % 26) as u8);
+        if col < 26 {
+            break;
+        }
+        col = col / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+/// 列出Excel文件中所有工作表的名称、数据范围和行列数
+pub fn list_sheets(input_path: &Path) -> Result<()> {
+    let (mut workbook, kind) = open_excel(input_path)?;
+    let sheet_names = workbook.sheet_names().to_vec();
+    
+    println!("工作簿类型: {}", kind);
+    println!("{:<6}{:<30}{:<16}{:>10}{:>8}", "序号", "名称", "范围", "行数", "列数");
+    for (idx, name) in sheet_names.iter().enumerate() {
+        let range = match workbook.worksheet_range(name) {
+            Some(Ok(range)) => range,
+            Some(Err(e)) => return Err(excel_error(kind, e)),
+            None => return Err(TransmutaError::ExcelError(format!("无法读取工作表: {}", name))),
+        };
+        
+        let dimensions = match (range.start(), range.end()) {
+            (Some((r1, c1)), Some((r2, c2))) => format!(
+                "{}{}:{}{}", column_name(c1), r1 + 1, column_name(c2), r2 + 1
+            ),
+            _ => "-".to_string(),
+        };
+        
+        println!("{:<6}{:<30}{:<16}{:>10}{:>8}", idx + 1, name, dimensions, range.height(), range.width());
+    }
+    
+    Ok(())
+}
+
+/// 根据名称或序号（从1开始）查找工作表
+fn resolve_sheet_name(sheet_names: &[String], selector: &str) -> Result<String> {
+    if let Some(name) = sheet_names.iter().find(|name| name.as_str() == selector) {
+        return Ok(name.clone());
+    }
+    
+    match selector.parse::<usize>() {
+        Ok(index) if index >= 1 && index <= sheet_names.len() => Ok(sheet_names[index - 1].clone()),
+        _ => Err(TransmutaError::InvalidArgument(format!(
+            "找不到工作表'{}'，可用的工作表: {}", selector, sheet_names.join(", ")
+        ))),
+    }
+}
+
 /// 转换Excel文件到其他格式
 pub fn convert_excel(
     input_path: &Path,
     output_path: &Path,
     format: &OutputFormat,
-    batch_size: usize,
-    delimiter: char,
-    threads: Option<usize>,
     skip_rows: usize,
+    sheet: &SheetSelection,
+    options: &ConvertOptions,
 ) -> Result<()> {
     let start_time = Instant::now();
     
-    // 检查输入文件是否是Excel文件
-    let ext = utils::get_file_extension(input_path)?;
-    if !["xlsx", "xls", "xlsm"].contains(&ext.as_str()) {
-        return Err(TransmutaError::FileFormatError(format!(
-            "不支持的Excel文件格式: {}", ext
-        )));
+    if let SheetSelection::All = sheet {
+        if utils::is_stdio(output_path) {
+            return Err(TransmutaError::InvalidArgument(
+                "--all-sheets为每个工作表输出一个文件，不能输出到标准输出".to_string()
+            ));
+        }
     }
     
     info!("开始处理Excel文件: {}", input_path.display());
     
     // 打开Excel文件
-    let mut workbook: Xlsx<_> = open_workbook(input_path)?;
+    let (mut workbook, kind) = open_excel(input_path)?;
     
-    // 获取第一个工作表
     let sheet_names = workbook.sheet_names().to_vec();
     if sheet_names.is_empty() {
         return Err(TransmutaError::DataProcessingError("Excel文件中没有工作表".to_string()));
     }
     
-    let sheet_name = &sheet_names[0];
-    info!("使用工作表: {}", sheet_name);
+    // 确定要转换的工作表
+    let selected_sheets = match sheet {
+        SheetSelection::First => vec![sheet_names[0].clone()],
+        SheetSelection::Named(selector) => vec![resolve_sheet_name(&sheet_names, selector)?],
+        SheetSelection::All => sheet_names.clone(),
+    };
+    
+    // 所有工作表中无法转换的单元格写入同一个报告
+    let mut cast_failures = CastFailureLog::try_new(options.cast_report.as_deref())?;
+    
+    for sheet_name in &selected_sheets {
+        info!("使用工作表: {}", sheet_name);
+        
+        // 读取工作表内容
+        let range = match workbook.worksheet_range(sheet_name) {
+            Some(Ok(range)) => range,
+            Some(Err(e)) => return Err(excel_error(kind, e)),
+            None => return Err(TransmutaError::ExcelError(format!("无法读取工作表: {}", sheet_name))),
+        };
+        
+        if let SheetSelection::All = sheet {
+            // 所有工作表模式下，每个工作表输出到以工作表名命名的文件，跳过空工作表
+            if range.height() <= skip_rows + 1 {
+                warn!("工作表'{}'没有数据行，已跳过", sheet_name);
+                continue;
+            }
+            let sheet_output_path = utils::suffixed_file_path(output_path, &utils::sanitize_file_name(sheet_name));
+            convert_range(&range, &sheet_output_path, format, skip_rows, options, &mut cast_failures)?;
+        } else {
+            convert_range(&range, output_path, format, skip_rows, options, &mut cast_failures)?;
+        }
+    }
+    cast_failures.finish()?;
+    
+    let elapsed = start_time.elapsed();
+    info!("总处理时间: {:.2}秒", elapsed.as_secs_f64());
     
-    // 读取工作表内容
-    if let Some(Ok(range)) = workbook.worksheet_range(sheet_name) {
-        // 获取总行数
+    Ok(())
+}
+
+/// 按批次读取工作表数据的读取器，跳过的行之后的第一行为标题行
+struct RangeBatchReader<'a> {
+    rows: Rows<'a, ExcelDataType>,
+    builder: SheetBatchBuilder,
+    batch_size: usize,
+    /// 下一个数据行在工作表中的行号（从1开始）
+    next_row_number: usize,
+    finished: bool,
+}
+
+impl<'a> RangeBatchReader<'a> {
+    fn try_new(
+        range: &'a Range<ExcelDataType>,
+        skip_rows: usize,
+        options: &ConvertOptions,
+    ) -> Result<Self> {
         let row_count = range.height();
         if row_count <= skip_rows {
             return Err(TransmutaError::DataProcessingError(format!(
@@ -77,18 +445,11 @@ pub fn convert_excel(
             )));
         }
         
-        let effective_row_count = row_count - skip_rows;
-        info!("总行数: {}, 有效行数: {}", row_count, effective_row_count);
-        
-        // 设置进度条
-        let pb = ProgressBar::new(effective_row_count as u64);
-        pb.set_style(ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
-            .unwrap()
-            .progress_chars("#>-"));
+        // 跳过的行之后的第一行为标题行，其后为数据行
+        let data_start_row = skip_rows + 1;
         
         // 确定并创建标题
-        let headers: Vec<String> = if skip_rows < range.height() && range.width() > 0 {
+        let headers: Vec<String> = if range.width() > 0 {
             range.rows()
                 .nth(skip_rows)
                 .map(|row| {
@@ -108,99 +469,212 @@ pub fn convert_excel(
                     (0..range.width()).map(|i| format!("Column{}", i + 1)).collect()
                 })
         } else {
-            (0..range.width()).map(|i| format!("Column{}", i + 1)).collect()
+            Vec::new()
         };
         
         debug!("列标题: {:?}", headers);
         
-        // 初始化Arrow字段
-        let schema = Schema::new(
-            headers.iter().map(|name| {
-                Field::new(name, DataType::Utf8, true)
-            }).collect::<Vec<Field>>()
-        );
+        // 只推断和解析选择的列，--schema按输出的列名匹配
+        let projection = options.columns.resolve(&headers)?;
+        let headers = projection.names;
+        let columns = projection.indices;
         
-        // 计算批次数
-        let batch_count = (effective_row_count + batch_size - 1) / batch_size;
-        info!("将数据分为{}个批次处理，每批次{}行", batch_count, batch_size);
-        
-        // 设置线程数
-        let thread_count = utils::get_thread_count(threads);
-        
-        // 处理数据
-        let mut processed_rows = 0;
-        
-        for batch_idx in 0..batch_count {
-            let start_row = skip_rows + batch_idx * batch_size;
-            let end_row = std::cmp::min(skip_rows + (batch_idx + 1) * batch_size, row_count);
-            let current_batch_size = end_row - start_row;
-            
-            debug!("处理批次 {}/{}: 行 {} 到 {}", batch_idx + 1, batch_count, start_row, end_row - 1);
-            
-            // 为每列创建一个StringBuilder
-            let mut string_builders: Vec<StringBuilder> = headers.iter()
-                .map(|_| StringBuilder::new())
-                .collect();
-            
-            // 添加数据到builders
-            for row_idx in start_row..end_row {
-                if let Some(row) = range.rows().nth(row_idx) {
-                    for (col_idx, cell) in row.iter().enumerate() {
-                        if col_idx < string_builders.len() {
-                            string_builders[col_idx].append_value(&cell_to_string(cell));
-                        } else {
-                            string_builders.push(StringBuilder::new());
-                            string_builders.last_mut().unwrap().append_value(&cell_to_string(cell));
-                        }
-                    }
-                    
-                    // 对于缺失的列，添加空字符串
-                    for col_idx in row.len()..headers.len() {
-                        string_builders[col_idx].append_value("");
-                    }
-                }
-                
-                processed_rows += 1;
-                pb.set_position(processed_rows as u64);
+        // 采样数据行推断列类型
+        let mut column_types = match options.infer_rows {
+            Some(infer_rows) => {
+                let sample_rows: Vec<Vec<String>> = range.rows()
+                    .skip(data_start_row)
+                    .take(infer_rows)
+                    .map(|row| project(row, &columns).collect())
+                    .collect();
+                infer::infer_column_types(
+                    headers.len(),
+                    sample_rows.iter().map(|row| row.iter().map(String::as_str)),
+                )
             }
-            
-            // 创建数组
-            let arrays: Vec<Arc<dyn Array>> = string_builders.into_iter()
-                .map(|mut builder| Arc::new(builder.finish()) as Arc<dyn Array>)
-                .collect();
-            
-            // 创建RecordBatch
-            let record_batch = RecordBatch::try_new(Arc::new(schema.clone()), arrays)?;
-            
-            // 确定输出路径
-            let mut output_file_path = output_path.to_path_buf();
-            
-            // 如果有多个批次，为每个批次生成不同的文件名
-            if batch_count > 1 {
-                if let Some(file_name) = output_path.file_stem() {
-                    let mut new_file_name = file_name.to_string_lossy().to_string();
-                    new_file_name.push_str(&format!("_part{:04}", batch_idx + 1));
-                    
-                    if let Some(ext) = output_path.extension() {
-                        new_file_name.push('.');
-                        new_file_name.push_str(&ext.to_string_lossy());
-                    }
-                    
-                    output_file_path = output_path.with_file_name(new_file_name);
-                }
+            None => vec![DataType::Utf8; headers.len()],
+        };
+        let declared = match &options.schema {
+            Some(declared_schema) => infer::apply_declared_types(
+                headers.iter().map(String::as_str), &mut column_types, declared_schema,
+            )?,
+            None => vec![false; headers.len()],
+        };
+        
+        // 日期时间和持续时间列不论是否推断类型都使用单元格自身的类型，声明的列除外
+        let native_types: Vec<Option<DataType>> = columns.iter().zip(&declared)
+            .map(|(&column, &is_declared)| match is_declared {
+                true => None,
+                false => native_column_type(range.rows().skip(data_start_row).filter_map(|row| row.get(column))),
+            })
+            .collect();
+        for (column_type, native_type) in column_types.iter_mut().zip(&native_types) {
+            if let Some(native_type) = native_type {
+                *column_type = native_type.clone();
             }
-            
-            // 保存到指定格式
-            super::common::save_data(&record_batch, &output_file_path, format, delimiter)?;
         }
         
-        pb.finish_with_message("Excel文件转换完成");
+        // 初始化Arrow字段
+        let schema = Arc::new(Schema::new(
+            headers.iter().zip(column_types.iter()).map(|(name, data_type)| {
+                Field::new(name, data_type.clone(), true)
+            }).collect::<Vec<Field>>()
+        ));
+        debug!("列类型: {}", schema.fields().iter()
+            .map(|f| format!("{}({})", f.name(), f.data_type()))
+            .collect::<Vec<_>>()
+            .join(", "));
         
-        let elapsed = start_time.elapsed();
-        info!("总处理时间: {:.2}秒", elapsed.as_secs_f64());
+        // 按字符串解析的列单独构建
+        let text_indices: Vec<usize> = (0..columns.len()).filter(|&i| native_types[i].is_none()).collect();
+        let text_schema = Arc::new(schema.project(&text_indices)?);
+        let text_builder = TypedBatchBuilder::new(text_schema).with_cast_options(
+            text_indices.iter().map(|&i| declared[i]).collect(),
+            options.on_cast_error,
+        );
+        let builder = SheetBatchBuilder {
+            schema,
+            text_columns: text_indices.iter().map(|&i| columns[i]).collect(),
+            columns,
+            native_types,
+            text_builder,
+        };
         
-        Ok(())
-    } else {
-        Err(TransmutaError::ExcelError(format!("无法读取工作表: {}", sheet_name)))
+        // 跳过标题行及之前的行
+        let mut rows = range.rows();
+        rows.nth(skip_rows);
+        
+        Ok(RangeBatchReader {
+            rows,
+            builder,
+            batch_size: options.batch_size,
+            next_row_number: data_start_row + 1,
+            finished: false,
+        })
+    }
+    
+    fn schema(&self) -> SchemaRef {
+        self.builder.schema.clone()
+    }
+    
+    /// 读取下一块原始数据行及其第一行的行号，没有更多数据时返回None
+    fn next_chunk(&mut self) -> Option<(usize, Vec<&'a [ExcelDataType]>)> {
+        let chunk: Vec<&'a [ExcelDataType]> = self.rows.by_ref().take(self.batch_size).collect();
+        if chunk.is_empty() {
+            return None;
+        }
+        
+        let first_row_number = self.next_row_number;
+        self.next_row_number += chunk.len();
+        Some((first_row_number, chunk))
     }
-} 
\ No newline at end of file
+}
+
+impl Iterator for RangeBatchReader<'_> {
+    type Item = Result<RecordBatch>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.next_chunk() {
+            Some((first_row_number, rows)) => {
+                let batch = self.builder.build(first_row_number, &rows)
+                    .map(|(batch, _)| batch);
+                self.finished = batch.is_err();
+                Some(batch)
+            }
+            None => {
+                self.finished = true;
+                None
+            }
+        }
+    }
+}
+
+/// 转换单个工作表的数据
+fn convert_range(
+    range: &Range<ExcelDataType>,
+    output_path: &Path,
+    format: &OutputFormat,
+    skip_rows: usize,
+    options: &ConvertOptions,
+    cast_failures: &mut CastFailureLog,
+) -> Result<()> {
+    let batch_size = options.batch_size;
+    
+    let mut reader = RangeBatchReader::try_new(range, skip_rows, options)?;
+    
+    // 获取总行数
+    let row_count = range.height();
+    let effective_row_count = row_count - skip_rows - 1;
+    info!("总行数: {}, 有效行数: {}", row_count, effective_row_count);
+    
+    // 设置进度条
+    let pb = ProgressBar::new(effective_row_count as u64);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
+        .unwrap()
+        .progress_chars("#>-"));
+    
+    // 计算批次数
+    let batch_count = effective_row_count.div_ceil(batch_size);
+    info!("将数据分为{}个批次处理，每批次{}行", batch_count, batch_size);
+    
+    // 设置线程数
+    let thread_count = utils::get_thread_count(options.threads);
+    
+    // 在写出任何数据之前检查过滤条件
+    options.validate_filter(&reader.schema())?;
+    
+    // 打开输出文件，所有批次写入同一个写入器
+    let mut writer = open_writer(output_path, format, reader.schema(), &options.writer_options())?;
+    
+    let mut processed_rows = 0;
+    let builder = reader.builder.clone();
+    let chunks = std::iter::from_fn(|| reader.next_chunk()).map(Ok);
+    
+    // 按顺序切分数据行，线程池并行解析，按原始顺序写入输出文件
+    run_pipeline(
+        chunks,
+        thread_count,
+        |(first_row_number, rows): (usize, Vec<&[ExcelDataType]>)| {
+            let (record_batch, failures) = builder.build(first_row_number, &rows)?;
+            Ok((options.filter_batch(record_batch)?, failures, rows.len()))
+        },
+        |(record_batch, failures, row_count)| {
+            cast_failures.record(&failures)?;
+            writer.write(&record_batch)?;
+            processed_rows += row_count;
+            pb.set_position(processed_rows as u64);
+            Ok(())
+        },
+    )?;
+    
+    writer.close()?;
+    pb.finish_with_message("Excel文件转换完成");
+    
+    Ok(())
+}
+
+/// 读取Excel文件第一个工作表的数据。工作表在读取时已整体载入内存，因此批次也全部构建后返回
+pub fn read_excel(input_path: &Path, options: &ConvertOptions) -> Result<(SchemaRef, RecordBatches)> {
+    let (mut workbook, kind) = open_excel(input_path)?;
+    
+    let sheet_name = match workbook.sheet_names().first() {
+        Some(name) => name.clone(),
+        None => return Err(TransmutaError::DataProcessingError("Excel文件中没有工作表".to_string())),
+    };
+    debug!("读取工作表: {}", sheet_name);
+    
+    let range = match workbook.worksheet_range(&sheet_name) {
+        Some(Ok(range)) => range,
+        Some(Err(e)) => return Err(excel_error(kind, e)),
+        None => return Err(TransmutaError::ExcelError(format!("无法读取工作表: {}", sheet_name))),
+    };
+    
+    let reader = RangeBatchReader::try_new(&range, 0, options)?;
+    let schema = reader.schema();
+    let batches = reader.collect::<Result<Vec<RecordBatch>>>()?;
+    Ok((schema, Box::new(batches.into_iter().map(Ok))))
+}
@@ -0,0 +1,526 @@
+use crate::cli::Locale;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+// 以下词表直接编译进程序，生成时不需要网络，且相同的种子总是得到相同的结果
+
+/// 常见姓氏及其拼音
+const ZH_SURNAMES: &[(&str, &str)] = &[
+    ("王", "wang"), ("李", "li"), ("张", "zhang"), ("刘", "liu"), ("陈", "chen"),
+    ("杨", "yang"), ("黄", "huang"), ("赵", "zhao"), ("吴", "wu"), ("周", "zhou"),
+    ("徐", "xu"), ("孙", "sun"), ("马", "ma"), ("朱", "zhu"), ("胡", "hu"),
+    ("郭", "guo"), ("何", "he"), ("高", "gao"), ("林", "lin"), ("罗", "luo"),
+    ("郑", "zheng"), ("梁", "liang"), ("谢", "xie"), ("宋", "song"), ("唐", "tang"),
+    ("许", "xu"), ("韩", "han"), ("冯", "feng"), ("邓", "deng"), ("曹", "cao"),
+    ("彭", "peng"), ("曾", "zeng"), ("肖", "xiao"), ("田", "tian"), ("董", "dong"),
+    ("袁", "yuan"), ("潘", "pan"), ("于", "yu"), ("蒋", "jiang"), ("蔡", "cai"),
+    ("余", "yu"), ("杜", "du"), ("叶", "ye"), ("程", "cheng"), ("苏", "su"),
+    ("魏", "wei"), ("吕", "lv"), ("丁", "ding"), ("任", "ren"), ("沈", "shen"),
+    ("姚", "yao"), ("卢", "lu"), ("姜", "jiang"), ("崔", "cui"), ("钟", "zhong"),
+    ("谭", "tan"), ("陆", "lu"), ("汪", "wang"), ("范", "fan"), ("金", "jin"),
+    ("石", "shi"), ("廖", "liao"), ("贾", "jia"), ("夏", "xia"), ("韦", "wei"),
+    ("付", "fu"), ("方", "fang"), ("白", "bai"), ("邹", "zou"), ("孟", "meng"),
+    ("熊", "xiong"), ("秦", "qin"), ("邱", "qiu"), ("江", "jiang"), ("尹", "yin"),
+    ("薛", "xue"), ("闫", "yan"), ("段", "duan"), ("雷", "lei"), ("侯", "hou"),
+    ("龙", "long"), ("史", "shi"), ("陶", "tao"), ("黎", "li"), ("贺", "he"),
+    ("顾", "gu"), ("毛", "mao"), ("郝", "hao"), ("龚", "gong"), ("邵", "shao"),
+    ("欧阳", "ouyang"), ("司马", "sima"), ("上官", "shangguan"), ("诸葛", "zhuge"),
+];
+
+/// 名字常用字及其拼音
+const ZH_GIVEN_CHARS: &[(&str, &str)] = &[
+    ("伟", "wei"), ("芳", "fang"), ("娜", "na"), ("敏", "min"), ("静", "jing"),
+    ("丽", "li"), ("强", "qiang"), ("磊", "lei"), ("军", "jun"), ("洋", "yang"),
+    ("勇", "yong"), ("艳", "yan"), ("杰", "jie"), ("娟", "juan"), ("涛", "tao"),
+    ("明", "ming"), ("超", "chao"), ("秀", "xiu"), ("霞", "xia"), ("平", "ping"),
+    ("刚", "gang"), ("桂", "gui"), ("英", "ying"), ("华", "hua"), ("建", "jian"),
+    ("国", "guo"), ("文", "wen"), ("辉", "hui"), ("玲", "ling"), ("婷", "ting"),
+    ("宇", "yu"), ("浩", "hao"), ("鑫", "xin"), ("博", "bo"), ("晨", "chen"),
+    ("欣", "xin"), ("怡", "yi"), ("佳", "jia"), ("子", "zi"), ("轩", "xuan"),
+    ("梓", "zi"), ("涵", "han"), ("雨", "yu"), ("思", "si"), ("嘉", "jia"),
+    ("俊", "jun"), ("志", "zhi"), ("海", "hai"), ("鹏", "peng"), ("飞", "fei"),
+    ("晓", "xiao"), ("慧", "hui"), ("琳", "lin"), ("雪", "xue"), ("梅", "mei"),
+    ("兰", "lan"), ("红", "hong"), ("燕", "yan"), ("波", "bo"), ("斌", "bin"),
+    ("宁", "ning"), ("凯", "kai"), ("瑞", "rui"), ("泽", "ze"), ("然", "ran"),
+    ("天", "tian"), ("一", "yi"), ("诗", "shi"), ("若", "ruo"), ("安", "an"),
+    ("承", "cheng"), ("昊", "hao"), ("睿", "rui"), ("彤", "tong"), ("晴", "qing"),
+    ("可", "ke"), ("心", "xin"), ("语", "yu"), ("萱", "xuan"), ("铭", "ming"),
+];
+
+const EN_FIRST_NAMES: &[&str] = &[
+    "James", "Mary", "John", "Patricia", "Robert", "Jennifer", "Michael", "Linda",
+    "William", "Elizabeth", "David", "Barbara", "Richard", "Susan", "Joseph", "Jessica",
+    "Thomas", "Sarah", "Charles", "Karen", "Christopher", "Nancy", "Daniel", "Lisa",
+    "Matthew", "Betty", "Anthony", "Margaret", "Mark", "Sandra", "Donald", "Ashley",
+    "Steven", "Kimberly", "Paul", "Emily", "Andrew", "Donna", "Joshua", "Michelle",
+    "Kenneth", "Carol", "Kevin", "Amanda", "Brian", "Melissa", "George", "Deborah",
+    "Timothy", "Stephanie", "Ronald", "Rebecca", "Edward", "Sharon", "Jason", "Laura",
+    "Jeffrey", "Cynthia", "Ryan", "Kathleen", "Jacob", "Amy", "Gary", "Angela",
+    "Nicholas", "Shirley", "Eric", "Anna", "Jonathan", "Brenda", "Stephen", "Pamela",
+    "Larry", "Emma", "Justin", "Nicole", "Scott", "Helen", "Brandon", "Samantha",
+    "Benjamin", "Katherine", "Samuel", "Christine", "Gregory", "Debra", "Alexander", "Rachel",
+    "Frank", "Carolyn", "Patrick", "Janet", "Raymond", "Catherine", "Jack", "Maria",
+    "Dennis", "Heather", "Jerry", "Diane", "Tyler", "Olivia", "Aaron", "Julie",
+];
+
+const EN_LAST_NAMES: &[&str] = &[
+    "Smith", "Johnson", "Williams", "Brown", "Jones", "Garcia", "Miller", "Davis",
+    "Rodriguez", "Martinez", "Hernandez", "Lopez", "Gonzalez", "Wilson", "Anderson", "Thomas",
+    "Taylor", "Moore", "Jackson", "Martin", "Lee", "Perez", "Thompson", "White",
+    "Harris", "Sanchez", "Clark", "Ramirez", "Lewis", "Robinson", "Walker", "Young",
+    "Allen", "King", "Wright", "Scott", "Torres", "Nguyen", "Hill", "Flores",
+    "Green", "Adams", "Nelson", "Baker", "Hall", "Rivera", "Campbell", "Mitchell",
+    "Carter", "Roberts", "Gomez", "Phillips", "Evans", "Turner", "Diaz", "Parker",
+    "Cruz", "Edwards", "Collins", "Reyes", "Stewart", "Morris", "Morales", "Murphy",
+    "Cook", "Rogers", "Gutierrez", "Ortiz", "Morgan", "Cooper", "Peterson", "Bailey",
+    "Reed", "Kelly", "Howard", "Ramos", "Kim", "Cox", "Ward", "Richardson",
+    "Watson", "Brooks", "Chavez", "Wood", "James", "Bennett", "Gray", "Mendoza",
+    "Ruiz", "Hughes", "Price", "Alvarez", "Castillo", "Sanders", "Patel", "Myers",
+];
+
+/// 省级行政区和城市
+const ZH_CITIES: &[(&str, &str)] = &[
+    ("北京市", "北京市"), ("上海市", "上海市"), ("天津市", "天津市"), ("重庆市", "重庆市"),
+    ("广东省", "广州市"), ("广东省", "深圳市"), ("广东省", "东莞市"), ("广东省", "佛山市"),
+    ("广东省", "珠海市"), ("浙江省", "杭州市"), ("浙江省", "宁波市"), ("浙江省", "温州市"),
+    ("浙江省", "绍兴市"), ("江苏省", "南京市"), ("江苏省", "苏州市"), ("江苏省", "无锡市"),
+    ("江苏省", "常州市"), ("江苏省", "南通市"), ("山东省", "济南市"), ("山东省", "青岛市"),
+    ("山东省", "烟台市"), ("福建省", "福州市"), ("福建省", "厦门市"), ("福建省", "泉州市"),
+    ("四川省", "成都市"), ("四川省", "绵阳市"), ("湖北省", "武汉市"), ("湖北省", "宜昌市"),
+    ("湖南省", "长沙市"), ("湖南省", "株洲市"), ("河南省", "郑州市"), ("河南省", "洛阳市"),
+    ("河北省", "石家庄市"), ("河北省", "保定市"), ("陕西省", "西安市"), ("安徽省", "合肥市"),
+    ("安徽省", "芜湖市"), ("江西省", "南昌市"), ("辽宁省", "沈阳市"), ("辽宁省", "大连市"),
+    ("吉林省", "长春市"), ("黑龙江省", "哈尔滨市"), ("山西省", "太原市"), ("云南省", "昆明市"),
+    ("贵州省", "贵阳市"), ("广西壮族自治区", "南宁市"), ("广西壮族自治区", "桂林市"), ("海南省", "海口市"),
+    ("海南省", "三亚市"), ("甘肃省", "兰州市"), ("内蒙古自治区", "呼和浩特市"), ("新疆维吾尔自治区", "乌鲁木齐市"),
+    ("宁夏回族自治区", "银川市"), ("青海省", "西宁市"), ("西藏自治区", "拉萨市"),
+];
+
+const ZH_DISTRICTS: &[&str] = &[
+    "东城区", "西城区", "朝阳区", "海淀区", "南山区", "福田区", "天河区", "越秀区",
+    "西湖区", "滨江区", "鼓楼区", "玄武区", "高新区", "经济开发区", "新城区", "城关区",
+    "江北区", "江南区", "开发区", "市南区", "历下区", "武侯区", "锦江区", "雁塔区",
+];
+
+const ZH_STREET_WORDS: &[&str] = &[
+    "人民", "解放", "中山", "建设", "和平", "新华", "长江", "黄河", "胜利", "文化",
+    "友谊", "光明", "幸福", "青年", "朝阳", "东风", "红旗", "复兴", "学府", "科技",
+    "金融", "世纪", "滨江", "湖滨", "北京", "南京", "延安", "迎宾", "园林", "花园",
+];
+
+const ZH_STREET_SUFFIXES: &[&str] = &["路", "街", "大道", "大街", "巷"];
+
+/// 美国城市和所在州的缩写
+const EN_CITIES: &[(&str, &str)] = &[
+    ("New York", "NY"), ("Los Angeles", "CA"), ("Chicago", "IL"), ("Houston", "TX"),
+    ("Phoenix", "AZ"), ("Philadelphia", "PA"), ("San Antonio", "TX"), ("San Diego", "CA"),
+    ("Dallas", "TX"), ("San Jose", "CA"), ("Austin", "TX"), ("Jacksonville", "FL"),
+    ("Fort Worth", "TX"), ("Columbus", "OH"), ("Charlotte", "NC"), ("Indianapolis", "IN"),
+    ("San Francisco", "CA"), ("Seattle", "WA"), ("Denver", "CO"), ("Washington", "DC"),
+    ("Boston", "MA"), ("Nashville", "TN"), ("Detroit", "MI"), ("Portland", "OR"),
+    ("Las Vegas", "NV"), ("Memphis", "TN"), ("Louisville", "KY"), ("Baltimore", "MD"),
+    ("Milwaukee", "WI"), ("Albuquerque", "NM"), ("Tucson", "AZ"), ("Fresno", "CA"),
+    ("Sacramento", "CA"), ("Kansas City", "MO"), ("Atlanta", "GA"), ("Miami", "FL"),
+    ("Raleigh", "NC"), ("Omaha", "NE"), ("Minneapolis", "MN"), ("Tulsa", "OK"),
+    ("Cleveland", "OH"), ("Tampa", "FL"), ("New Orleans", "LA"), ("Pittsburgh", "PA"),
+    ("Cincinnati", "OH"), ("St. Louis", "MO"), ("Orlando", "FL"), ("Salt Lake City", "UT"),
+    ("Richmond", "VA"), ("Madison", "WI"), ("Boise", "ID"), ("Anchorage", "AK"),
+    ("Honolulu", "HI"), ("Spokane", "WA"), ("Des Moines", "IA"), ("Buffalo", "NY"),
+];
+
+const EN_STREET_NAMES: &[&str] = &[
+    "Main", "Oak", "Pine", "Maple", "Cedar", "Elm", "Washington", "Lake", "Hill", "Park",
+    "Walnut", "Sunset", "Lincoln", "Jackson", "Church", "River", "Spring", "Highland", "Forest", "Meadow",
+    "Madison", "Franklin", "Willow", "Cherry", "Center", "Mill", "Ridge", "Valley", "Jefferson", "Adams",
+];
+
+const EN_STREET_SUFFIXES: &[&str] = &[
+    "Street", "Avenue", "Road", "Boulevard", "Lane", "Drive", "Court", "Place", "Way", "Terrace",
+];
+
+/// 公司字号
+const ZH_COMPANY_WORDS: &[&str] = &[
+    "华信", "中科", "天润", "恒通", "鼎盛", "瑞丰", "宏达", "金桥", "博远", "新创",
+    "联合", "东方", "长城", "海纳", "万达", "盛世", "嘉禾", "融通", "卓越", "启明",
+    "飞扬", "环宇", "智联", "腾跃", "汇丰", "泰和", "永安", "鸿运", "星辰", "云帆",
+];
+
+const ZH_INDUSTRIES: &[&str] = &[
+    "科技", "信息技术", "网络科技", "电子商务", "贸易", "物流", "建设工程", "电子",
+    "实业", "文化传媒", "医药", "食品", "新能源", "教育咨询", "软件", "环保科技",
+];
+
+const ZH_COMPANY_SUFFIXES: &[&str] = &["有限公司", "股份有限公司", "集团有限公司", "有限责任公司"];
+
+const EN_COMPANY_WORDS: &[&str] = &[
+    "Global", "United", "Pacific", "Summit", "Pioneer", "Apex", "Vertex", "Horizon", "Atlas", "Nova",
+    "Blue", "Silver", "Bright", "Prime", "Quantum", "Evergreen", "Northern", "Liberty", "Keystone", "Crescent",
+];
+
+const EN_INDUSTRIES: &[&str] = &[
+    "Technologies", "Systems", "Solutions", "Logistics", "Holdings", "Partners", "Industries", "Labs",
+    "Consulting", "Media", "Energy", "Foods", "Networks", "Capital", "Health", "Software",
+];
+
+const EN_COMPANY_SUFFIXES: &[&str] = &["Inc.", "LLC", "Ltd.", "Corp.", "Group", "Co."];
+
+/// 常用的邮箱域名
+const ZH_EMAIL_DOMAINS: &[&str] = &["qq.com", "163.com", "126.com", "sina.com", "foxmail.com", "aliyun.com", "outlook.com"];
+const EN_EMAIL_DOMAINS: &[&str] = &["gmail.com", "yahoo.com", "outlook.com", "hotmail.com", "icloud.com", "aol.com", "proton.me"];
+
+const ZH_TLDS: &[&str] = &["cn", "com.cn", "com", "net", "org.cn"];
+const EN_TLDS: &[&str] = &["com", "net", "org", "io", "info", "biz"];
+
+const URL_PATH_WORDS: &[&str] = &[
+    "about", "products", "news", "blog", "search", "category", "help", "docs", "account", "app",
+    "posts", "tags", "explore", "main", "list", "index", "home", "articles", "login", "wp-content",
+];
+
+/// 中国大陆手机号码的号段
+const ZH_MOBILE_PREFIXES: &[&str] = &[
+    "130", "131", "132", "133", "134", "135", "136", "137", "138", "139",
+    "150", "151", "152", "153", "155", "156", "157", "158", "159",
+    "166", "173", "175", "176", "177", "178", "180", "181", "182", "183",
+    "184", "185", "186", "187", "188", "189", "191", "198", "199",
+];
+
+/// 美国常见的电话区号
+const EN_AREA_CODES: &[&str] = &[
+    "201", "202", "206", "212", "213", "214", "215", "305", "310", "312",
+    "313", "404", "415", "469", "503", "512", "602", "617", "646", "702",
+    "713", "718", "720", "773", "808", "817", "832", "858", "917", "929",
+];
+
+const ZH_LOREM_WORDS: &[&str] = &[
+    "我们", "发展", "经济", "社会", "时间", "问题", "工作", "公司", "市场", "技术",
+    "产品", "服务", "管理", "系统", "数据", "信息", "用户", "国家", "世界", "生活",
+    "重要", "进行", "已经", "可以", "通过", "提高", "建立", "需要", "能够", "继续",
+    "研究", "计划", "环境", "方面", "情况", "影响", "作为", "认为", "同时", "非常",
+    "城市", "学习", "教育", "文化", "历史", "未来", "变化", "合作", "质量", "安全",
+    "部分", "过程", "结果", "方法", "实现", "推动", "完成", "基础", "目标", "选择",
+];
+
+const EN_LOREM_WORDS: &[&str] = &[
+    "lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit", "sed", "do",
+    "eiusmod", "tempor", "incididunt", "ut", "labore", "et", "dolore", "magna", "aliqua", "enim",
+    "ad", "minim", "veniam", "quis", "nostrud", "exercitation", "ullamco", "laboris", "nisi", "aliquip",
+    "ex", "ea", "commodo", "consequat", "duis", "aute", "irure", "in", "reprehenderit", "voluptate",
+    "velit", "esse", "cillum", "fugiat", "nulla", "pariatur", "excepteur", "sint", "occaecat", "cupidatat",
+    "non", "proident", "sunt", "culpa", "qui", "officia", "deserunt", "mollit", "anim", "id",
+];
+
+/// IBAN的国家代码，以及BBAN中字母和数字的位数
+const IBAN_COUNTRIES: &[(&str, usize, usize)] = &[
+    ("DE", 0, 18),
+    ("GB", 4, 14),
+    ("NL", 4, 10),
+    ("AT", 0, 16),
+    ("FI", 0, 14),
+];
+
+/// 信用卡号的前缀和总位数
+const ZH_CARD_PREFIXES: &[(&str, usize)] = &[("62", 16), ("62", 19), ("4", 16), ("51", 16), ("53", 16), ("35", 16)];
+const EN_CARD_PREFIXES: &[(&str, usize)] = &[("4", 16), ("51", 16), ("55", 16), ("2221", 16), ("34", 15), ("37", 15), ("6011", 16)];
+
+/// 语义化的生成类型
+#[derive(Debug, Clone, Copy)]
+pub enum FakeKind {
+    Name,
+    Email,
+    Phone,
+    Address,
+    City,
+    Company,
+    Url,
+    Ipv4,
+    Ipv6,
+    Iban,
+    CreditCard,
+    /// 占位文本，单词数在给定范围内
+    Lorem { min_words: usize, max_words: usize },
+}
+
+impl FakeKind {
+    /// 按语言区域生成一个值
+    pub fn sample(&self, locale: Locale, rng: &mut StdRng) -> String {
+        match (self, locale) {
+            (FakeKind::Name, Locale::Zh) => {
+                let (surname, _) = pick(ZH_SURNAMES, rng);
+                let given: String = (0..rng.gen_range(1..=2)).map(|_| pick(ZH_GIVEN_CHARS, rng).0).collect();
+                format!("{}{}", surname, given)
+            }
+            (FakeKind::Name, Locale::En) => format!("{} {}", pick(EN_FIRST_NAMES, rng), pick(EN_LAST_NAMES, rng)),
+
+            (FakeKind::Email, Locale::Zh) => {
+                let (_, surname) = pick(ZH_SURNAMES, rng);
+                let given: String = (0..rng.gen_range(1..=2)).map(|_| pick(ZH_GIVEN_CHARS, rng).1).collect();
+                let user = match rng.gen_range(0..3) {
+                    0 => format!("{}{}", surname, given),
+                    1 => format!("{}.{}", given, surname),
+                    _ => format!("{}{}{}", surname, given, rng.gen_range(1..10000)),
+                };
+                format!("{}@{}", user, pick(ZH_EMAIL_DOMAINS, rng))
+            }
+            (FakeKind::Email, Locale::En) => {
+                let first = pick(EN_FIRST_NAMES, rng).to_lowercase();
+                let last = pick(EN_LAST_NAMES, rng).to_lowercase();
+                let user = match rng.gen_range(0..3) {
+                    0 => format!("{}.{}", first, last),
+                    1 => format!("{}{}", &first[..1], last),
+                    _ => format!("{}{}{}", first, last, rng.gen_range(1..1000)),
+                };
+                format!("{}@{}", user, pick(EN_EMAIL_DOMAINS, rng))
+            }
+
+            (FakeKind::Phone, Locale::Zh) => format!("{}{}", pick(ZH_MOBILE_PREFIXES, rng), digits(8, rng)),
+            (FakeKind::Phone, Locale::En) => format!(
+                "({}) {}-{}", pick(EN_AREA_CODES, rng), rng.gen_range(200..1000), digits(4, rng)
+            ),
+
+            (FakeKind::Address, Locale::Zh) => {
+                let (province, city) = pick(ZH_CITIES, rng);
+                // 直辖市不重复写省级名称
+                let province = if province == city { "" } else { province };
+                format!(
+                    "{}{}{}{}{}{}号",
+                    province,
+                    city,
+                    pick(ZH_DISTRICTS, rng),
+                    pick(ZH_STREET_WORDS, rng),
+                    pick(ZH_STREET_SUFFIXES, rng),
+                    rng.gen_range(1..1000)
+                )
+            }
+            (FakeKind::Address, Locale::En) => {
+                let (city, state) = pick(EN_CITIES, rng);
+                format!(
+                    "{} {} {}, {}, {} {}",
+                    rng.gen_range(1..10000),
+                    pick(EN_STREET_NAMES, rng),
+                    pick(EN_STREET_SUFFIXES, rng),
+                    city,
+                    state,
+                    digits(5, rng)
+                )
+            }
+
+            (FakeKind::City, Locale::Zh) => pick(ZH_CITIES, rng).1.to_string(),
+            (FakeKind::City, Locale::En) => pick(EN_CITIES, rng).0.to_string(),
+
+            (FakeKind::Company, Locale::Zh) => {
+                let city = pick(ZH_CITIES, rng).1.trim_end_matches('市');
+                format!(
+                    "{}{}{}{}",
+                    city,
+                    pick(ZH_COMPANY_WORDS, rng),
+                    pick(ZH_INDUSTRIES, rng),
+                    pick(ZH_COMPANY_SUFFIXES, rng)
+                )
+            }
+            (FakeKind::Company, Locale::En) => match rng.gen_range(0..3) {
+                0 => format!("{} & {} {}", pick(EN_LAST_NAMES, rng), pick(EN_LAST_NAMES, rng), pick(EN_COMPANY_SUFFIXES, rng)),
+                1 => format!("{} {} {}", pick(EN_LAST_NAMES, rng), pick(EN_INDUSTRIES, rng), pick(EN_COMPANY_SUFFIXES, rng)),
+                _ => format!("{} {} {}", pick(EN_COMPANY_WORDS, rng), pick(EN_INDUSTRIES, rng), pick(EN_COMPANY_SUFFIXES, rng)),
+            },
+
+            (FakeKind::Url, _) => {
+                let (host, tld) = match locale {
+                    Locale::Zh => (
+                        format!("{}{}", pick(ZH_GIVEN_CHARS, rng).1, pick(ZH_GIVEN_CHARS, rng).1),
+                        pick(ZH_TLDS, rng),
+                    ),
+                    Locale::En => (
+                        format!("{}{}", pick(EN_COMPANY_WORDS, rng), pick(EN_LAST_NAMES, rng)).to_lowercase(),
+                        pick(EN_TLDS, rng),
+                    ),
+                };
+                let mut url = format!("https://www.{}.{}/", host, tld);
+                for _ in 0..rng.gen_range(0..=2) {
+                    url.push_str(pick(URL_PATH_WORDS, rng));
+                    url.push('/');
+                }
+                url
+            }
+
+            (FakeKind::Ipv4, _) => {
+                // 首段避开0、127（回环地址）和224以上（组播及保留地址）
+                let first = loop {
+                    let first = rng.gen_range(1..224u8);
+                    if first != 127 {
+                        break first;
+                    }
+                };
+                Ipv4Addr::new(first, rng.gen(), rng.gen(), rng.gen_range(1..255)).to_string()
+            }
+            (FakeKind::Ipv6, _) => {
+                // 全球单播地址2000::/3
+                let mut segments: [u16; 8] = rng.gen();
+                segments[0] = 0x2000 | (segments[0] & 0x1fff);
+                Ipv6Addr::from(segments).to_string()
+            }
+
+            (FakeKind::Iban, _) => generate_iban(rng),
+            (FakeKind::CreditCard, _) => {
+                let prefixes = match locale {
+                    Locale::Zh => ZH_CARD_PREFIXES,
+                    Locale::En => EN_CARD_PREFIXES,
+                };
+                let (prefix, len) = pick(prefixes, rng);
+                generate_card_number(prefix, len, rng)
+            }
+
+            (FakeKind::Lorem { min_words, max_words }, _) => {
+                let count = rng.gen_range(*min_words..=*max_words);
+                let (words, separator, comma, period) = match locale {
+                    Locale::Zh => (ZH_LOREM_WORDS, "", "，", "。"),
+                    Locale::En => (EN_LOREM_WORDS, " ", ",", "."),
+                };
+                let mut text = String::new();
+                for i in 0..count {
+                    let word = pick(words, rng);
+                    if i == 0 {
+                        // 英文句首字母大写
+                        let mut chars = word.chars();
+                        text.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+                        text.push_str(chars.as_str());
+                        continue;
+                    }
+                    // 大约每七个词插入一个逗号
+                    if i + 1 < count && rng.gen_bool(0.15) {
+                        text.push_str(comma);
+                    }
+                    text.push_str(separator);
+                    text.push_str(word);
+                }
+                if count > 0 {
+                    text.push_str(period);
+                }
+                text
+            }
+        }
+    }
+}
+
+fn pick<T: Copy>(items: &[T], rng: &mut StdRng) -> T {
+    *items.choose(rng).expect("词表不能为空")
+}
+
+/// 指定位数的随机数字串，可以以0开头
+fn digits(count: usize, rng: &mut StdRng) -> String {
+    (0..count).map(|_| char::from(b'0' + rng.gen_range(0..10u8))).collect()
+}
+
+/// 生成校验位正确（ISO 13616，模97余1）的IBAN
+fn generate_iban(rng: &mut StdRng) -> String {
+    let (country, letters, numbers) = pick(IBAN_COUNTRIES, rng);
+    let mut bban: String = (0..letters).map(|_| char::from(rng.gen_range(b'A'..=b'Z'))).collect();
+    bban.push_str(&digits(numbers, rng));
+
+    // 把国家代码和"00"移到末尾，字母按A=10、B=11……换算后对97取余
+    let remainder = format!("{}{}00", bban, country).chars().fold(0u32, |acc, c| {
+        let value = c.to_digit(36).expect("IBAN只包含字母和数字");
+        if value < 10 { (acc * 10 + value) % 97 } else { (acc * 100 + value) % 97 }
+    });
+    format!("{}{:02}{}", country, 98 - remainder, bban)
+}
+
+/// 生成以指定前缀开头、末位为Luhn校验位的卡号
+fn generate_card_number(prefix: &str, len: usize, rng: &mut StdRng) -> String {
+    let mut number = prefix.to_string();
+    number.push_str(&digits(len - prefix.len() - 1, rng));
+
+    // 从右往左，校验位左边的第一位开始每隔一位乘2
+    let sum: u32 = number.chars().rev().enumerate().map(|(i, c)| {
+        let digit = c.to_digit(10).expect("卡号只包含数字");
+        if i % 2 == 0 {
+            let doubled = digit * 2;
+            if doubled > 9 { doubled - 9 } else { doubled }
+        } else {
+            digit
+        }
+    }).sum();
+    number.push(char::from(b'0' + ((10 - sum % 10) % 10) as u8));
+    number
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    /// 按Luhn算法校验整个卡号（含校验位）：从右往左每隔一位乘2，各位之和是10的倍数
+    fn luhn_valid(number: &str) -> bool {
+        let mut sum = 0;
+        for (i, c) in number.chars().rev().enumerate() {
+            let mut digit = match c.to_digit(10) {
+                Some(digit) => digit,
+                None => return false,
+            };
+            if i % 2 == 1 {
+                digit *= 2;
+                if digit > 9 {
+                    digit -= 9;
+                }
+            }
+            sum += digit;
+        }
+        sum % 10 == 0
+    }
+
+    /// 按ISO 7064 MOD 97-10校验IBAN：前4位移到末尾，字母换算为两位数后整个数字对97取余为1
+    fn iban_valid(iban: &str) -> bool {
+        let (head, tail) = iban.split_at(4);
+        let mut numeric = String::new();
+        for c in tail.chars().chain(head.chars()) {
+            match c {
+                '0'..='9' => numeric.push(c),
+                'A'..='Z' => numeric.push_str(&(c as u32 - 'A' as u32 + 10).to_string()),
+                _ => return false,
+            }
+        }
+        // 每次取7位数字，与上一段的余数（最多两位）拼接后取余，避免大数运算
+        let mut remainder = 0u64;
+        for chunk in numeric.as_bytes().chunks(7) {
+            let chunk = std::str::from_utf8(chunk).unwrap();
+            remainder = format!("{}{}", remainder, chunk).parse::<u64>().unwrap() % 97;
+        }
+        remainder == 1
+    }
+
+    #[test]
+    fn reference_checks_accept_known_numbers() {
+        assert!(luhn_valid("4111111111111111"));
+        assert!(!luhn_valid("4111111111111112"));
+        assert!(iban_valid("GB82WEST12345698765432"));
+        assert!(iban_valid("DE89370400440532013000"));
+        assert!(!iban_valid("GB82WEST12345698765433"));
+    }
+
+    #[test]
+    fn card_numbers_pass_luhn_check() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for &(prefix, len) in ZH_CARD_PREFIXES.iter().chain(EN_CARD_PREFIXES) {
+            for _ in 0..200 {
+                let number = generate_card_number(prefix, len, &mut rng);
+                assert_eq!(number.len(), len);
+                assert!(number.starts_with(prefix));
+                assert!(luhn_valid(&number), "{}", number);
+            }
+        }
+    }
+
+    #[test]
+    fn ibans_pass_mod_97_check() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..1000 {
+            let iban = generate_iban(&mut rng);
+            let &(_, letters, numbers) = IBAN_COUNTRIES.iter()
+                .find(|(country, _, _)| iban.starts_with(country))
+                .unwrap();
+            assert_eq!(iban.len(), 4 + letters + numbers, "{}", iban);
+            assert!(iban_valid(&iban), "{}", iban);
+        }
+    }
+}
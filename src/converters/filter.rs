@@ -0,0 +1,723 @@
+use crate::error::{Result, TransmutaError};
+use arrow::array::{Array, ArrayRef, AsArray, BooleanArray, Datum, Scalar, StringArray};
+use arrow::compute::kernels::cmp;
+use arrow::compute::kernels::comparison::{ilike, like, nilike, nlike, regexp_is_match_scalar};
+use arrow::compute::{and_kleene, can_cast_types, cast, cast_with_options, filter_record_batch, is_not_null, is_null, not, or_kleene, CastOptions};
+use arrow::datatypes::{DataType, SchemaRef, TimeUnit};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use std::fmt;
+use std::sync::Arc;
+
+/// 按条件表达式过滤行，例如 `status == 'active' && amount > 100`
+///
+/// 支持比较（== = != <> < <= > >=）、逻辑运算（&& and、|| or、! not）、括号、
+/// `in (...)`、`like`/`ilike`、正则匹配（`~`或`regexp`）、`is null`/`is not null`，
+/// 以及`date '2024-01-31'`、`timestamp '2024-01-31 08:00:00'`形式的日期和时间戳值。
+/// 条件对每个RecordBatch用Arrow计算内核整体求值，结果为空值的行不保留。
+#[derive(Debug, Clone)]
+pub struct Filter {
+    source: String,
+    expr: Expr,
+}
+
+impl Filter {
+    /// 解析条件表达式
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        if let Some((position, _)) = parser.tokens.get(parser.pos) {
+            return Err(syntax_error(*position, "条件已结束，后面有多余的内容"));
+        }
+        Ok(Filter { source: source.to_string(), expr })
+    }
+
+    /// 检查条件中的列和值是否与schema匹配，在处理数据之前发现错误
+    pub fn validate(&self, schema: &SchemaRef) -> Result<()> {
+        self.evaluate(&RecordBatch::new_empty(schema.clone())).map(|_| ())
+    }
+
+    /// 计算每一行是否满足条件，不满足或结果为空值的行为false或空值
+    pub fn evaluate(&self, batch: &RecordBatch) -> Result<BooleanArray> {
+        self.expr.evaluate(batch)
+    }
+
+    /// 只保留满足条件的行
+    pub fn apply(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        let mask = self.evaluate(batch)?;
+        Ok(filter_record_batch(batch, &mask)?)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// 条件中的常量值，保留原始文本以便按列的类型精确解析
+#[derive(Debug, Clone)]
+enum Literal {
+    Str(String),
+    Number(String),
+    Bool(bool),
+    Date(String),
+    Timestamp(String),
+}
+
+impl Literal {
+    fn text(&self) -> String {
+        match self {
+            Literal::Str(s) | Literal::Number(s) | Literal::Date(s) | Literal::Timestamp(s) => s.clone(),
+            Literal::Bool(b) => b.to_string(),
+        }
+    }
+
+    /// 值本身的类型，列无法按值的文本解析时把列转换为该类型再比较
+    fn data_type(&self) -> DataType {
+        match self {
+            Literal::Str(_) => DataType::Utf8,
+            Literal::Number(s) if s.parse::<i64>().is_ok() => DataType::Int64,
+            Literal::Number(_) => DataType::Float64,
+            Literal::Bool(_) => DataType::Boolean,
+            Literal::Date(_) => DataType::Date32,
+            Literal::Timestamp(_) => DataType::Timestamp(TimeUnit::Millisecond, None),
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Str(s) => write!(f, "'{}'", s),
+            Literal::Number(s) => write!(f, "{}", s),
+            Literal::Bool(b) => write!(f, "{}", b),
+            Literal::Date(s) => write!(f, "date '{}'", s),
+            Literal::Timestamp(s) => write!(f, "timestamp '{}'", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Column(String),
+    Literal(Literal),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl CompareOp {
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "==" | "=" => Some(CompareOp::Eq),
+            "!=" | "<>" => Some(CompareOp::NotEq),
+            "<" => Some(CompareOp::Lt),
+            "<=" => Some(CompareOp::LtEq),
+            ">" => Some(CompareOp::Gt),
+            ">=" => Some(CompareOp::GtEq),
+            _ => None,
+        }
+    }
+
+    /// 交换两侧时对应的运算符，用于`100 < amount`形式的比较
+    fn flip(self) -> Self {
+        match self {
+            CompareOp::Lt => CompareOp::Gt,
+            CompareOp::LtEq => CompareOp::GtEq,
+            CompareOp::Gt => CompareOp::Lt,
+            CompareOp::GtEq => CompareOp::LtEq,
+            other => other,
+        }
+    }
+
+    fn apply(self, left: &dyn Datum, right: &dyn Datum) -> Result<BooleanArray> {
+        let result = match self {
+            CompareOp::Eq => cmp::eq(left, right),
+            CompareOp::NotEq => cmp::neq(left, right),
+            CompareOp::Lt => cmp::lt(left, right),
+            CompareOp::LtEq => cmp::lt_eq(left, right),
+            CompareOp::Gt => cmp::gt(left, right),
+            CompareOp::GtEq => cmp::gt_eq(left, right),
+        };
+        Ok(result?)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    /// 布尔类型的列
+    Column(String),
+    Compare { left: Operand, op: CompareOp, right: Operand },
+    In { column: String, values: Vec<Literal>, negated: bool },
+    Like { column: String, pattern: String, case_insensitive: bool, negated: bool },
+    Regex { column: String, pattern: String, negated: bool },
+    IsNull { column: String, negated: bool },
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn evaluate(&self, batch: &RecordBatch) -> Result<BooleanArray> {
+        match self {
+            Expr::Column(name) => {
+                let array = column(batch, name)?;
+                match array.data_type() {
+                    DataType::Boolean => Ok(array.as_boolean().clone()),
+                    other => Err(TransmutaError::InvalidArgument(format!(
+                        "列'{}'的类型为{}，不能直接作为条件，请使用比较运算", name, other
+                    ))),
+                }
+            }
+            Expr::Compare { left, op, right } => match (left, right) {
+                (Operand::Column(left), Operand::Column(right)) => {
+                    let left = column(batch, left)?;
+                    let right_array = column(batch, right)?;
+                    let right_array = if right_array.data_type() == left.data_type() {
+                        right_array
+                    } else if can_cast_types(right_array.data_type(), left.data_type()) {
+                        cast(&right_array, left.data_type())?
+                    } else {
+                        return Err(TransmutaError::InvalidArgument(format!(
+                            "列'{}'的类型{}不能与类型{}比较", right, right_array.data_type(), left.data_type()
+                        )));
+                    };
+                    op.apply(&left, &right_array)
+                }
+                (Operand::Column(name), Operand::Literal(value)) => compare_value(batch, name, *op, value),
+                (Operand::Literal(value), Operand::Column(name)) => compare_value(batch, name, op.flip(), value),
+                (Operand::Literal(_), Operand::Literal(_)) => unreachable!("解析时已排除两侧都是值的比较"),
+            },
+            Expr::In { column: name, values, negated } => {
+                let mut result: Option<BooleanArray> = None;
+                for value in values {
+                    let matched = compare_value(batch, name, CompareOp::Eq, value)?;
+                    result = Some(match result {
+                        Some(result) => or_kleene(&result, &matched)?,
+                        None => matched,
+                    });
+                }
+                let result = result.expect("解析时保证in列表不为空");
+                Ok(if *negated { not(&result)? } else { result })
+            }
+            Expr::Like { column: name, pattern, case_insensitive, negated } => {
+                let array = utf8_column(batch, name)?;
+                let pattern = Scalar::new(StringArray::from(vec![pattern.as_str()]));
+                let result = match (case_insensitive, negated) {
+                    (false, false) => like(&array, &pattern),
+                    (true, false) => ilike(&array, &pattern),
+                    (false, true) => nlike(&array, &pattern),
+                    (true, true) => nilike(&array, &pattern),
+                };
+                Ok(result?)
+            }
+            Expr::Regex { column: name, pattern, negated } => {
+                let array = utf8_column(batch, name)?;
+                let result = regexp_is_match_scalar(array.as_string::<i32>(), pattern, None)
+                    .map_err(|e| match e {
+                        ArrowError::ComputeError(message) if message.contains("did not compile") => TransmutaError::InvalidArgument(
+                            format!("--where条件中的正则表达式'{}'无效", pattern)
+                        ),
+                        e => e.into(),
+                    })?;
+                Ok(if *negated { not(&result)? } else { result })
+            }
+            Expr::IsNull { column: name, negated } => {
+                let array = column(batch, name)?;
+                Ok(if *negated { is_not_null(&array)? } else { is_null(&array)? })
+            }
+            Expr::Not(expr) => Ok(not(&expr.evaluate(batch)?)?),
+            Expr::And(left, right) => Ok(and_kleene(&left.evaluate(batch)?, &right.evaluate(batch)?)?),
+            Expr::Or(left, right) => Ok(or_kleene(&left.evaluate(batch)?, &right.evaluate(batch)?)?),
+        }
+    }
+}
+
+/// 按列名取出批次中的列
+fn column(batch: &RecordBatch, name: &str) -> Result<ArrayRef> {
+    match batch.schema().index_of(name) {
+        Ok(index) => Ok(batch.column(index).clone()),
+        Err(_) => Err(TransmutaError::InvalidArgument(format!(
+            "--where条件中的列'{}'不存在，可用的列: {}",
+            name,
+            batch.schema().fields().iter().map(|field| field.name().as_str()).collect::<Vec<_>>().join(", ")
+        ))),
+    }
+}
+
+/// 取出列并转换为字符串，用于like和正则匹配
+fn utf8_column(batch: &RecordBatch, name: &str) -> Result<ArrayRef> {
+    let array = column(batch, name)?;
+    match array.data_type() {
+        DataType::Utf8 => Ok(array),
+        _ => Ok(cast(&array, &DataType::Utf8)?),
+    }
+}
+
+/// 比较列和常量值
+///
+/// 字符串值按列的类型解析，例如`born > '1990-01-01'`按日期比较，无法解析时报错；数值、布尔和日期值与字符串列比较时，
+/// 把列转换为值的类型，无法转换的单元格视为空值。值无法按列的类型解析时（如整数列与3.5比较），同样转换列的类型。
+fn compare_value(batch: &RecordBatch, name: &str, op: CompareOp, value: &Literal) -> Result<BooleanArray> {
+    let array = column(batch, name)?;
+    let column_type = array.data_type();
+    let text: ArrayRef = Arc::new(StringArray::from(vec![value.text()]));
+    let strict = CastOptions { safe: false, ..Default::default() };
+
+    let is_text_column = matches!(column_type, DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View);
+    if !is_text_column || matches!(value, Literal::Str(_)) {
+        match cast_with_options(&text, column_type, &strict) {
+            Ok(parsed) => return op.apply(&array, &Scalar::new(parsed)),
+            // 字符串值不退回按文本比较，否则数值列与'abc'比较时会静默地按字典序比较
+            Err(_) if matches!(value, Literal::Str(_)) => return Err(TransmutaError::InvalidArgument(format!(
+                "--where条件中的值{}无法解析为列'{}'的类型{}", value, name, column_type
+            ))),
+            Err(_) => {}
+        }
+    }
+
+    let value_type = value.data_type();
+    let parsed = cast_with_options(&text, &value_type, &strict)
+        .map_err(|_| TransmutaError::InvalidArgument(format!("--where条件中的值{}无效", value)))?;
+    if !can_cast_types(column_type, &value_type) {
+        return Err(TransmutaError::InvalidArgument(format!(
+            "列'{}'的类型{}不能与{}比较", name, column_type, value
+        )));
+    }
+    op.apply(&cast(&array, &value_type)?, &Scalar::new(parsed))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// 列名或关键字
+    Word(String),
+    /// 用双引号或反引号括起的列名，不会被当作关键字
+    Quoted(String),
+    Str(String),
+    Number(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(s) | Token::Number(s) => write!(f, "{}", s),
+            Token::Quoted(s) => write!(f, "\"{}\"", s),
+            Token::Str(s) => write!(f, "'{}'", s),
+            Token::Symbol(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// 运算符和标点，较长的在前以便优先匹配
+const SYMBOLS: [&str; 17] = ["==", "!=", "<>", "<=", ">=", "&&", "||", "!~", "=", "<", ">", "!", "~", "-", "(", ")", ","];
+
+/// 关键字，作为列名时需要用双引号或反引号括起
+const KEYWORDS: [&str; 11] = ["and", "or", "not", "in", "like", "ilike", "regexp", "is", "null", "true", "false"];
+
+fn syntax_error(position: usize, message: &str) -> TransmutaError {
+    TransmutaError::InvalidArgument(format!("--where条件语法错误（第{}个字符）: {}", position + 1, message))
+}
+
+/// 把条件拆分为单词、字符串、数字和运算符，同时记录每个单词的字符位置
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let token = if c == '\'' || c == '"' || c == '`' {
+            // 引号内连续两个引号表示引号本身
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Some(&q) if q == c && chars.get(i + 1) == Some(&c) => {
+                        text.push(c);
+                        i += 2;
+                    }
+                    Some(&q) if q == c => {
+                        i += 1;
+                        break;
+                    }
+                    Some(&other) => {
+                        text.push(other);
+                        i += 1;
+                    }
+                    None => return Err(syntax_error(start, "引号没有闭合")),
+                }
+            }
+            if c == '\'' { Token::Str(text) } else { Token::Quoted(text) }
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // 科学计数法，例如1e6、2.5E-3
+            if matches!(chars.get(i), Some('e' | 'E')) {
+                let sign = usize::from(matches!(chars.get(i + 1), Some('+' | '-')));
+                if chars.get(i + 1 + sign).is_some_and(char::is_ascii_digit) {
+                    i += 1 + sign;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            Token::Number(chars[start..i].iter().collect())
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            Token::Word(chars[start..i].iter().collect())
+        } else {
+            let rest: String = chars[i..].iter().take(2).collect();
+            match SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+                Some(symbol) => {
+                    i += symbol.chars().count();
+                    Token::Symbol(symbol)
+                }
+                None => return Err(syntax_error(start, &format!("无法识别的字符'{}'", c))),
+            }
+        };
+        tokens.push((start, token));
+    }
+
+    Ok(tokens)
+}
+
+/// 递归下降解析器，优先级从低到高为 or、and、not、比较
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    /// 当前位置，到达末尾时为最后一个单词之后
+    fn position(&self) -> usize {
+        match self.tokens.get(self.pos) {
+            Some((position, _)) => *position,
+            None => self.tokens.last().map_or(0, |(position, token)| position + token.to_string().chars().count()),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(_, token)| token.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(syntax_error(self.position(), &format!("此处应为'{}'", symbol)))
+        }
+    }
+
+    fn expect_string(&mut self, after: &str) -> Result<String> {
+        let position = self.position();
+        match self.next() {
+            Some(Token::Str(s)) => Ok(s),
+            _ => Err(syntax_error(position, &format!("{}之后应为单引号括起的字符串", after))),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.eat_keyword("or") || self.eat_symbol("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_not()?;
+        while self.eat_keyword("and") || self.eat_symbol("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr> {
+        if self.eat_keyword("not") || self.eat_symbol("!") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        if self.eat_symbol("(") {
+            let expr = self.parse_or()?;
+            self.expect_symbol(")")?;
+            return Ok(expr);
+        }
+        self.parse_predicate()
+    }
+
+    fn parse_predicate(&mut self) -> Result<Expr> {
+        let position = self.position();
+        let left = self.parse_operand()?;
+
+        if let Some(op) = match self.peek() {
+            Some(Token::Symbol(symbol)) => CompareOp::from_symbol(symbol),
+            _ => None,
+        } {
+            self.pos += 1;
+            let right_position = self.position();
+            let right = self.parse_operand()?;
+            if let (Operand::Literal(_), Operand::Literal(_)) = (&left, &right) {
+                return Err(syntax_error(right_position, "比较的两侧至少有一侧应为列名"));
+            }
+            return Ok(Expr::Compare { left, op, right });
+        }
+
+        // 其余谓词的左侧都必须是列名
+        let column = match left {
+            Operand::Column(name) => name,
+            Operand::Literal(value) => {
+                return Err(syntax_error(position, &format!("值{}之后应为比较运算符", value)));
+            }
+        };
+
+        if self.eat_keyword("is") {
+            let negated = self.eat_keyword("not");
+            if !self.eat_keyword("null") {
+                return Err(syntax_error(self.position(), "is之后应为null或not null"));
+            }
+            return Ok(Expr::IsNull { column, negated });
+        }
+
+        if self.eat_symbol("~") {
+            return Ok(Expr::Regex { column, pattern: self.expect_string("~")?, negated: false });
+        }
+        if self.eat_symbol("!~") {
+            return Ok(Expr::Regex { column, pattern: self.expect_string("!~")?, negated: true });
+        }
+
+        let negated = self.eat_keyword("not");
+        if self.eat_keyword("in") {
+            self.expect_symbol("(")?;
+            let mut values = Vec::new();
+            loop {
+                let value_position = self.position();
+                match self.parse_operand()? {
+                    Operand::Literal(value) => values.push(value),
+                    Operand::Column(_) => return Err(syntax_error(value_position, "in列表中只能是值")),
+                }
+                if !self.eat_symbol(",") {
+                    break;
+                }
+            }
+            self.expect_symbol(")")?;
+            return Ok(Expr::In { column, values, negated });
+        }
+        if self.eat_keyword("like") {
+            return Ok(Expr::Like { column, pattern: self.expect_string("like")?, case_insensitive: false, negated });
+        }
+        if self.eat_keyword("ilike") {
+            return Ok(Expr::Like { column, pattern: self.expect_string("ilike")?, case_insensitive: true, negated });
+        }
+        if self.eat_keyword("regexp") {
+            return Ok(Expr::Regex { column, pattern: self.expect_string("regexp")?, negated });
+        }
+        if negated {
+            return Err(syntax_error(self.position(), "not之后应为in、like、ilike或regexp"));
+        }
+
+        Ok(Expr::Column(column))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand> {
+        let position = self.position();
+        let operand = match self.next() {
+            Some(Token::Quoted(name)) => Operand::Column(name),
+            Some(Token::Str(s)) => Operand::Literal(Literal::Str(s)),
+            Some(Token::Number(n)) => Operand::Literal(Literal::Number(n)),
+            Some(Token::Symbol("-")) => match self.next() {
+                Some(Token::Number(n)) => Operand::Literal(Literal::Number(format!("-{}", n))),
+                _ => return Err(syntax_error(position, "负号之后应为数字")),
+            },
+            Some(Token::Word(word)) => {
+                let lower = word.to_lowercase();
+                match lower.as_str() {
+                    "true" | "false" => Operand::Literal(Literal::Bool(lower == "true")),
+                    "null" => return Err(syntax_error(position, "与null比较的结果总是空值，请使用is null或is not null")),
+                    // date和timestamp之后紧跟字符串时为日期和时间戳值，否则为列名
+                    "date" | "timestamp" => match self.peek().cloned() {
+                        Some(Token::Str(s)) => {
+                            self.pos += 1;
+                            Operand::Literal(if lower == "date" { Literal::Date(s) } else { Literal::Timestamp(s) })
+                        }
+                        _ => Operand::Column(word),
+                    },
+                    _ if KEYWORDS.contains(&lower.as_str()) => {
+                        return Err(syntax_error(position, &format!(
+                            "此处应为列名或值，而不是关键字{}（作为列名时请用双引号括起）", word
+                        )));
+                    }
+                    _ => Operand::Column(word),
+                }
+            }
+            Some(token) => return Err(syntax_error(position, &format!("此处应为列名或值，而不是'{}'", token))),
+            None => return Err(syntax_error(position, "条件不完整，此处应为列名或值")),
+        };
+        Ok(operand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Date32Array, Int64Array, TimestampMillisecondArray};
+    use arrow::datatypes::{Field, Schema};
+
+    /// 第三行的name、amount、born和ts都是空值
+    fn batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Boolean, true),
+            Field::new("b", DataType::Boolean, true),
+            Field::new("c", DataType::Boolean, true),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("amount", DataType::Int64, true),
+            Field::new("born", DataType::Date32, true),
+            Field::new("ts", DataType::Timestamp(TimeUnit::Millisecond, None), true),
+        ]));
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(BooleanArray::from(vec![Some(true), Some(false), Some(false), None])),
+            Arc::new(BooleanArray::from(vec![false, true, false, true])),
+            Arc::new(BooleanArray::from(vec![false, true, true, false])),
+            Arc::new(StringArray::from(vec![Some("Alice"), Some("bob"), None, Some("Carol")])),
+            Arc::new(Int64Array::from(vec![Some(1), Some(3), None, Some(4)])),
+            // 2024-01-01、2024-02-01、空值、2023-12-31
+            Arc::new(Date32Array::from(vec![Some(19723), Some(19754), None, Some(19722)])),
+            // 2024-01-01 00:00:00、2024-01-01 12:00:00、空值、2023-12-31 23:59:59
+            Arc::new(TimestampMillisecondArray::from(vec![
+                Some(1_704_067_200_000), Some(1_704_110_400_000), None, Some(1_704_067_199_000),
+            ])),
+        ];
+        RecordBatch::try_new(schema, columns).unwrap()
+    }
+
+    fn evaluate(source: &str) -> Vec<Option<bool>> {
+        Filter::parse(source).unwrap().evaluate(&batch()).unwrap().iter().collect()
+    }
+
+    /// 解析或按schema检查条件时的错误信息
+    fn error(source: &str) -> String {
+        match Filter::parse(source).and_then(|filter| filter.validate(&batch().schema())) {
+            Ok(()) => panic!("条件{}应当报错", source),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(evaluate("a || b && c"), vec![Some(true), Some(true), Some(false), None]);
+        assert_eq!(evaluate("(a || b) && c"), vec![Some(false), Some(true), Some(false), Some(false)]);
+        assert_eq!(evaluate("a or b and c"), evaluate("a || b && c"));
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and() {
+        assert_eq!(evaluate("!a && b"), vec![Some(false), Some(true), Some(false), None]);
+        assert_eq!(evaluate("not (a or c)"), vec![Some(false), Some(false), Some(false), None]);
+        assert_eq!(evaluate("!!a"), evaluate("a"));
+    }
+
+    #[test]
+    fn in_and_not_in_leave_nulls_null() {
+        assert_eq!(evaluate("amount in (1, 4)"), vec![Some(true), Some(false), None, Some(true)]);
+        assert_eq!(evaluate("amount not in (1, 4)"), vec![Some(false), Some(true), None, Some(false)]);
+        assert_eq!(evaluate("name in ('bob', 'Carol')"), vec![Some(false), Some(true), None, Some(true)]);
+        assert_eq!(evaluate("name is null"), vec![Some(false), Some(false), Some(true), Some(false)]);
+    }
+
+    #[test]
+    fn like_ilike_and_regex() {
+        assert_eq!(evaluate("name like 'A%'"), vec![Some(true), Some(false), None, Some(false)]);
+        assert_eq!(evaluate("name not like '%o%'"), vec![Some(true), Some(false), None, Some(false)]);
+        assert_eq!(evaluate("name ilike 'B%'"), vec![Some(false), Some(true), None, Some(false)]);
+        assert_eq!(evaluate("name ~ '^[A-Z]'"), vec![Some(true), Some(false), None, Some(true)]);
+        assert_eq!(evaluate("name !~ 'o'"), vec![Some(true), Some(false), None, Some(false)]);
+        assert_eq!(evaluate("name regexp 'l{2}'"), vec![Some(false), Some(false), None, Some(false)]);
+    }
+
+    #[test]
+    fn invalid_regex_is_reported() {
+        assert!(error("name ~ '('").contains("正则表达式'('无效"));
+    }
+
+    #[test]
+    fn date_literals_against_date_and_timestamp_columns() {
+        assert_eq!(evaluate("born >= date '2024-01-01'"), vec![Some(true), Some(true), None, Some(false)]);
+        assert_eq!(evaluate("born = '2024-02-01'"), vec![Some(false), Some(true), None, Some(false)]);
+        assert_eq!(evaluate("ts >= date '2024-01-01'"), vec![Some(true), Some(true), None, Some(false)]);
+        assert_eq!(
+            evaluate("ts < timestamp '2024-01-01 12:00:00'"),
+            vec![Some(true), Some(false), None, Some(true)]
+        );
+        assert_eq!(evaluate("date '2024-01-15' < born"), vec![Some(false), Some(true), None, Some(false)]);
+    }
+
+    #[test]
+    fn integer_column_against_fractional_value() {
+        assert_eq!(evaluate("amount > 3.5"), vec![Some(false), Some(false), None, Some(true)]);
+        assert_eq!(evaluate("amount = 3.0"), vec![Some(false), Some(true), None, Some(false)]);
+        assert_eq!(evaluate("amount > '2'"), vec![Some(false), Some(true), None, Some(true)]);
+    }
+
+    #[test]
+    fn unparsable_string_against_typed_column_is_an_error() {
+        assert!(error("amount > 'abc'").contains("无法解析为列'amount'的类型Int64"));
+        assert!(error("born = 'yesterday'").contains("无法解析为列'born'的类型Date32"));
+    }
+
+    #[test]
+    fn unknown_column_is_an_error() {
+        assert!(error("missing = 1").contains("列'missing'不存在"));
+    }
+
+    #[test]
+    fn syntax_errors_report_positions() {
+        assert!(error("age between 1").contains("第5个字符"));
+        assert!(error("age == null").contains("第8个字符"));
+        assert!(error("city = '北京' &&").contains("第15个字符"));
+        assert!(error("'a' = 'b'").contains("第7个字符"));
+        assert!(error("amount in (1, 2").contains("此处应为')'"));
+    }
+}
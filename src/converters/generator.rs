@@ -0,0 +1,945 @@
+use crate::cli::Locale;
+use crate::error::{Result, TransmutaError};
+use super::datagen::{ColumnDefinition, DataType, DistributionKind, DistributionSpec};
+use super::fake::FakeKind;
+use super::infer::{self, TypedColumnBuilder};
+use arrow::array::*;
+use arrow::compute::take;
+use arrow::datatypes::{i256, IntervalMonthDayNano, DECIMAL128_MAX_PRECISION};
+use chrono::{NaiveTime, Timelike};
+use rand::distributions::{Alphanumeric, Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand_distr::{Normal, Zipf};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// 正态分布采样超出取值范围时的最大重试次数，之后截断到范围内
+const MAX_NORMAL_ATTEMPTS: usize = 100;
+
+/// 生成不重复的值时，连续遇到重复值的最大次数
+const MAX_UNIQUE_ATTEMPTS: usize = 1000;
+
+/// 正则表达式中*、+等不限次数的重复最多展开的次数
+const MAX_REGEX_REPEAT: u32 = 16;
+
+/// 2000-01-01对应的天数，日期的默认最小值
+const DEFAULT_MIN_DAYS: i128 = 10957;
+
+/// 日期的默认最大值（约为2022年）
+const DEFAULT_MAX_DAYS: i128 = 19000;
+
+/// 2000-01-01 00:00:00对应的毫秒数，时间戳的默认最小值
+const DEFAULT_MIN_TIMESTAMP_MS: i128 = 946_684_800_000;
+
+/// 2025-01-01 00:00:00对应的毫秒数，时间戳的默认最大值。使用固定值使相同的种子总是生成相同的数据
+const DEFAULT_MAX_TIMESTAMP_MS: i128 = 1_735_689_600_000;
+
+/// 一天的毫秒数和纳秒数
+const MS_PER_DAY: i128 = 86_400_000;
+const NANOS_PER_DAY: i128 = 86_400_000_000_000;
+
+/// 持续时间的默认最大值（约1年的纳秒数）
+const DEFAULT_MAX_DURATION_NANOS: i128 = 31_536_000_000_000_000;
+
+/// 单个随机值，日期、时间和高精度小数使用其底层整数表示
+#[derive(Debug, Clone)]
+enum Sample {
+    Int(i128),
+    Float(f64),
+    /// 未缩放的高精度小数的十进制字符串，用于超出i128范围的decimal256
+    Digits(String),
+    Bool(bool),
+    Text(String),
+    Bytes(Vec<u8>),
+    Interval(IntervalMonthDayNano),
+}
+
+/// 用于检查唯一性的键
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum UniqueKey {
+    Int(i128),
+    Bytes(Vec<u8>),
+}
+
+impl Sample {
+    fn unique_key(&self) -> UniqueKey {
+        match self {
+            Sample::Int(v) => UniqueKey::Int(*v),
+            Sample::Float(v) => UniqueKey::Int(v.to_bits() as i128),
+            Sample::Bool(v) => UniqueKey::Int(*v as i128),
+            Sample::Digits(v) | Sample::Text(v) => UniqueKey::Bytes(v.as_bytes().to_vec()),
+            Sample::Bytes(v) => UniqueKey::Bytes(v.clone()),
+            Sample::Interval(v) => UniqueKey::Int(
+                ((v.months as i128) << 96) ^ ((v.days as i128) << 64) ^ v.nanoseconds as i128
+            ),
+        }
+    }
+
+    fn into_int(self) -> i128 {
+        match self {
+            Sample::Int(v) => v,
+            _ => unreachable!("整数列的采样值应为整数"),
+        }
+    }
+
+    fn into_float(self) -> f64 {
+        match self {
+            Sample::Float(v) => v,
+            _ => unreachable!("浮点数列的采样值应为浮点数"),
+        }
+    }
+
+    fn into_bool(self) -> bool {
+        match self {
+            Sample::Bool(v) => v,
+            _ => unreachable!("布尔列的采样值应为布尔值"),
+        }
+    }
+
+    fn into_text(self) -> String {
+        match self {
+            Sample::Text(v) => v,
+            _ => unreachable!("字符串列的采样值应为字符串"),
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Sample::Bytes(v) => v,
+            _ => unreachable!("二进制列的采样值应为二进制数据"),
+        }
+    }
+
+    fn into_interval(self) -> IntervalMonthDayNano {
+        match self {
+            Sample::Interval(v) => v,
+            _ => unreachable!("时间间隔列的采样值应为时间间隔"),
+        }
+    }
+
+    fn into_i256(self) -> i256 {
+        match self {
+            Sample::Int(v) => i256::from_i128(v),
+            Sample::Digits(v) => i256::from_string(&v).expect("不超过76位的整数"),
+            _ => unreachable!("高精度小数列的采样值应为整数"),
+        }
+    }
+}
+
+/// 数值的分布
+#[derive(Debug, Clone)]
+enum NumberDistribution {
+    Uniform,
+    Normal(Normal<f64>),
+    /// Zipf分布，取值范围内越小的值出现得越频繁
+    Zipf(Zipf<f64>),
+}
+
+/// 整数取值范围，日期、时间、持续时间和高精度小数也按其底层整数表示取值
+#[derive(Debug, Clone)]
+struct IntSampler {
+    min: i128,
+    max: i128,
+    distribution: NumberDistribution,
+}
+
+impl IntSampler {
+    fn sample(&self, rng: &mut StdRng) -> i128 {
+        match &self.distribution {
+            NumberDistribution::Uniform => rng.gen_range(self.min..=self.max),
+            NumberDistribution::Normal(normal) => {
+                // 超出范围的值重新采样，多次仍超出时截断到范围内
+                let mut value = 0.0;
+                for _ in 0..MAX_NORMAL_ATTEMPTS {
+                    value = normal.sample(rng).round();
+                    if value >= self.min as f64 && value <= self.max as f64 {
+                        break;
+                    }
+                }
+                (value as i128).clamp(self.min, self.max)
+            }
+            NumberDistribution::Zipf(zipf) => self.min + zipf.sample(rng) as i128 - 1,
+        }
+    }
+}
+
+/// 浮点数取值范围
+#[derive(Debug, Clone)]
+struct FloatSampler {
+    min: f64,
+    max: f64,
+    distribution: NumberDistribution,
+}
+
+impl FloatSampler {
+    fn sample(&self, rng: &mut StdRng) -> f64 {
+        match &self.distribution {
+            NumberDistribution::Normal(normal) => {
+                let mut value = 0.0;
+                for _ in 0..MAX_NORMAL_ATTEMPTS {
+                    value = normal.sample(rng);
+                    if value >= self.min && value <= self.max {
+                        break;
+                    }
+                }
+                value.clamp(self.min, self.max)
+            }
+            _ => rng.gen_range(self.min..=self.max),
+        }
+    }
+}
+
+/// 单列的取值方式
+#[derive(Debug, Clone)]
+enum ValueSampler {
+    Int(IntSampler),
+    Float(FloatSampler),
+    /// 未指定范围的高精度小数，生成有效位数为precision的随机值
+    DecimalDigits(u8),
+    Boolean,
+    Text { min_len: usize, max_len: usize },
+    Regex(rand_regex::Regex),
+    Uuid,
+    Binary { min_len: usize, max_len: usize },
+    Interval,
+    Null,
+    /// 按语言区域生成的姓名、地址等
+    Fake(FakeKind, Locale),
+    /// 按权重选择enum中的值，采样值为其序号
+    Choice(WeightedIndex<f64>),
+    /// 从被引用列的n个值中等概率选择，采样值为其序号
+    Index(usize),
+}
+
+impl ValueSampler {
+    fn sample(&self, rng: &mut StdRng) -> Sample {
+        match self {
+            ValueSampler::Int(sampler) => Sample::Int(sampler.sample(rng)),
+            ValueSampler::Float(sampler) => Sample::Float(sampler.sample(rng)),
+            ValueSampler::DecimalDigits(precision) => {
+                let digits: String = (0..*precision)
+                    .map(|_| char::from(b'0' + rng.gen_range(0..10u8)))
+                    .collect();
+                Sample::Digits(if rng.gen_bool(0.5) { format!("-{}", digits) } else { digits })
+            }
+            ValueSampler::Boolean => Sample::Bool(rng.gen()),
+            ValueSampler::Text { min_len, max_len } => {
+                let len = rng.gen_range(*min_len..=*max_len);
+                Sample::Text(rng.sample_iter(&Alphanumeric).take(len).map(char::from).collect())
+            }
+            ValueSampler::Regex(regex) => Sample::Text(rng.sample(regex)),
+            ValueSampler::Uuid => Sample::Text(generate_random_uuid(rng)),
+            ValueSampler::Binary { min_len, max_len } => {
+                let mut bytes = vec![0u8; rng.gen_range(*min_len..=*max_len)];
+                rng.fill(&mut bytes[..]);
+                Sample::Bytes(bytes)
+            }
+            ValueSampler::Interval => {
+                // 月：-100年到+100年，日：-10年到+10年，时间：-1天到+1天（毫秒精度）
+                let months = rng.gen_range(-1200..1200);
+                let days = rng.gen_range(-3650..3650);
+                let millis: i64 = rng.gen_range(-86_400_000..86_400_000);
+                Sample::Interval(IntervalMonthDayNano::new(months, days, millis * 1_000_000))
+            }
+            ValueSampler::Null => Sample::Int(0),
+            ValueSampler::Fake(kind, locale) => Sample::Text(kind.sample(*locale, rng)),
+            ValueSampler::Choice(index) => Sample::Int(index.sample(rng) as i128),
+            ValueSampler::Index(n) => Sample::Int(rng.gen_range(0..*n) as i128),
+        }
+    }
+}
+
+// 生成随机UUID（v4）
+fn generate_random_uuid(rng: &mut StdRng) -> String {
+    use std::fmt::Write;
+
+    let mut bytes = [0u8; 16];
+    rng.fill(&mut bytes[..]);
+
+    // 设置版本(v4)和变体位
+    bytes[6] = (bytes[6] & 0x0f) | 0x40; // 版本4
+    bytes[8] = (bytes[8] & 0x3f) | 0x80; // 变体1
+
+    let mut uuid = String::with_capacity(36);
+
+    for (i, b) in bytes.iter().enumerate() {
+        // 在特定位置添加连字符
+        if i == 4 || i == 6 || i == 8 || i == 10 {
+            uuid.push('-');
+        }
+        write!(&mut uuid, "{:02x}", b).unwrap();
+    }
+
+    uuid
+}
+
+/// JSON值的文本形式，字符串不带引号
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 解析整数取值
+fn parse_int_value(value: &Value) -> Option<i128> {
+    match value {
+        Value::Number(n) => n.as_i64().map(i128::from).or_else(|| n.as_u64().map(i128::from)),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// 解析浮点数取值
+fn parse_float_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// 解析"HH:MM:SS"或"HH:MM:SS.fff"形式的时间
+fn parse_time_value(value: &Value) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.as_str()?.trim(), "%H:%M:%S%.f").ok()
+}
+
+/// 检查列定义中不适用于该类型的选项
+fn reject_options(col: &ColumnDefinition, options: &[(&str, bool)]) -> Result<()> {
+    match options.iter().find(|(_, present)| *present) {
+        Some((option, _)) => Err(TransmutaError::InvalidArgument(format!(
+            "列'{}'的类型{:?}不支持{}选项", col.name, col.data_type, option
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// 按列定义生成一列随机数据的生成器
+#[derive(Debug, Clone)]
+pub struct ColumnGenerator {
+    name: String,
+    data_type: arrow::datatypes::DataType,
+    sampler: ValueSampler,
+    /// enum中的值转换为列类型后的数组
+    choices: Option<ArrayRef>,
+    null_ratio: f64,
+    unique: bool,
+}
+
+/// unique列已生成过的值，跨批次保留，以保证整列不重复
+#[derive(Debug, Default)]
+pub struct UniqueValues(HashSet<UniqueKey>);
+
+impl ColumnGenerator {
+    /// 根据列定义创建生成器，并检查各选项是否适用于该列的类型，列定义未指定locale时使用default_locale
+    pub fn try_new(col: &ColumnDefinition, default_locale: Locale) -> Result<Self> {
+        let data_type = col.arrow_type();
+        if col.cardinality.is_some() {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'的cardinality只能与references一起使用", col.name
+            )));
+        }
+        if col.locale.is_some() && !col.data_type.is_semantic() {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'的类型{:?}不支持locale选项", col.name, col.data_type
+            )));
+        }
+
+        let null_ratio = col.null_ratio.unwrap_or(0.0);
+        if !(0.0..=1.0).contains(&null_ratio) {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'的null_ratio必须在0到1之间: {}", col.name, null_ratio
+            )));
+        }
+        if col.unique && matches!(col.data_type, DataType::Null) {
+            return Err(TransmutaError::InvalidArgument(format!("列'{}'为null类型，不能指定unique", col.name)));
+        }
+
+        let (sampler, choices) = match &col.values {
+            Some(values) => {
+                let (sampler, choices) = Self::choice_sampler(col, values, &data_type)?;
+                (sampler, Some(choices))
+            }
+            None => {
+                if col.weights.is_some() {
+                    return Err(TransmutaError::InvalidArgument(format!(
+                        "列'{}'的weights只能与enum一起使用", col.name
+                    )));
+                }
+                (Self::value_sampler(col, col.locale.unwrap_or(default_locale))?, None)
+            }
+        };
+
+        Ok(ColumnGenerator {
+            name: col.name.clone(),
+            data_type,
+            sampler,
+            choices,
+            null_ratio,
+            unique: col.unique,
+        })
+    }
+
+    /// 从被引用列已生成的值中随机取值的生成器，未指定cardinality的references列使用
+    pub fn try_new_reference(col: &ColumnDefinition, keys: ArrayRef) -> Result<Self> {
+        reject_reference_options(col)?;
+        let null_ratio = col.null_ratio.unwrap_or(0.0);
+        if !(0.0..=1.0).contains(&null_ratio) {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'的null_ratio必须在0到1之间: {}", col.name, null_ratio
+            )));
+        }
+        if keys.is_empty() {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'引用的{}没有生成任何值", col.name, col.references.as_deref().unwrap_or_default()
+            )));
+        }
+
+        Ok(ColumnGenerator {
+            name: col.name.clone(),
+            data_type: keys.data_type().clone(),
+            sampler: ValueSampler::Index(keys.len()),
+            choices: Some(keys),
+            null_ratio,
+            unique: col.unique,
+        })
+    }
+
+    /// 从enum中按权重取值的采样器，以及enum中的值转换为列类型后的数组
+    fn choice_sampler(
+        col: &ColumnDefinition,
+        values: &[Value],
+        data_type: &arrow::datatypes::DataType,
+    ) -> Result<(ValueSampler, ArrayRef)> {
+        reject_options(col, &[
+            ("min", col.min.is_some()),
+            ("max", col.max.is_some()),
+            ("length", col.length.is_some()),
+            ("regex", col.regex.is_some()),
+            ("distribution", col.distribution.is_some()),
+        ]).map_err(|_| TransmutaError::InvalidArgument(format!(
+            "列'{}'指定了enum，不能再指定min、max、length、regex或distribution", col.name
+        )))?;
+
+        if values.is_empty() {
+            return Err(TransmutaError::InvalidArgument(format!("列'{}'的enum不能为空", col.name)));
+        }
+        if !TypedColumnBuilder::supports(data_type) {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'的类型{}不支持enum", col.name, data_type
+            )));
+        }
+
+        // 按与--schema相同的规则把enum中的值转换为列类型
+        let mut builder = TypedColumnBuilder::new(data_type);
+        for value in values {
+            let text = value_text(value);
+            let appended = match value {
+                Value::Null => {
+                    builder.append_null();
+                    true
+                }
+                _ => builder.append(&text),
+            };
+            if !appended {
+                return Err(TransmutaError::InvalidArgument(format!(
+                    "列'{}'的enum值{}无法转换为{}", col.name, value, data_type
+                )));
+            }
+        }
+        let (choices, failures) = builder.finish()?;
+        if let Some((index, _)) = failures.first() {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'的enum值{}无法转换为{}", col.name, values[*index], data_type
+            )));
+        }
+
+        let weights = match &col.weights {
+            Some(weights) if weights.len() != values.len() => {
+                return Err(TransmutaError::InvalidArgument(format!(
+                    "列'{}'的weights有{}个，与enum的{}个值不一致", col.name, weights.len(), values.len()
+                )));
+            }
+            Some(weights) => weights.clone(),
+            None => vec![1.0; values.len()],
+        };
+        let index = WeightedIndex::new(&weights).map_err(|e| TransmutaError::InvalidArgument(format!(
+            "列'{}'的weights无效: {}", col.name, e
+        )))?;
+
+        Ok((ValueSampler::Choice(index), choices))
+    }
+
+    /// 按列类型和min、max、length等选项创建采样器，未指定的选项使用各类型的默认范围
+    fn value_sampler(col: &ColumnDefinition, locale: Locale) -> Result<ValueSampler> {
+        let range_options = [
+            ("min", col.min.is_some()),
+            ("max", col.max.is_some()),
+            ("distribution", col.distribution.is_some()),
+        ];
+        let length_options = [
+            ("length", col.length.is_some()),
+            ("regex", col.regex.is_some()),
+        ];
+
+        // 整数形式的取值：默认范围、类型允许的范围和取值的解析方式
+        let int_range = |default: (i128, i128), bounds: (i128, i128), parse: &dyn Fn(&Value) -> Option<i128>| {
+            reject_options(col, &length_options)?;
+            int_sampler(col, default, bounds, parse).map(ValueSampler::Int)
+        };
+
+        match col.data_type {
+            DataType::Integer => int_range((-1000, 1000), (i32::MIN as i128, i32::MAX as i128), &parse_int_value),
+            DataType::Int8 => int_range((i8::MIN as i128, i8::MAX as i128), (i8::MIN as i128, i8::MAX as i128), &parse_int_value),
+            DataType::Int16 => int_range((i16::MIN as i128, i16::MAX as i128), (i16::MIN as i128, i16::MAX as i128), &parse_int_value),
+            // 32位和64位整数默认只使用部分范围以避免极端值
+            DataType::Int32 => int_range(((i32::MIN / 2) as i128, (i32::MAX / 2) as i128), (i32::MIN as i128, i32::MAX as i128), &parse_int_value),
+            DataType::Int64 => int_range(((i64::MIN / 1000) as i128, (i64::MAX / 1000) as i128), (i64::MIN as i128, i64::MAX as i128), &parse_int_value),
+            DataType::UInt8 => int_range((0, u8::MAX as i128), (0, u8::MAX as i128), &parse_int_value),
+            DataType::UInt16 => int_range((0, u16::MAX as i128), (0, u16::MAX as i128), &parse_int_value),
+            DataType::UInt32 => int_range((0, (u32::MAX / 2) as i128), (0, u32::MAX as i128), &parse_int_value),
+            DataType::UInt64 => int_range((0, (u64::MAX / 1000) as i128), (0, u64::MAX as i128), &parse_int_value),
+
+            DataType::Float | DataType::Float32 | DataType::Float64 => {
+                reject_options(col, &length_options)?;
+                let default = match col.data_type {
+                    DataType::Float64 => (-1_000_000.0, 1_000_000.0),
+                    _ => (-1000.0, 1000.0),
+                };
+                float_sampler(col, default).map(ValueSampler::Float)
+            }
+
+            DataType::Decimal | DataType::Decimal128 | DataType::Decimal256 => {
+                reject_options(col, &length_options)?;
+                let (precision, scale) = match col.arrow_type() {
+                    arrow::datatypes::DataType::Decimal128(p, s) | arrow::datatypes::DataType::Decimal256(p, s) => (p, s),
+                    _ => unreachable!(),
+                };
+                if col.min.is_none() && col.max.is_none() && col.distribution.is_none() {
+                    return Ok(ValueSampler::DecimalDigits(precision));
+                }
+                // 指定范围时按未缩放的整数取值，范围限制在i128以内
+                let limit = 10i128.pow(precision.min(DECIMAL128_MAX_PRECISION) as u32) - 1;
+                let parse = |value: &Value| infer::parse_decimal_digits(&value_text(value), precision, scale)
+                    .and_then(|digits| digits.parse::<i128>().ok());
+                int_range((-limit, limit), (-limit, limit), &parse)
+            }
+
+            DataType::Date | DataType::Date32 => int_range(
+                (DEFAULT_MIN_DAYS, DEFAULT_MAX_DAYS),
+                (i32::MIN as i128, i32::MAX as i128),
+                &|value| infer::parse_date32(value.as_str()?.trim()).map(i128::from),
+            ),
+            DataType::Timestamp => int_range(
+                (DEFAULT_MIN_TIMESTAMP_MS, DEFAULT_MAX_TIMESTAMP_MS),
+                (i64::MIN as i128, i64::MAX as i128),
+                &|value| infer::parse_timestamp(value.as_str()?.trim())
+                    .and_then(|dt| infer::datetime_to_unit(&dt, &arrow::datatypes::TimeUnit::Millisecond))
+                    .map(i128::from),
+            ),
+            DataType::Time32 => int_range(
+                (0, MS_PER_DAY - 1),
+                (0, MS_PER_DAY - 1),
+                &|value| parse_time_value(value)
+                    .map(|t| t.num_seconds_from_midnight() as i128 * 1000 + (t.nanosecond() / 1_000_000) as i128),
+            ),
+            DataType::Time64 => int_range(
+                (0, NANOS_PER_DAY - 1),
+                (0, NANOS_PER_DAY - 1),
+                &|value| parse_time_value(value)
+                    .map(|t| t.num_seconds_from_midnight() as i128 * 1_000_000_000 + t.nanosecond() as i128),
+            ),
+            DataType::Duration => int_range(
+                (0, DEFAULT_MAX_DURATION_NANOS - 1),
+                (i64::MIN as i128, i64::MAX as i128),
+                &|value| infer::parse_iso_duration(value.as_str()?.trim()).map(i128::from),
+            ),
+
+            DataType::String => {
+                reject_options(col, &range_options)?;
+                match (&col.regex, col.length) {
+                    (Some(_), Some(_)) => Err(TransmutaError::InvalidArgument(format!(
+                        "列'{}'不能同时指定regex和length", col.name
+                    ))),
+                    (Some(pattern), None) => rand_regex::Regex::compile(pattern, MAX_REGEX_REPEAT)
+                        .map(ValueSampler::Regex)
+                        .map_err(|e| TransmutaError::InvalidArgument(format!(
+                            "列'{}'的正则表达式无效: {}", col.name, e
+                        ))),
+                    (None, length) => {
+                        let (min_len, max_len) = length_bounds(col, length.map(|l| l.bounds()).unwrap_or((5, 20)))?;
+                        Ok(ValueSampler::Text { min_len, max_len })
+                    }
+                }
+            }
+            DataType::Binary => {
+                reject_options(col, &range_options)?;
+                reject_options(col, &[("regex", col.regex.is_some())])?;
+                let (min_len, max_len) = length_bounds(col, col.length.map(|l| l.bounds()).unwrap_or((4, 20)))?;
+                Ok(ValueSampler::Binary { min_len, max_len })
+            }
+            DataType::FixedSizeBinary => {
+                reject_options(col, &range_options)?;
+                reject_options(col, &[("regex", col.regex.is_some())])?;
+                let width = col.fixed_size_binary_width();
+                if col.length.is_some_and(|l| l.bounds() != (width, width)) || width == 0 || width > i32::MAX as usize {
+                    return Err(TransmutaError::InvalidArgument(format!(
+                        "列'{}'为fixedsizebinary，length必须是一个正整数", col.name
+                    )));
+                }
+                Ok(ValueSampler::Binary { min_len: width, max_len: width })
+            }
+
+            DataType::Boolean | DataType::Uuid | DataType::Interval | DataType::Null => {
+                reject_options(col, &range_options)?;
+                reject_options(col, &length_options)?;
+                Ok(match col.data_type {
+                    DataType::Boolean => ValueSampler::Boolean,
+                    DataType::Uuid => ValueSampler::Uuid,
+                    DataType::Interval => ValueSampler::Interval,
+                    _ => ValueSampler::Null,
+                })
+            }
+
+            // lorem的length为单词数（中文为词数），其他语义化类型不支持length
+            DataType::Lorem => {
+                reject_options(col, &range_options)?;
+                reject_options(col, &[("regex", col.regex.is_some())])?;
+                let (min_words, max_words) = length_bounds(col, col.length.map(|l| l.bounds()).unwrap_or((8, 20)))?;
+                Ok(ValueSampler::Fake(FakeKind::Lorem { min_words, max_words }, locale))
+            }
+            DataType::Name | DataType::Email | DataType::Phone | DataType::Address | DataType::City
+                | DataType::Company | DataType::Url | DataType::Ipv4 | DataType::Ipv6 | DataType::Iban
+                | DataType::CreditCard => {
+                reject_options(col, &range_options)?;
+                reject_options(col, &length_options)?;
+                let kind = match col.data_type {
+                    DataType::Name => FakeKind::Name,
+                    DataType::Email => FakeKind::Email,
+                    DataType::Phone => FakeKind::Phone,
+                    DataType::Address => FakeKind::Address,
+                    DataType::City => FakeKind::City,
+                    DataType::Company => FakeKind::Company,
+                    DataType::Url => FakeKind::Url,
+                    DataType::Ipv4 => FakeKind::Ipv4,
+                    DataType::Ipv6 => FakeKind::Ipv6,
+                    DataType::Iban => FakeKind::Iban,
+                    _ => FakeKind::CreditCard,
+                };
+                Ok(ValueSampler::Fake(kind, locale))
+            }
+        }
+    }
+
+    /// 是否需要生成不重复的值
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// 生成下一批数据，unique列需要传入此前各批次已生成过的值
+    pub fn generate(&self, rows: usize, rng: &mut StdRng, mut seen: Option<&mut UniqueValues>) -> Result<ArrayRef> {
+        if self.data_type == arrow::datatypes::DataType::Null {
+            return Ok(Arc::new(NullArray::new(rows)));
+        }
+
+        let mut samples = Vec::with_capacity(rows);
+        for _ in 0..rows {
+            if self.null_ratio > 0.0 && rng.gen_bool(self.null_ratio) {
+                samples.push(None);
+            } else {
+                samples.push(Some(self.sample_value(rng, seen.as_deref_mut())?));
+            }
+        }
+
+        if let Some(choices) = &self.choices {
+            let indices: UInt64Array = samples.into_iter()
+                .map(|sample| sample.map(|s| s.into_int() as u64))
+                .collect();
+            return Ok(take(choices.as_ref(), &indices, None)?);
+        }
+
+        build_array(&self.data_type, samples)
+    }
+
+    /// 采样一个值，传入seen时跳过已生成过的值
+    fn sample_value(&self, rng: &mut StdRng, seen: Option<&mut UniqueValues>) -> Result<Sample> {
+        let Some(UniqueValues(seen)) = seen else {
+            return Ok(self.sampler.sample(rng));
+        };
+
+        for _ in 0..MAX_UNIQUE_ATTEMPTS {
+            let sample = self.sampler.sample(rng);
+            if seen.insert(sample.unique_key()) {
+                return Ok(sample);
+            }
+        }
+
+        Err(TransmutaError::DataProcessingError(format!(
+            "列'{}'已生成{}个不重复的值，无法再生成新的值，请扩大取值范围或减少行数",
+            self.name, seen.len()
+        )))
+    }
+}
+
+/// references列的值取自被引用列，不能再指定取值方式
+fn reject_reference_options(col: &ColumnDefinition) -> Result<()> {
+    reject_options(col, &[
+        ("min", col.min.is_some()),
+        ("max", col.max.is_some()),
+        ("length", col.length.is_some()),
+        ("enum", col.values.is_some()),
+        ("weights", col.weights.is_some()),
+        ("regex", col.regex.is_some()),
+        ("distribution", col.distribution.is_some()),
+        ("locale", col.locale.is_some()),
+    ]).map_err(|_| TransmutaError::InvalidArgument(format!(
+        "列'{}'指定了references，不能再指定min、max、length、enum、weights、regex、distribution或locale", col.name
+    )))
+}
+
+/// 按cardinality为被引用表的每一行生成若干行，决定子表的行数和引用列的值。
+/// 子表的行按被引用行的顺序排列，例如每个客户的1到5个订单依次相邻
+#[derive(Debug, Clone)]
+pub struct ChildRows {
+    keys: ArrayRef,
+    /// 第i个被引用行对应子表中[offsets[i], offsets[i + 1])的行
+    offsets: Vec<u64>,
+}
+
+impl ChildRows {
+    /// 为keys中的每个值随机决定对应的行数
+    pub fn try_new(col: &ColumnDefinition, keys: ArrayRef, rng: &mut StdRng) -> Result<Self> {
+        reject_reference_options(col)?;
+        if col.unique || col.null_ratio.is_some() {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'指定了cardinality，不能再指定unique或null_ratio", col.name
+            )));
+        }
+        let (min, max) = col.cardinality
+            .map(|cardinality| cardinality.bounds())
+            .unwrap_or((1, 1));
+        if min > max {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'的cardinality中min大于max", col.name
+            )));
+        }
+
+        let mut offsets = Vec::with_capacity(keys.len() + 1);
+        let mut total = 0u64;
+        offsets.push(total);
+        for _ in 0..keys.len() {
+            total += rng.gen_range(min..=max) as u64;
+            offsets.push(total);
+        }
+
+        Ok(ChildRows { keys, offsets })
+    }
+
+    /// 子表的总行数
+    pub fn rows(&self) -> u64 {
+        *self.offsets.last().unwrap_or(&0)
+    }
+
+    /// 子表中从第start行开始的rows行的引用列
+    pub fn generate(&self, start: u64, rows: usize) -> Result<ArrayRef> {
+        // 第一个被引用行，之后顺序向后查找
+        let mut parent = self.offsets.partition_point(|&offset| offset <= start).saturating_sub(1);
+        let mut indices = Vec::with_capacity(rows);
+        for row in start..start + rows as u64 {
+            while self.offsets[parent + 1] <= row {
+                parent += 1;
+            }
+            indices.push(parent as u64);
+        }
+        Ok(take(self.keys.as_ref(), &UInt64Array::from(indices), None)?)
+    }
+}
+
+/// 按min、max和distribution创建整数采样器
+fn int_sampler(
+    col: &ColumnDefinition,
+    default: (i128, i128),
+    bounds: (i128, i128),
+    parse: &dyn Fn(&Value) -> Option<i128>,
+) -> Result<IntSampler> {
+    let parse_option = |name: &str, value: &Option<Value>, default: i128| -> Result<i128> {
+        let Some(value) = value else {
+            return Ok(default);
+        };
+        let parsed = parse(value).ok_or_else(|| TransmutaError::InvalidArgument(format!(
+            "列'{}'的{}无法解析为{:?}类型的值: {}", col.name, name, col.data_type, value
+        )))?;
+        if parsed < bounds.0 || parsed > bounds.1 {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'的{}超出了{:?}类型的范围: {}", col.name, name, col.data_type, value
+            )));
+        }
+        Ok(parsed)
+    };
+
+    let min = parse_option("min", &col.min, default.0)?;
+    let max = parse_option("max", &col.max, default.1)?;
+    if min > max {
+        return Err(TransmutaError::InvalidArgument(format!(
+            "列'{}'的min大于max", col.name
+        )));
+    }
+
+    let distribution = match &col.distribution {
+        None => NumberDistribution::Uniform,
+        Some(spec) => match spec.kind {
+            DistributionKind::Uniform => NumberDistribution::Uniform,
+            DistributionKind::Normal => {
+                let mean = match &spec.mean {
+                    Some(mean) => parse_option("mean", &Some(mean.clone()), 0)? as f64,
+                    None => (min as f64 + max as f64) / 2.0,
+                };
+                normal_distribution(col, spec, mean, (max - min) as f64)?
+            }
+            DistributionKind::Zipf => {
+                let n = u64::try_from(max - min + 1).map_err(|_| TransmutaError::InvalidArgument(format!(
+                    "列'{}'的取值范围太大，无法使用zipf分布", col.name
+                )))?;
+                let zipf = Zipf::new(n, spec.exponent.unwrap_or(1.0)).map_err(|e| TransmutaError::InvalidArgument(format!(
+                    "列'{}'的zipf分布参数无效: {}", col.name, e
+                )))?;
+                NumberDistribution::Zipf(zipf)
+            }
+        },
+    };
+
+    Ok(IntSampler { min, max, distribution })
+}
+
+/// 按min、max和distribution创建浮点数采样器，浮点数不支持zipf分布
+fn float_sampler(col: &ColumnDefinition, default: (f64, f64)) -> Result<FloatSampler> {
+    let parse_option = |name: &str, value: &Option<Value>, default: f64| -> Result<f64> {
+        match value {
+            Some(value) => parse_float_value(value)
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| TransmutaError::InvalidArgument(format!(
+                    "列'{}'的{}不是有效的数值: {}", col.name, name, value
+                ))),
+            None => Ok(default),
+        }
+    };
+
+    let min = parse_option("min", &col.min, default.0)?;
+    let max = parse_option("max", &col.max, default.1)?;
+    if min > max {
+        return Err(TransmutaError::InvalidArgument(format!(
+            "列'{}'的min大于max", col.name
+        )));
+    }
+    if matches!(col.data_type, DataType::Float32) && (min < f32::MIN as f64 || max > f32::MAX as f64) {
+        return Err(TransmutaError::InvalidArgument(format!(
+            "列'{}'的取值范围超出了float32的范围", col.name
+        )));
+    }
+
+    let distribution = match &col.distribution {
+        None => NumberDistribution::Uniform,
+        Some(spec) => match spec.kind {
+            DistributionKind::Uniform => NumberDistribution::Uniform,
+            DistributionKind::Normal => {
+                let mean = parse_option("mean", &spec.mean, (min + max) / 2.0)?;
+                normal_distribution(col, spec, mean, max - min)?
+            }
+            DistributionKind::Zipf => {
+                return Err(TransmutaError::InvalidArgument(format!(
+                    "列'{}'为浮点数，不支持zipf分布", col.name
+                )));
+            }
+        },
+    };
+
+    Ok(FloatSampler { min, max, distribution })
+}
+
+/// 创建正态分布，未指定标准差时取范围的六分之一，使约99.7%的值落在范围内
+fn normal_distribution(col: &ColumnDefinition, spec: &DistributionSpec, mean: f64, range: f64) -> Result<NumberDistribution> {
+    let std_dev = spec.std_dev.unwrap_or(range / 6.0);
+    Normal::new(mean, std_dev)
+        .map(NumberDistribution::Normal)
+        .map_err(|e| TransmutaError::InvalidArgument(format!(
+            "列'{}'的normal分布参数无效: {}", col.name, e
+        )))
+}
+
+/// 检查长度范围
+fn length_bounds(col: &ColumnDefinition, (min_len, max_len): (usize, usize)) -> Result<(usize, usize)> {
+    if min_len > max_len {
+        return Err(TransmutaError::InvalidArgument(format!(
+            "列'{}'的length中min大于max", col.name
+        )));
+    }
+    Ok((min_len, max_len))
+}
+
+/// 把采样值构建为指定类型的数组
+fn build_array(data_type: &arrow::datatypes::DataType, samples: Vec<Option<Sample>>) -> Result<ArrayRef> {
+    use arrow::datatypes::{DataType as ArrowType, IntervalUnit, TimeUnit};
+
+    let ints = |samples: Vec<Option<Sample>>| samples.into_iter().map(|s| s.map(Sample::into_int));
+
+    let array: ArrayRef = match data_type {
+        ArrowType::Int8 => Arc::new(ints(samples).map(|v| v.map(|v| v as i8)).collect::<Int8Array>()),
+        ArrowType::Int16 => Arc::new(ints(samples).map(|v| v.map(|v| v as i16)).collect::<Int16Array>()),
+        ArrowType::Int32 => Arc::new(ints(samples).map(|v| v.map(|v| v as i32)).collect::<Int32Array>()),
+        ArrowType::Int64 => Arc::new(ints(samples).map(|v| v.map(|v| v as i64)).collect::<Int64Array>()),
+        ArrowType::UInt8 => Arc::new(ints(samples).map(|v| v.map(|v| v as u8)).collect::<UInt8Array>()),
+        ArrowType::UInt16 => Arc::new(ints(samples).map(|v| v.map(|v| v as u16)).collect::<UInt16Array>()),
+        ArrowType::UInt32 => Arc::new(ints(samples).map(|v| v.map(|v| v as u32)).collect::<UInt32Array>()),
+        ArrowType::UInt64 => Arc::new(ints(samples).map(|v| v.map(|v| v as u64)).collect::<UInt64Array>()),
+        ArrowType::Float32 => Arc::new(samples.into_iter()
+            .map(|s| s.map(|s| s.into_float() as f32))
+            .collect::<Float32Array>()),
+        ArrowType::Float64 => Arc::new(samples.into_iter()
+            .map(|s| s.map(Sample::into_float))
+            .collect::<Float64Array>()),
+        ArrowType::Decimal128(precision, scale) => Arc::new(samples.into_iter()
+            .map(|s| s.map(|s| s.into_i256().as_i128()))
+            .collect::<Decimal128Array>()
+            .with_precision_and_scale(*precision, *scale)?),
+        ArrowType::Decimal256(precision, scale) => Arc::new(samples.into_iter()
+            .map(|s| s.map(Sample::into_i256))
+            .collect::<Decimal256Array>()
+            .with_precision_and_scale(*precision, *scale)?),
+        ArrowType::Date32 => Arc::new(ints(samples).map(|v| v.map(|v| v as i32)).collect::<Date32Array>()),
+        ArrowType::Timestamp(TimeUnit::Millisecond, None) => Arc::new(ints(samples)
+            .map(|v| v.map(|v| v as i64))
+            .collect::<TimestampMillisecondArray>()),
+        ArrowType::Time32(TimeUnit::Millisecond) => Arc::new(ints(samples)
+            .map(|v| v.map(|v| v as i32))
+            .collect::<Time32MillisecondArray>()),
+        ArrowType::Time64(TimeUnit::Nanosecond) => Arc::new(ints(samples)
+            .map(|v| v.map(|v| v as i64))
+            .collect::<Time64NanosecondArray>()),
+        ArrowType::Duration(TimeUnit::Nanosecond) => Arc::new(ints(samples)
+            .map(|v| v.map(|v| v as i64))
+            .collect::<DurationNanosecondArray>()),
+        ArrowType::Interval(IntervalUnit::MonthDayNano) => Arc::new(samples.into_iter()
+            .map(|s| s.map(Sample::into_interval))
+            .collect::<IntervalMonthDayNanoArray>()),
+        ArrowType::Boolean => Arc::new(samples.into_iter()
+            .map(|s| s.map(Sample::into_bool))
+            .collect::<BooleanArray>()),
+        ArrowType::Utf8 => Arc::new(samples.into_iter()
+            .map(|s| s.map(Sample::into_text))
+            .collect::<StringArray>()),
+        ArrowType::Binary => Arc::new(samples.into_iter()
+            .map(|s| s.map(Sample::into_bytes))
+            .collect::<BinaryArray>()),
+        ArrowType::FixedSizeBinary(width) => Arc::new(FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+            samples.into_iter().map(|s| s.map(Sample::into_bytes)),
+            *width,
+        )?),
+        other => return Err(TransmutaError::DataProcessingError(format!(
+            "不支持生成{}类型的数据", other
+        ))),
+    };
+
+    Ok(array)
+}
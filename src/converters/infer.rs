@@ -0,0 +1,776 @@
+use crate::cli::CastErrorMode;
+use crate::error::{Result, TransmutaError};
+use crate::utils;
+use arrow::array::*;
+use arrow::compute::{can_cast_types, cast_with_options, CastOptions};
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+use chrono::{NaiveDate, NaiveDateTime};
+use log::warn;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 支持识别的日期格式
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d"];
+
+/// 支持识别的时间戳格式（不带时区）
+const TIMESTAMP_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y/%m/%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M",
+];
+
+/// Unix纪元对应的日期
+fn unix_epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
+}
+
+/// 解析布尔值
+fn parse_bool(value: &str) -> Option<bool> {
+    if value.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// 带前导零的数字（如编号、邮编"007"）按字符串处理，避免丢失前导零
+fn has_leading_zero(value: &str) -> bool {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.")
+}
+
+/// 解析整数
+fn parse_int(value: &str) -> Option<i64> {
+    if has_leading_zero(value) {
+        return None;
+    }
+    value.parse::<i64>().ok()
+}
+
+/// 解析浮点数，不接受"inf"、"NaN"等不含数字的写法
+fn parse_float(value: &str) -> Option<f64> {
+    if has_leading_zero(value) || !value.bytes().any(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse::<f64>().ok()
+}
+
+/// 解析日期，返回自Unix纪元以来的天数
+pub fn parse_date32(value: &str) -> Option<i32> {
+    DATE_FORMATS.iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(value, fmt).ok())
+        .map(|date| date.signed_duration_since(unix_epoch()).num_days() as i32)
+}
+
+/// 解析不带时区的时间戳
+pub fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    TIMESTAMP_FORMATS.iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+}
+
+/// 解析时间戳，只有日期的值按当天零点处理
+pub fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    parse_datetime(value).or_else(|| {
+        DATE_FORMATS.iter()
+            .find_map(|fmt| NaiveDate::parse_from_str(value, fmt).ok())
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })
+}
+
+/// 解析ISO 8601格式的持续时间（如"PT1H30M"、"P2DT3.5S"、"-PT45S"），返回纳秒数
+///
+/// 只支持天、时、分、秒，年、月、周的长度不固定，不作为持续时间处理。
+pub fn parse_iso_duration(value: &str) -> Option<i64> {
+    let (negative, rest) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+    let rest = rest.strip_prefix('P')?;
+    let (date_part, time_part) = match rest.split_once('T') {
+        Some((d, t)) if !t.is_empty() => (d, Some(t)),
+        Some(_) => return None,
+        None => (rest, None),
+    };
+    if date_part.is_empty() && time_part.is_none() {
+        return None;
+    }
+    
+    let mut nanos: i64 = 0;
+    
+    // 日期部分只接受天
+    if !date_part.is_empty() {
+        let days = date_part.strip_suffix('D')?;
+        nanos = i64::try_from(days.parse::<u64>().ok()?).ok()?.checked_mul(86_400_000_000_000)?;
+    }
+    
+    // 时间部分依次为时、分、秒，每个部分都可以省略
+    if let Some(mut time) = time_part {
+        for (designator, scale) in [('H', 3_600_000_000_000), ('M', 60_000_000_000), ('S', 1_000_000_000)] {
+            if let Some(pos) = time.find(designator) {
+                nanos = nanos.checked_add(duration_component_nanos(&time[..pos], scale)?)?;
+                time = &time[pos + 1..];
+            }
+        }
+        if !time.is_empty() {
+            return None;
+        }
+    }
+    
+    Some(if negative { -nanos } else { nanos })
+}
+
+/// 按整数运算把持续时间中的一个数值（如"1"、"0.000123"）乘以该部分每单位的纳秒数，
+/// 不足1纳秒的部分截断，避免浮点数在大数值或小数位较多时丢失精度
+fn duration_component_nanos(number: &str, scale: i64) -> Option<i64> {
+    let (int_part, frac_part) = number.split_once('.').unwrap_or((number, ""));
+    if (int_part.is_empty() && frac_part.is_empty()) || !int_part.bytes().chain(frac_part.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    
+    let whole = match int_part {
+        "" => 0,
+        digits => digits.parse::<i64>().ok()?.checked_mul(scale)?,
+    };
+    // 超过18位的小数不影响纳秒结果（每单位最多约3.6e12纳秒），截断后i128不会溢出
+    let frac_part = &frac_part[..frac_part.len().min(18)];
+    let fraction = match frac_part.parse::<i128>() {
+        Ok(digits) => digits * scale as i128 / 10i128.pow(frac_part.len() as u32),
+        Err(_) => 0,
+    };
+    whole.checked_add(fraction as i64)
+}
+
+/// 按小数位数精确解析十进制小数，返回未缩放整数的十进制字符串，例如"12.5"按scale为2解析为"1250"
+///
+/// 不经过浮点数，也不做舍入：超出小数位数的部分必须全为0，有效位数超过precision时解析失败。
+pub fn parse_decimal_digits(value: &str, precision: u8, scale: i8) -> Option<String> {
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if int_part.is_empty() && frac_part.is_empty() {
+        return None;
+    }
+    if !int_part.bytes().chain(frac_part.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    
+    // 超出小数位数的部分只能是0
+    let scale = usize::try_from(scale).ok()?;
+    let (kept, dropped) = frac_part.split_at(frac_part.len().min(scale));
+    if dropped.bytes().any(|b| b != b'0') {
+        return None;
+    }
+    
+    let mut digits = String::with_capacity(int_part.len() + scale + 1);
+    digits.push_str(int_part);
+    digits.push_str(kept);
+    digits.push_str(&"0".repeat(scale - kept.len()));
+    let digits = digits.trim_start_matches('0');
+    if digits.len() > precision as usize {
+        return None;
+    }
+    
+    Some(match (digits.is_empty(), negative) {
+        (true, _) => "0".to_string(),
+        (false, true) => format!("-{}", digits),
+        (false, false) => digits.to_string(),
+    })
+}
+
+/// 将纳秒数转换为指定时间单位
+fn nanos_to_unit(nanos: i64, unit: &TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Second => nanos / 1_000_000_000,
+        TimeUnit::Millisecond => nanos / 1_000_000,
+        TimeUnit::Microsecond => nanos / 1_000,
+        TimeUnit::Nanosecond => nanos,
+    }
+}
+
+/// 将时间戳转换为指定时间单位的整数
+pub fn datetime_to_unit(dt: &NaiveDateTime, unit: &TimeUnit) -> Option<i64> {
+    let utc = dt.and_utc();
+    match unit {
+        TimeUnit::Second => Some(utc.timestamp()),
+        TimeUnit::Millisecond => Some(utc.timestamp_millis()),
+        TimeUnit::Microsecond => Some(utc.timestamp_micros()),
+        TimeUnit::Nanosecond => utc.timestamp_nanos_opt(),
+    }
+}
+
+/// 统计时间戳或持续时间字符串中秒的小数位数，用于选择时间单位
+fn fraction_digits(value: &str) -> usize {
+    match value.rfind('.') {
+        Some(pos) => value[pos + 1..].bytes().take_while(|b| b.is_ascii_digit()).count(),
+        None => 0,
+    }
+}
+
+/// 按秒的小数位数选择能精确表示的时间单位：3位以内为毫秒，4~6位为微秒，更多为纳秒
+fn unit_for_fraction_digits(digits: usize) -> TimeUnit {
+    match digits {
+        0..=3 => TimeUnit::Millisecond,
+        4..=6 => TimeUnit::Microsecond,
+        _ => TimeUnit::Nanosecond,
+    }
+}
+
+/// 单列的类型推断状态，记录哪些候选类型仍然适用于所有已见到的值
+#[derive(Debug, Clone)]
+struct ColumnInference {
+    seen_values: bool,
+    boolean: bool,
+    int64: bool,
+    float64: bool,
+    date32: bool,
+    timestamp: bool,
+    duration: bool,
+    max_fraction_digits: usize,
+    max_duration_fraction_digits: usize,
+}
+
+impl ColumnInference {
+    fn new() -> Self {
+        ColumnInference {
+            seen_values: false,
+            boolean: true,
+            int64: true,
+            float64: true,
+            date32: true,
+            timestamp: true,
+            duration: true,
+            max_fraction_digits: 0,
+            max_duration_fraction_digits: 0,
+        }
+    }
+
+    fn observe(&mut self, value: &str) {
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+
+        self.seen_values = true;
+        if self.boolean && parse_bool(value).is_none() {
+            self.boolean = false;
+        }
+        if self.int64 && parse_int(value).is_none() {
+            self.int64 = false;
+        }
+        if self.float64 && parse_float(value).is_none() {
+            self.float64 = false;
+        }
+        if self.date32 && parse_date32(value).is_none() {
+            self.date32 = false;
+        }
+        if self.timestamp {
+            if parse_timestamp(value).is_some() {
+                self.max_fraction_digits = self.max_fraction_digits.max(fraction_digits(value));
+            } else {
+                self.timestamp = false;
+            }
+        }
+        if self.duration {
+            if parse_iso_duration(value).is_some() {
+                self.max_duration_fraction_digits = self.max_duration_fraction_digits.max(fraction_digits(value));
+            } else {
+                self.duration = false;
+            }
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        if !self.seen_values {
+            DataType::Utf8
+        } else if self.boolean {
+            DataType::Boolean
+        } else if self.int64 {
+            DataType::Int64
+        } else if self.float64 {
+            DataType::Float64
+        } else if self.date32 {
+            DataType::Date32
+        } else if self.timestamp {
+            DataType::Timestamp(unit_for_fraction_digits(self.max_fraction_digits), None)
+        } else if self.duration {
+            DataType::Duration(unit_for_fraction_digits(self.max_duration_fraction_digits))
+        } else {
+            DataType::Utf8
+        }
+    }
+}
+
+/// 根据采样行推断每一列的Arrow类型
+///
+/// 空值不参与推断；某列所有采样值都能解析为同一类型时才使用该类型，否则为Utf8。
+pub fn infer_column_types<'a, I, R>(column_count: usize, sample_rows: I) -> Vec<DataType>
+where
+    I: IntoIterator<Item = R>,
+    R: IntoIterator<Item = &'a str>,
+{
+    let mut columns = vec![ColumnInference::new(); column_count];
+
+    for row in sample_rows {
+        for (col_idx, value) in row.into_iter().enumerate().take(column_count) {
+            columns[col_idx].observe(value);
+        }
+    }
+
+    columns.iter().map(ColumnInference::data_type).collect()
+}
+
+/// 按列类型将字符串值解析后追加到对应的Arrow构建器
+pub enum TypedColumnBuilder {
+    Utf8(StringBuilder),
+    Boolean(BooleanBuilder),
+    Int64(Int64Builder),
+    Float64(Float64Builder),
+    Date32(Date32Builder),
+    Timestamp(TimeUnit, Vec<Option<i64>>),
+    Duration(TimeUnit, Vec<Option<i64>>),
+    Decimal128(u8, i8, Decimal128Builder),
+    Decimal256(u8, i8, Decimal256Builder),
+    /// 其他类型先收集字符串，完成时用Arrow的类型转换解析
+    Cast(DataType, StringBuilder),
+}
+
+impl TypedColumnBuilder {
+    /// 为指定类型创建构建器，没有专门解析规则的类型在完成时由字符串转换
+    pub fn new(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Utf8 => TypedColumnBuilder::Utf8(StringBuilder::new()),
+            DataType::Boolean => TypedColumnBuilder::Boolean(BooleanBuilder::new()),
+            DataType::Int64 => TypedColumnBuilder::Int64(Int64Builder::new()),
+            DataType::Float64 => TypedColumnBuilder::Float64(Float64Builder::new()),
+            DataType::Date32 => TypedColumnBuilder::Date32(Date32Builder::new()),
+            DataType::Timestamp(unit, None) => TypedColumnBuilder::Timestamp(*unit, Vec::new()),
+            DataType::Duration(unit) => TypedColumnBuilder::Duration(*unit, Vec::new()),
+            DataType::Decimal128(precision, scale) => TypedColumnBuilder::Decimal128(
+                *precision, *scale, Decimal128Builder::new().with_data_type(data_type.clone()),
+            ),
+            DataType::Decimal256(precision, scale) => TypedColumnBuilder::Decimal256(
+                *precision, *scale, Decimal256Builder::new().with_data_type(data_type.clone()),
+            ),
+            other => TypedColumnBuilder::Cast(other.clone(), StringBuilder::new()),
+        }
+    }
+    
+    /// 构建器能否产生指定类型的列
+    pub fn supports(data_type: &DataType) -> bool {
+        match TypedColumnBuilder::new(data_type) {
+            TypedColumnBuilder::Cast(data_type, _) => can_cast_types(&DataType::Utf8, &data_type),
+            _ => true,
+        }
+    }
+
+    /// 追加一个值。对非字符串列，空字符串写为null；无法解析时返回false
+    pub fn append(&mut self, value: &str) -> bool {
+        if let TypedColumnBuilder::Utf8(builder) = self {
+            builder.append_value(value);
+            return true;
+        }
+
+        let value = value.trim();
+        if value.is_empty() {
+            self.append_null();
+            return true;
+        }
+
+        match self {
+            TypedColumnBuilder::Utf8(_) => unreachable!(),
+            TypedColumnBuilder::Boolean(builder) => parse_bool(value)
+                .map(|v| builder.append_value(v))
+                .is_some(),
+            TypedColumnBuilder::Int64(builder) => value.parse::<i64>().ok()
+                .map(|v| builder.append_value(v))
+                .is_some(),
+            TypedColumnBuilder::Float64(builder) => value.parse::<f64>().ok()
+                .map(|v| builder.append_value(v))
+                .is_some(),
+            TypedColumnBuilder::Date32(builder) => parse_date32(value)
+                .map(|v| builder.append_value(v))
+                .is_some(),
+            TypedColumnBuilder::Timestamp(unit, values) => parse_timestamp(value)
+                .and_then(|dt| datetime_to_unit(&dt, unit))
+                .map(|v| values.push(Some(v)))
+                .is_some(),
+            TypedColumnBuilder::Duration(unit, values) => parse_iso_duration(value)
+                .map(|nanos| values.push(Some(nanos_to_unit(nanos, unit))))
+                .is_some(),
+            TypedColumnBuilder::Decimal128(precision, scale, builder) => parse_decimal_digits(value, *precision, *scale)
+                .and_then(|digits| digits.parse::<i128>().ok())
+                .map(|v| builder.append_value(v))
+                .is_some(),
+            TypedColumnBuilder::Decimal256(precision, scale, builder) => parse_decimal_digits(value, *precision, *scale)
+                .and_then(|digits| i256::from_string(&digits))
+                .map(|v| builder.append_value(v))
+                .is_some(),
+            TypedColumnBuilder::Cast(_, builder) => {
+                builder.append_value(value);
+                true
+            }
+        }
+    }
+
+    /// 追加一个null值
+    pub fn append_null(&mut self) {
+        match self {
+            TypedColumnBuilder::Utf8(builder) => builder.append_null(),
+            TypedColumnBuilder::Boolean(builder) => builder.append_null(),
+            TypedColumnBuilder::Int64(builder) => builder.append_null(),
+            TypedColumnBuilder::Float64(builder) => builder.append_null(),
+            TypedColumnBuilder::Date32(builder) => builder.append_null(),
+            TypedColumnBuilder::Timestamp(_, values) => values.push(None),
+            TypedColumnBuilder::Duration(_, values) => values.push(None),
+            TypedColumnBuilder::Decimal128(_, _, builder) => builder.append_null(),
+            TypedColumnBuilder::Decimal256(_, _, builder) => builder.append_null(),
+            TypedColumnBuilder::Cast(_, builder) => builder.append_null(),
+        }
+    }
+
+    /// 完成构建并返回数组，以及在完成时才发现无法转换的值的行序号和原始值（这些值写为空值）
+    pub fn finish(self) -> Result<(ArrayRef, Vec<(usize, String)>)> {
+        let array: ArrayRef = match self {
+            TypedColumnBuilder::Utf8(mut builder) => Arc::new(builder.finish()),
+            TypedColumnBuilder::Boolean(mut builder) => Arc::new(builder.finish()),
+            TypedColumnBuilder::Int64(mut builder) => Arc::new(builder.finish()),
+            TypedColumnBuilder::Float64(mut builder) => Arc::new(builder.finish()),
+            TypedColumnBuilder::Date32(mut builder) => Arc::new(builder.finish()),
+            TypedColumnBuilder::Timestamp(unit, values) => match unit {
+                TimeUnit::Second => Arc::new(TimestampSecondArray::from(values)),
+                TimeUnit::Millisecond => Arc::new(TimestampMillisecondArray::from(values)),
+                TimeUnit::Microsecond => Arc::new(TimestampMicrosecondArray::from(values)),
+                TimeUnit::Nanosecond => Arc::new(TimestampNanosecondArray::from(values)),
+            },
+            TypedColumnBuilder::Duration(unit, values) => match unit {
+                TimeUnit::Second => Arc::new(DurationSecondArray::from(values)),
+                TimeUnit::Millisecond => Arc::new(DurationMillisecondArray::from(values)),
+                TimeUnit::Microsecond => Arc::new(DurationMicrosecondArray::from(values)),
+                TimeUnit::Nanosecond => Arc::new(DurationNanosecondArray::from(values)),
+            },
+            TypedColumnBuilder::Decimal128(_, _, mut builder) => Arc::new(builder.finish()),
+            TypedColumnBuilder::Decimal256(_, _, mut builder) => Arc::new(builder.finish()),
+            TypedColumnBuilder::Cast(data_type, mut builder) => {
+                // 无法转换的值变为空值，对比转换前后的空值找出这些值
+                let strings = builder.finish();
+                let options = CastOptions { safe: true, ..Default::default() };
+                let array = cast_with_options(&strings, &data_type, &options)?;
+                let failures = (0..strings.len())
+                    .filter(|&i| strings.is_valid(i) && array.is_null(i))
+                    .map(|i| (i, strings.value(i).to_string()))
+                    .collect();
+                return Ok((array, failures));
+            }
+        };
+        Ok((array, Vec::new()))
+    }
+}
+
+/// 无法转换为列类型的单元格
+#[derive(Debug, Clone)]
+pub struct CastFailure {
+    /// 在输入中的行号
+    pub row_number: usize,
+    pub column: String,
+    pub value: String,
+    pub data_type: DataType,
+}
+
+/// 按列名用--schema声明的类型覆盖推断的列类型，返回每一列是否为声明的类型
+pub fn apply_declared_types<'a, I>(headers: I, column_types: &mut [DataType], declared: &Schema) -> Result<Vec<bool>>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let headers: Vec<&str> = headers.into_iter().collect();
+    let mut is_declared = vec![false; column_types.len()];
+    
+    for field in declared.fields() {
+        let col_idx = headers.iter()
+            .position(|name| *name == field.name())
+            .ok_or_else(|| TransmutaError::InvalidArgument(format!(
+                "--schema中声明的列'{}'在输入中不存在，输入的列: {}", field.name(), headers.join(", ")
+            )))?;
+        if !TypedColumnBuilder::supports(field.data_type()) {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "列'{}'声明的类型{}不支持从文本转换", field.name(), field.data_type()
+            )));
+        }
+        
+        column_types[col_idx] = field.data_type().clone();
+        is_declared[col_idx] = true;
+    }
+    
+    Ok(is_declared)
+}
+
+/// 按schema中的列类型把字符串行构建为RecordBatch
+#[derive(Debug, Clone)]
+pub struct TypedBatchBuilder {
+    schema: SchemaRef,
+    /// 每一列是否为--schema声明的类型，用于给出对应的错误提示
+    declared: Vec<bool>,
+    on_cast_error: CastErrorMode,
+}
+
+impl TypedBatchBuilder {
+    pub fn new(schema: SchemaRef) -> Self {
+        let declared = vec![false; schema.fields().len()];
+        TypedBatchBuilder { schema, declared, on_cast_error: CastErrorMode::Strict }
+    }
+
+    /// 指定哪些列为声明的类型，以及值无法转换时的处理方式
+    pub fn with_cast_options(mut self, declared: Vec<bool>, on_cast_error: CastErrorMode) -> Self {
+        self.declared = declared;
+        self.on_cast_error = on_cast_error;
+        self
+    }
+
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// 处理一个无法转换的值：声明类型的列在strict模式下返回错误，其余情况记录下来并写为空值。
+    /// 推断类型的列只按采样行推断，之后出现的不符合类型的值不应使转换失败
+    fn cast_failed(&self, failures: &mut Vec<CastFailure>, row_number: usize, col_idx: usize, value: &str) -> Result<()> {
+        let field = self.schema.field(col_idx);
+        if self.declared[col_idx] && self.on_cast_error == CastErrorMode::Strict {
+            return Err(TransmutaError::DataProcessingError(format!(
+                "第{}行列'{}'的值'{}'无法解析为{}，可使用--on-cast-error lenient把无法转换的值写为空值",
+                row_number, field.name(), value, field.data_type()
+            )));
+        }
+        
+        failures.push(CastFailure {
+            row_number,
+            column: field.name().clone(),
+            value: value.to_string(),
+            data_type: field.data_type().clone(),
+        });
+        Ok(())
+    }
+
+    /// 构建一个批次，同时返回写为空值的单元格（按行号排序）。
+    /// first_row_number为第一行在输入中的行号，用于错误信息；多出的列被忽略，缺少的列写为空值
+    pub fn build<I, R, S>(&self, rows: I, first_row_number: usize) -> Result<(RecordBatch, Vec<CastFailure>)>
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut builders: Vec<TypedColumnBuilder> = self.schema.fields().iter()
+            .map(|field| TypedColumnBuilder::new(field.data_type()))
+            .collect();
+        let mut failures = Vec::new();
+
+        for (row_idx, row) in rows.into_iter().enumerate() {
+            let mut col_count = 0;
+            for (col_idx, value) in row.into_iter().enumerate().take(builders.len()) {
+                let value = value.as_ref();
+                if !builders[col_idx].append(value) {
+                    self.cast_failed(&mut failures, first_row_number + row_idx, col_idx, value)?;
+                    builders[col_idx].append_null();
+                }
+                col_count += 1;
+            }
+
+            // 如果某行数据列数少于标题列数，填充空值
+            for builder in builders.iter_mut().skip(col_count) {
+                builder.append("");
+            }
+        }
+
+        let mut arrays: Vec<ArrayRef> = Vec::with_capacity(builders.len());
+        for (col_idx, builder) in builders.into_iter().enumerate() {
+            let (array, cast_failures) = builder.finish()?;
+            for (row_idx, value) in cast_failures {
+                self.cast_failed(&mut failures, first_row_number + row_idx, col_idx, &value)?;
+            }
+            arrays.push(array);
+        }
+        failures.sort_by_key(|failure| failure.row_number);
+
+        Ok((RecordBatch::try_new(self.schema.clone(), arrays)?, failures))
+    }
+}
+
+/// 日志中最多列出的无法转换的单元格数，其余只写入报告文件
+const MAX_LOGGED_CAST_FAILURES: usize = 20;
+
+/// 记录lenient模式下无法转换的单元格：前几个写入日志，指定了报告文件时全部写入CSV报告
+pub struct CastFailureLog {
+    report: Option<csv::Writer<Box<dyn Write + Send>>>,
+    count: usize,
+}
+
+impl CastFailureLog {
+    pub fn try_new(report_path: Option<&Path>) -> Result<Self> {
+        let report = match report_path {
+            Some(path) => {
+                utils::ensure_output_dir(path)?;
+                let mut writer = csv::Writer::from_writer(utils::create_buf_writer(path)?);
+                writer.write_record(["row", "column", "value", "data_type"])?;
+                Some(writer)
+            }
+            None => None,
+        };
+        Ok(CastFailureLog { report, count: 0 })
+    }
+
+    /// 记录一个批次中无法转换的单元格
+    pub fn record(&mut self, failures: &[CastFailure]) -> Result<()> {
+        for failure in failures {
+            if self.count < MAX_LOGGED_CAST_FAILURES {
+                warn!("第{}行列'{}'的值'{}'无法解析为{}，已写为空值",
+                      failure.row_number, failure.column, failure.value, failure.data_type);
+            }
+            if let Some(report) = self.report.as_mut() {
+                report.write_record([
+                    failure.row_number.to_string(),
+                    failure.column.clone(),
+                    failure.value.clone(),
+                    failure.data_type.to_string(),
+                ])?;
+            }
+            self.count += 1;
+        }
+        Ok(())
+    }
+
+    /// 输出汇总并关闭报告文件
+    pub fn finish(self) -> Result<()> {
+        if self.count > MAX_LOGGED_CAST_FAILURES {
+            warn!("省略了其余{}个无法解析的值", self.count - MAX_LOGGED_CAST_FAILURES);
+        }
+        if self.count > 0 {
+            warn!("共{}个值无法解析为列类型，已写为空值", self.count);
+        }
+        if let Some(mut report) = self.report {
+            report.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 推断单列的类型
+    fn infer(values: &[&str]) -> DataType {
+        infer_column_types(1, values.iter().map(|value| [*value]))[0].clone()
+    }
+
+    /// 按一列id（Int64）和一列amount（声明为decimal）构建批次
+    fn build(rows: &[[&str; 2]], on_cast_error: CastErrorMode) -> Result<(RecordBatch, Vec<CastFailure>)> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("amount", DataType::Decimal128(10, 2), true),
+        ]));
+        TypedBatchBuilder::new(schema)
+            .with_cast_options(vec![false, true], on_cast_error)
+            .build(rows.iter().map(|row| row.iter()), 2)
+    }
+
+    #[test]
+    fn infers_the_narrowest_type_for_all_values() {
+        assert_eq!(infer(&["true", "", "FALSE"]), DataType::Boolean);
+        assert_eq!(infer(&["1", "-2", ""]), DataType::Int64);
+        assert_eq!(infer(&["1", "2.5"]), DataType::Float64);
+        assert_eq!(infer(&["2024-01-31", "2024/02/01"]), DataType::Date32);
+        assert_eq!(infer(&["1", "N/A"]), DataType::Utf8);
+        assert_eq!(infer(&["", ""]), DataType::Utf8);
+    }
+
+    #[test]
+    fn leading_zeros_stay_strings() {
+        assert_eq!(infer(&["007", "8"]), DataType::Utf8);
+        assert_eq!(infer(&["0", "0.5"]), DataType::Float64);
+    }
+
+    #[test]
+    fn timestamp_unit_follows_fraction_digits() {
+        assert_eq!(infer(&["2024-01-31 08:00:00", "2024-01-31 08:00:00.123"]),
+                   DataType::Timestamp(TimeUnit::Millisecond, None));
+        assert_eq!(infer(&["2024-01-31T08:00:00.123456"]), DataType::Timestamp(TimeUnit::Microsecond, None));
+        assert_eq!(infer(&["2024-01-31 08:00:00.123456789"]), DataType::Timestamp(TimeUnit::Nanosecond, None));
+    }
+
+    #[test]
+    fn duration_unit_follows_fraction_digits() {
+        assert_eq!(infer(&["PT1H30M", "P2D"]), DataType::Duration(TimeUnit::Millisecond));
+        assert_eq!(infer(&["PT1H30M", "PT0.000123S"]), DataType::Duration(TimeUnit::Microsecond));
+        assert_eq!(infer(&["-PT0.000000001S"]), DataType::Duration(TimeUnit::Nanosecond));
+        assert_eq!(infer(&["PT1H", "P1Y"]), DataType::Utf8);
+    }
+
+    #[test]
+    fn iso_durations_are_parsed_exactly() {
+        assert_eq!(parse_iso_duration("PT0.000123S"), Some(123_000));
+        assert_eq!(parse_iso_duration("PT0.000000001S"), Some(1));
+        assert_eq!(parse_iso_duration("-P2DT3.5S"), Some(-(2 * 86_400 + 3) * 1_000_000_000 - 500_000_000));
+        assert_eq!(parse_iso_duration("PT1.5H"), Some(5_400_000_000_000));
+        assert_eq!(parse_iso_duration("PT.25S"), Some(250_000_000));
+        assert_eq!(parse_iso_duration("P106751DT23H47M16.854775807S"), Some(i64::MAX));
+        for invalid in ["P", "PT", "PT.S", "PT1", "P1W", "P-1D", "PT1S2M", "P200000D"] {
+            assert_eq!(parse_iso_duration(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn decimal_digits_are_parsed_without_rounding() {
+        assert_eq!(parse_decimal_digits("12.5", 10, 2).as_deref(), Some("1250"));
+        assert_eq!(parse_decimal_digits("-0.50", 10, 2).as_deref(), Some("-50"));
+        assert_eq!(parse_decimal_digits("1.230", 10, 2).as_deref(), Some("123"));
+        assert_eq!(parse_decimal_digits("1.234", 10, 2), None);
+        assert_eq!(parse_decimal_digits("123456789", 10, 2), None);
+    }
+
+    #[test]
+    fn declared_column_fails_in_strict_mode() {
+        let error = build(&[["1", "9.99"], ["2", "abc"]], CastErrorMode::Strict).unwrap_err().to_string();
+        assert!(error.contains("第3行列'amount'的值'abc'无法解析为"), "{}", error);
+    }
+
+    #[test]
+    fn lenient_mode_writes_nulls_and_reports_failures() {
+        let (batch, failures) = build(&[["1", "9.99"], ["x", "abc"], ["3", ""]], CastErrorMode::Lenient).unwrap();
+        let ids = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        let amounts = batch.column(1).as_any().downcast_ref::<Decimal128Array>().unwrap();
+        assert_eq!(ids.iter().collect::<Vec<_>>(), vec![Some(1), None, Some(3)]);
+        assert_eq!(amounts.iter().collect::<Vec<_>>(), vec![Some(999), None, None]);
+        let failures: Vec<_> = failures.iter()
+            .map(|failure| (failure.row_number, failure.column.as_str(), failure.value.as_str()))
+            .collect();
+        assert_eq!(failures, vec![(3, "id", "x"), (3, "amount", "abc")]);
+    }
+
+    #[test]
+    fn inferred_column_never_fails_the_conversion() {
+        // id不是声明的类型，strict模式下也只记录无法转换的值
+        let (batch, failures) = build(&[["N/A", "1"]], CastErrorMode::Strict).unwrap();
+        assert!(batch.column(0).is_null(0));
+        assert_eq!(failures.len(), 1);
+    }
+
+    #[test]
+    fn short_rows_are_padded_with_nulls() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Utf8, true),
+            Field::new("b", DataType::Int64, true),
+        ]));
+        let rows = vec![vec!["x"], vec!["y", "2", "extra"]];
+        let (batch, failures) = TypedBatchBuilder::new(schema).build(rows, 2).unwrap();
+        assert!(failures.is_empty());
+        assert_eq!(batch.num_rows(), 2);
+        assert!(batch.column(1).is_null(0));
+        assert_eq!(batch.column(1).as_any().downcast_ref::<Int64Array>().unwrap().value(1), 2);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::cli::OutputFormat;
+use crate::error::Result;
+use crate::utils;
+use super::common::ConvertOptions;
+use super::reader::RecordBatches;
+use super::writer::open_writer;
+use std::path::Path;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read};
+use log::{info, warn, debug};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Instant;
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::record_batch::RecordBatchReader;
+
+/// Arrow IPC文件格式（Feather v2）的文件头，流格式没有文件头
+pub const ARROW_FILE_MAGIC: &[u8] = b"ARROW1";
+
+/// 打开Arrow IPC输入，按文件头自动区分文件格式和流格式，路径为"-"时读取标准输入
+///
+/// 压缩过的（LZ4或ZSTD）数据在读取时自动解压。
+fn open_ipc_reader(input_path: &Path) -> Result<Box<dyn RecordBatchReader>> {
+    let mut input = utils::create_buf_reader(input_path)?;
+    let is_file_format = input.fill_buf()?.starts_with(ARROW_FILE_MAGIC);
+
+    let reader: Box<dyn RecordBatchReader> = if !is_file_format {
+        debug!("按Arrow IPC流格式读取: {}", input_path.display());
+        Box::new(StreamReader::try_new(input, None)?)
+    } else if utils::is_stdio(input_path) {
+        // 文件格式的元数据在文件末尾，需要随机读取，标准输入先读入内存
+        debug!("按Arrow IPC文件格式读取标准输入");
+        let mut data = Vec::new();
+        input.read_to_end(&mut data)?;
+        Box::new(FileReader::try_new(Cursor::new(data), None)?)
+    } else {
+        debug!("按Arrow IPC文件格式读取: {}", input_path.display());
+        let file = BufReader::new(File::open(input_path)?);
+        Box::new(FileReader::try_new(file, None)?)
+    };
+
+    Ok(reader)
+}
+
+/// 转换Arrow IPC文件（.arrow、.feather）或IPC流到其他格式
+pub fn convert_ipc(
+    input_path: &Path,
+    output_path: &Path,
+    format: &OutputFormat,
+    options: &ConvertOptions,
+) -> Result<()> {
+    let start_time = Instant::now();
+
+    // 检查输入文件扩展名
+    if !utils::is_stdio(input_path) {
+        if let Ok(ext) = utils::get_file_extension(input_path) {
+            if !matches!(ext.as_str(), "arrow" | "arrows" | "feather" | "ipc") {
+                warn!("输入文件扩展名不是.arrow、.arrows、.feather或.ipc: {}", ext);
+            }
+        }
+    }
+
+    info!("开始处理Arrow IPC文件: {}", input_path.display());
+
+    let reader = open_ipc_reader(input_path)?;
+    debug!("Arrow schema: {:?}", reader.schema());
+
+    // 流格式无法预知总行数，只显示已处理的行数
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(ProgressStyle::default_spinner()
+        .template("{spinner:.green} [{elapsed_precise}] {pos}行")
+        .unwrap());
+
+    // 在写出任何数据之前检查过滤条件
+    options.validate_filter(&reader.schema())?;
+
+    // 打开输出文件，所有批次写入同一个写入器
+    let mut writer = open_writer(output_path, format, reader.schema(), &options.writer_options())?;
+
+    let mut processed_rows = 0;
+    let mut written_rows = 0;
+
+    for batch in reader {
+        let record_batch = batch?;
+        processed_rows += record_batch.num_rows();
+
+        let record_batch = options.filter_batch(record_batch)?;
+        writer.write(&record_batch)?;
+
+        written_rows += record_batch.num_rows();
+        pb.set_position(processed_rows as u64);
+    }
+
+    writer.close()?;
+    pb.finish_with_message("Arrow IPC文件转换完成");
+
+    info!("共转换{}行数据", written_rows);
+    let elapsed = start_time.elapsed();
+    info!("总处理时间: {:.2}秒", elapsed.as_secs_f64());
+
+    Ok(())
+}
+
+/// 按批次逐个读取Arrow IPC文件或流的数据
+pub fn read_ipc(input_path: &Path) -> Result<(SchemaRef, RecordBatches)> {
+    let reader = open_ipc_reader(input_path)?;
+
+    let schema = reader.schema();
+    Ok((schema, Box::new(reader.map(|batch| Ok(batch?)))))
+}
@@ -0,0 +1,262 @@
+use crate::cli::OutputFormat;
+use crate::error::{Result, TransmutaError};
+use crate::utils;
+use super::common::ConvertOptions;
+use super::reader::RecordBatches;
+use super::writer::open_writer;
+use std::path::Path;
+use std::io::BufRead;
+use log::{info, warn, debug};
+use arrow::array::*;
+use arrow::datatypes::*;
+use arrow::record_batch::RecordBatch;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Instant;
+use std::sync::Arc;
+use serde_json::{Map, Value};
+
+/// 从JSON值推断出的列类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum JsonColumnType {
+    /// 只出现过null
+    Null,
+    Boolean,
+    Int64,
+    Float64,
+    /// 字符串，或类型混杂、嵌套对象和数组（以JSON文本保存）
+    Utf8,
+}
+
+impl JsonColumnType {
+    /// 根据单个值确定类型
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => JsonColumnType::Null,
+            Value::Bool(_) => JsonColumnType::Boolean,
+            Value::Number(n) if n.is_i64() => JsonColumnType::Int64,
+            Value::Number(_) => JsonColumnType::Float64,
+            _ => JsonColumnType::Utf8,
+        }
+    }
+    
+    /// 合并两个类型，整数和浮点数合并为浮点数，其他不一致的类型合并为字符串
+    fn merge(self, other: Self) -> Self {
+        use JsonColumnType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Null, t) | (t, Null) => t,
+            (Int64, Float64) | (Float64, Int64) => Float64,
+            _ => Utf8,
+        }
+    }
+    
+    fn to_arrow(self) -> DataType {
+        match self {
+            // 全为null的列按字符串处理，便于写出为各种格式
+            JsonColumnType::Null | JsonColumnType::Utf8 => DataType::Utf8,
+            JsonColumnType::Boolean => DataType::Boolean,
+            JsonColumnType::Int64 => DataType::Int64,
+            JsonColumnType::Float64 => DataType::Float64,
+        }
+    }
+}
+
+/// 读取JSON文件中的所有记录，支持顶层数组和NDJSON（每行一个对象）两种形式
+fn read_json_records(input_path: &Path) -> Result<Vec<Map<String, Value>>> {
+    let mut reader = utils::create_buf_reader(input_path)?;
+    
+    // 通过第一个非空白字符判断文件形式
+    let first_char = loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Err(TransmutaError::DataProcessingError("JSON文件为空".to_string()));
+        }
+        match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(pos) => break buf[pos],
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+            }
+        }
+    };
+    
+    let values: Vec<Value> = if first_char == b'[' {
+        debug!("检测到JSON数组格式");
+        serde_json::from_reader(reader)?
+    } else {
+        debug!("检测到NDJSON格式");
+        serde_json::Deserializer::from_reader(reader)
+            .into_iter::<Value>()
+            .collect::<std::result::Result<Vec<Value>, _>>()?
+    };
+    
+    values.into_iter()
+        .enumerate()
+        .map(|(idx, value)| match value {
+            Value::Object(obj) => Ok(obj),
+            other => Err(TransmutaError::FileFormatError(format!(
+                "第{}条记录不是JSON对象: {}", idx + 1, other
+            ))),
+        })
+        .collect()
+}
+
+/// 根据所有记录推断schema，字段为所有记录键的并集，按首次出现的顺序排列
+fn infer_schema(records: &[Map<String, Value>]) -> Schema {
+    let mut names: Vec<String> = Vec::new();
+    let mut types: Vec<JsonColumnType> = Vec::new();
+    let mut positions: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    
+    for record in records {
+        for (key, value) in record {
+            let value_type = JsonColumnType::of(value);
+            match positions.get(key) {
+                Some(&idx) => types[idx] = types[idx].merge(value_type),
+                None => {
+                    positions.insert(key.clone(), names.len());
+                    names.push(key.clone());
+                    types.push(value_type);
+                }
+            }
+        }
+    }
+    
+    Schema::new(
+        names.iter().zip(types.iter())
+            .map(|(name, t)| Field::new(name, t.to_arrow(), true))
+            .collect::<Vec<Field>>()
+    )
+}
+
+/// 将一批记录中的某一列构建为Arrow数组，缺失的键填充为null
+fn build_column(records: &[Map<String, Value>], field: &Field) -> ArrayRef {
+    let name = field.name();
+    match field.data_type() {
+        DataType::Boolean => {
+            let mut builder = BooleanBuilder::with_capacity(records.len());
+            for record in records {
+                builder.append_option(record.get(name).and_then(Value::as_bool));
+            }
+            Arc::new(builder.finish())
+        }
+        DataType::Int64 => {
+            let mut builder = Int64Builder::with_capacity(records.len());
+            for record in records {
+                builder.append_option(record.get(name).and_then(Value::as_i64));
+            }
+            Arc::new(builder.finish())
+        }
+        DataType::Float64 => {
+            let mut builder = Float64Builder::with_capacity(records.len());
+            for record in records {
+                builder.append_option(record.get(name).and_then(Value::as_f64));
+            }
+            Arc::new(builder.finish())
+        }
+        _ => {
+            let mut builder = StringBuilder::new();
+            for record in records {
+                match record.get(name) {
+                    None | Some(Value::Null) => builder.append_null(),
+                    Some(Value::String(s)) => builder.append_value(s),
+                    // 类型混杂的值或嵌套结构保存为JSON文本
+                    Some(other) => builder.append_value(other.to_string()),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+    }
+}
+
+/// 转换JSON或NDJSON文件到其他格式
+pub fn convert_json(
+    input_path: &Path,
+    output_path: &Path,
+    format: &OutputFormat,
+    options: &ConvertOptions,
+) -> Result<()> {
+    let start_time = Instant::now();
+    let batch_size = options.batch_size;
+    
+    // 检查输入文件扩展名
+    if !utils::is_stdio(input_path) {
+        if let Ok(ext) = utils::get_file_extension(input_path) {
+            if !["json", "jsonl", "ndjson"].contains(&ext.as_str()) {
+                warn!("输入文件扩展名不是.json/.jsonl/.ndjson: {}", ext);
+            }
+        }
+    }
+    
+    info!("开始处理JSON文件: {}", input_path.display());
+    
+    // 读取全部记录，schema需要所有记录键的并集
+    let records = read_json_records(input_path)?;
+    let total_rows = records.len();
+    if total_rows == 0 {
+        return Err(TransmutaError::DataProcessingError("JSON文件中没有记录".to_string()));
+    }
+    info!("JSON文件共有{}条记录", total_rows);
+    
+    let schema = Arc::new(infer_schema(&records));
+    debug!("推断的schema: {:?}", schema);
+    
+    // 创建进度条
+    let pb = ProgressBar::new(total_rows as u64);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
+        .unwrap()
+        .progress_chars("#>-"));
+    
+    // 计算处理批次
+    let batch_count = total_rows.div_ceil(batch_size);
+    info!("将数据分为{}个批次处理，每批次{}行", batch_count, batch_size);
+    
+    // 在写出任何数据之前检查过滤条件
+    options.validate_filter(&schema)?;
+    
+    // 打开输出文件，所有批次写入同一个写入器
+    let mut writer = open_writer(output_path, format, schema.clone(), &options.writer_options())?;
+    
+    let mut processed_rows = 0;
+    
+    for chunk in records.chunks(batch_size) {
+        // 按列构建数组
+        let arrays: Vec<ArrayRef> = schema.fields().iter()
+            .map(|field| build_column(chunk, field))
+            .collect();
+        
+        // 创建RecordBatch
+        let record_batch = RecordBatch::try_new(schema.clone(), arrays)?;
+        
+        // 写入满足过滤条件的行
+        writer.write(&options.filter_batch(record_batch)?)?;
+        
+        processed_rows += chunk.len();
+        pb.set_position(processed_rows as u64);
+    }
+    
+    writer.close()?;
+    pb.finish_with_message("JSON文件转换完成");
+    
+    let elapsed = start_time.elapsed();
+    info!("总处理时间: {:.2}秒", elapsed.as_secs_f64());
+    
+    Ok(())
+}
+
+/// 读取JSON或NDJSON文件的数据。推断类型需要所有记录，记录整体载入内存后按批次逐个构建
+pub fn read_json(input_path: &Path, options: &ConvertOptions) -> Result<(SchemaRef, RecordBatches)> {
+    let records = read_json_records(input_path)?;
+    let schema = Arc::new(infer_schema(&records));
+    
+    let batch_size = options.batch_size;
+    let batch_schema = schema.clone();
+    let batches = (0..records.len().div_ceil(batch_size)).map(move |index| {
+        let chunk = &records[index * batch_size..records.len().min((index + 1) * batch_size)];
+        let arrays: Vec<ArrayRef> = batch_schema.fields().iter()
+            .map(|field| build_column(chunk, field))
+            .collect();
+        Ok(RecordBatch::try_new(batch_schema.clone(), arrays)?)
+    });
+    Ok((schema, Box::new(batches)))
+}
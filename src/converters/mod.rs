@@ -1,5 +1,17 @@
 pub mod excel;
 pub mod csv;
+pub mod parquet;
+pub mod json;
+pub mod ipc;
 pub mod common;
+pub mod columns;
+pub mod filter;
+pub mod infer;
+pub mod pipeline;
+pub mod reader;
+pub mod writer;
 pub mod datagen;
-pub mod diff; 
\ No newline at end of file
+pub mod generator;
+pub mod fake;
+pub mod diff;
+pub mod schema; 
\ No newline at end of file
@@ -0,0 +1,100 @@
+use crate::cli::OutputFormat;
+use crate::error::Result;
+use crate::utils;
+use super::common::ConvertOptions;
+use super::reader::RecordBatches;
+use super::writer::open_writer;
+use std::path::Path;
+use std::fs::File;
+use log::{info, warn, debug};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Instant;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatchReader;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+/// 转换Parquet文件到其他格式
+pub fn convert_parquet(
+    input_path: &Path,
+    output_path: &Path,
+    format: &OutputFormat,
+    options: &ConvertOptions,
+) -> Result<()> {
+    let start_time = Instant::now();
+    let batch_size = options.batch_size;
+    
+    // 检查输入文件扩展名
+    if let Ok(ext) = utils::get_file_extension(input_path) {
+        if ext != "parquet" {
+            warn!("输入文件扩展名不是.parquet: {}", ext);
+        }
+    }
+    
+    info!("开始处理Parquet文件: {}", input_path.display());
+    
+    // 打开Parquet文件，元数据中已经包含总行数和行组信息
+    let file = File::open(input_path)?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    
+    let metadata = builder.metadata().clone();
+    let total_rows = metadata.file_metadata().num_rows().max(0) as usize;
+    info!("Parquet文件共有{}行数据，{}个行组", total_rows, metadata.num_row_groups());
+    debug!("Parquet schema: {:?}", builder.schema());
+    
+    if total_rows == 0 {
+        warn!("Parquet文件没有数据行，只输出列结构");
+    }
+    
+    // 创建进度条
+    let pb = ProgressBar::new(total_rows as u64);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
+        .unwrap()
+        .progress_chars("#>-"));
+    
+    // 计算处理批次
+    let batch_count = total_rows.div_ceil(batch_size);
+    info!("将数据分为{}个批次处理，每批次{}行", batch_count, batch_size);
+    
+    // 按批次流式读取行组
+    let reader = builder
+        .with_batch_size(batch_size)
+        .build()?;
+    
+    // 在写出任何数据之前检查过滤条件
+    options.validate_filter(&reader.schema())?;
+    
+    // 打开输出文件，所有批次写入同一个写入器
+    let mut writer = open_writer(output_path, format, reader.schema(), &options.writer_options())?;
+    
+    let mut processed_rows = 0;
+    
+    for batch in reader {
+        let record_batch = batch?;
+        processed_rows += record_batch.num_rows();
+        
+        // 写入满足过滤条件的行
+        writer.write(&options.filter_batch(record_batch)?)?;
+        
+        pb.set_position(processed_rows as u64);
+    }
+    
+    writer.close()?;
+    pb.finish_with_message("Parquet文件转换完成");
+    
+    let elapsed = start_time.elapsed();
+    info!("总处理时间: {:.2}秒", elapsed.as_secs_f64());
+    
+    Ok(())
+}
+
+/// 按批次逐个读取Parquet文件的数据
+pub fn read_parquet(input_path: &Path, options: &ConvertOptions) -> Result<(SchemaRef, RecordBatches)> {
+    let file = File::open(input_path)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?
+        .with_batch_size(options.batch_size)
+        .build()?;
+    
+    let schema = reader.schema();
+    Ok((schema, Box::new(reader.map(|batch| Ok(batch?)))))
+}
@@ -0,0 +1,169 @@
+use crate::error::{Result, TransmutaError};
+use log::debug;
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+
+/// 每个工作线程允许同时处理或等待写入的数据块数，用于限制内存占用
+const CHUNKS_PER_THREAD: usize = 2;
+
+/// 并行转换流水线
+///
+/// 一个读取线程按顺序产生原始数据块，线程池并行把数据块解析为RecordBatch等结果，
+/// 写入端在当前线程按数据块的原始顺序依次写出，因此输出与单线程处理完全相同。
+/// 任一环节出错时停止读取并返回第一个错误。
+pub fn run_pipeline<C, T, I, P, W>(chunks: I, thread_count: usize, parse: P, mut write: W) -> Result<()>
+where
+    C: Send,
+    T: Send,
+    I: Iterator<Item = Result<C>> + Send,
+    P: Fn(C) -> Result<T> + Sync,
+    W: FnMut(T) -> Result<()>,
+{
+    let thread_count = thread_count.max(1);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(thread_count)
+        .build()
+        .map_err(|e| TransmutaError::DataProcessingError(format!("创建线程池失败: {}", e)))?;
+    let max_in_flight = thread_count * CHUNKS_PER_THREAD;
+    debug!("并行处理：{}个线程，最多{}个数据块同时处理", thread_count, max_in_flight);
+
+    std::thread::scope(|threads| {
+        // 读取线程
+        let (chunk_tx, chunk_rx) = mpsc::sync_channel::<Result<C>>(max_in_flight);
+        threads.spawn(move || {
+            for chunk in chunks {
+                let failed = chunk.is_err();
+                // 写入端已停止或读取出错时结束
+                if chunk_tx.send(chunk).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        pool.in_place_scope(|scope| {
+            let parse = &parse;
+            let (result_tx, result_rx) = mpsc::channel::<(usize, Result<T>)>();
+            // 已解析但尚未轮到写入的批次
+            let mut pending: BTreeMap<usize, Result<T>> = BTreeMap::new();
+            let mut submitted = 0;
+            let mut written = 0;
+            let mut reader_done = false;
+
+            loop {
+                if !reader_done && submitted - written < max_in_flight {
+                    // 提交下一个数据块
+                    match chunk_rx.recv() {
+                        Ok(chunk) => {
+                            let chunk = chunk?;
+                            let seq = submitted;
+                            let result_tx = result_tx.clone();
+                            scope.spawn(move |_| {
+                                // 写入端出错返回后接收端已关闭，结果直接丢弃
+                                let _ = result_tx.send((seq, parse(chunk)));
+                            });
+                            submitted += 1;
+                        }
+                        Err(_) => reader_done = true,
+                    }
+
+                    while let Ok((seq, batch)) = result_rx.try_recv() {
+                        pending.insert(seq, batch);
+                    }
+                } else if written == submitted {
+                    break;
+                } else {
+                    // 等待至少一个批次完成
+                    let (seq, batch) = result_rx.recv()
+                        .map_err(|_| TransmutaError::DataProcessingError("并行处理线程意外退出".to_string()))?;
+                    pending.insert(seq, batch);
+                }
+
+                // 按顺序写出已完成的批次
+                while let Some(batch) = pending.remove(&written) {
+                    write(batch?)?;
+                    written += 1;
+                }
+            }
+
+            Ok(())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// 解析时前面的数据块耗时更长，使完成顺序与原始顺序相反
+    fn slow_parse(chunk: usize) -> Result<usize> {
+        std::thread::sleep(Duration::from_millis((20 - chunk % 20) as u64));
+        Ok(chunk * 10)
+    }
+
+    fn run(chunk_count: usize, thread_count: usize) -> Vec<usize> {
+        let mut output = Vec::new();
+        run_pipeline((0..chunk_count).map(Ok), thread_count, slow_parse, |value| {
+            output.push(value);
+            Ok(())
+        }).unwrap();
+        output
+    }
+
+    fn error(result: Result<()>) -> String {
+        match result {
+            Ok(()) => panic!("应当报错"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn writes_in_input_order_regardless_of_thread_count() {
+        let expected: Vec<usize> = (0..40).map(|chunk| chunk * 10).collect();
+        assert_eq!(run(40, 1), expected);
+        assert_eq!(run(40, 8), expected);
+        assert_eq!(run(0, 4), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn parse_error_stops_reading() {
+        // 数据块无限多，出错后必须停止读取才能返回
+        let read = AtomicUsize::new(0);
+        let chunks = (0..).map(|chunk| {
+            read.fetch_add(1, Ordering::SeqCst);
+            Ok(chunk)
+        });
+        let mut written = Vec::new();
+        let result = run_pipeline(chunks, 4, |chunk: usize| {
+            if chunk == 5 {
+                Err(TransmutaError::DataProcessingError("第5块解析失败".to_string()))
+            } else {
+                Ok(chunk)
+            }
+        }, |chunk| {
+            written.push(chunk);
+            Ok(())
+        });
+        assert!(error(result).contains("第5块解析失败"));
+        assert_eq!(written, vec![0, 1, 2, 3, 4]);
+        // 已写出的5块，加上最多max_in_flight块正在处理、同样多的块在通道中等待和读取线程手中的1块
+        let max_in_flight = 4 * CHUNKS_PER_THREAD;
+        assert!(read.load(Ordering::SeqCst) <= 5 + 2 * max_in_flight + 1);
+    }
+
+    #[test]
+    fn read_and_write_errors_are_returned() {
+        let chunks = (0..10).map(|chunk| match chunk {
+            3 => Err(TransmutaError::DataProcessingError("读取失败".to_string())),
+            _ => Ok(chunk),
+        });
+        assert!(error(run_pipeline(chunks, 2, Ok, |_| Ok(()))).contains("读取失败"));
+
+        let result = run_pipeline((0..100).map(Ok), 2, Ok, |chunk: usize| match chunk {
+            7 => Err(TransmutaError::DataProcessingError("写入失败".to_string())),
+            _ => Ok(()),
+        });
+        assert!(error(result).contains("写入失败"));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::error::{Result, TransmutaError};
+use crate::utils;
+use super::common::ConvertOptions;
+use std::io::BufRead;
+use std::path::Path;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use log::debug;
+
+/// 可以读取的输入文件格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat {
+    Csv,
+    Excel,
+    Parquet,
+    Json,
+    Arrow,
+}
+
+impl std::fmt::Display for InputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputFormat::Csv => write!(f, "csv"),
+            InputFormat::Excel => write!(f, "excel"),
+            InputFormat::Parquet => write!(f, "parquet"),
+            InputFormat::Json => write!(f, "json"),
+            InputFormat::Arrow => write!(f, "arrow"),
+        }
+    }
+}
+
+impl InputFormat {
+    /// 根据文件扩展名判断输入格式，标准输入按内容开头识别
+    pub fn from_path(path: &Path) -> Result<Self> {
+        if utils::is_stdio(path) {
+            return Self::detect_stdin();
+        }
+        
+        let ext = utils::get_file_extension(path)?;
+        match ext.as_str() {
+            "csv" | "tsv" | "txt" => Ok(InputFormat::Csv),
+            "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" => Ok(InputFormat::Excel),
+            "parquet" => Ok(InputFormat::Parquet),
+            "json" | "jsonl" | "ndjson" => Ok(InputFormat::Json),
+            "arrow" | "arrows" | "feather" | "ipc" => Ok(InputFormat::Arrow),
+            _ => Err(TransmutaError::FileFormatError(format!(
+                "无法识别输入文件格式: {}", path.display()
+            ))),
+        }
+    }
+
+    /// 查看标准输入开头的内容（不消耗数据）识别格式：Excel、Parquet和Arrow IPC按文件头，
+    /// 以[或{开头的按JSON，其余按CSV
+    fn detect_stdin() -> Result<Self> {
+        const PARQUET_MAGIC: &[u8] = b"PAR1";
+        const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+        const CFB_MAGIC: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+        // Arrow IPC流以消息的连续标记开头
+        const ARROW_STREAM_MAGIC: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF];
+
+        let stdin = std::io::stdin();
+        let mut stdin = stdin.lock();
+        let head = stdin.fill_buf()?;
+
+        let format = if head.starts_with(PARQUET_MAGIC) {
+            return Err(TransmutaError::FileFormatError(
+                "Parquet需要随机读取，不支持从标准输入读取".to_string()
+            ));
+        } else if head.starts_with(ZIP_MAGIC) || head.starts_with(CFB_MAGIC) {
+            InputFormat::Excel
+        } else if head.starts_with(super::ipc::ARROW_FILE_MAGIC) || head.starts_with(ARROW_STREAM_MAGIC) {
+            InputFormat::Arrow
+        } else {
+            match head.iter().find(|b| !b.is_ascii_whitespace()) {
+                Some(b'[') | Some(b'{') => InputFormat::Json,
+                _ => InputFormat::Csv,
+            }
+        };
+
+        debug!("标准输入识别为{}格式", format);
+        Ok(format)
+    }
+}
+
+/// 按批次逐个读取的数据
+pub type RecordBatches = Box<dyn Iterator<Item = Result<RecordBatch>>>;
+
+/// 按批次逐个读取任意支持格式的输入文件，格式由扩展名决定，路径为"-"时读取标准输入
+///
+/// CSV按有标题行读取，Excel读取第一个工作表。CSV、Parquet和Arrow IPC边读取边返回批次，
+/// Excel和JSON的格式需要先把整个文件载入内存。
+pub fn stream_batches(input_path: &Path, options: &ConvertOptions) -> Result<(SchemaRef, RecordBatches)> {
+    let format = InputFormat::from_path(input_path)?;
+    debug!("按{}格式读取文件: {}", format, input_path.display());
+
+    let (schema, batches) = match format {
+        InputFormat::Csv => super::csv::read_csv(input_path, true, options)?,
+        InputFormat::Excel => super::excel::read_excel(input_path, options)?,
+        InputFormat::Parquet => super::parquet::read_parquet(input_path, options)?,
+        InputFormat::Json => super::json::read_json(input_path, options)?,
+        InputFormat::Arrow => super::ipc::read_ipc(input_path)?,
+    };
+
+    // 只保留满足--where条件的行
+    options.validate_filter(&schema)?;
+    let filter = options.filter.clone();
+    let batches = batches.map(move |batch| match &filter {
+        Some(filter) => filter.apply(&batch?),
+        None => batch,
+    });
+    Ok((schema, Box::new(batches)))
+}
+
+/// 读取任意支持格式的输入文件的全部数据，见stream_batches
+pub fn read_batches(input_path: &Path, options: &ConvertOptions) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    let (schema, batches) = stream_batches(input_path, options)?;
+    Ok((schema, batches.collect::<Result<Vec<RecordBatch>>>()?))
+}
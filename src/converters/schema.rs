@@ -0,0 +1,367 @@
+use crate::error::Result;
+use crate::utils;
+use super::common::{array_value_to_json, array_value_to_string, ConvertOptions};
+use super::datagen::{ColumnDefinition, DataType, LengthSpec};
+use super::infer::TypedColumnBuilder;
+use super::reader::stream_batches;
+use arrow::array::{make_comparator, Array, ArrayRef};
+use arrow::compute::SortOptions;
+use arrow::datatypes::DataType as ArrowType;
+use log::{info, warn};
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+/// 推断列定义的选项
+#[derive(Debug, Clone)]
+pub struct InferSchemaOptions {
+    /// CSV分隔符
+    pub delimiter: char,
+    /// CSV和Excel用于推断列类型的采样行数
+    pub infer_rows: usize,
+    /// 不同值不超过该数量的列输出为enum，为0时不输出enum
+    pub max_enum: usize,
+}
+
+/// 每个值至少出现这么多次的列才输出为enum，避免把只出现一两次的真实值（姓名、邮箱等）原样写入列定义
+const MIN_ENUM_VALUE_COUNT: usize = 5;
+
+/// 每列最多记录的不同值数量，超过后不再记录，不同值数量只是下限，并假定该列的值不重复
+const MAX_TRACKED_DISTINCT: usize = 100_000;
+
+/// 推断出的列定义，附带只供参考、data-gen不使用的统计信息
+#[derive(Debug, Serialize)]
+struct InferredColumn {
+    #[serde(flatten)]
+    definition: ColumnDefinition,
+    /// 非空值中不同值的数量，不同值过多时为下限
+    distinct_count: usize,
+}
+
+/// 可以按正则以外的规则识别的字符串内容
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextKind {
+    Uuid,
+    Email,
+    Ipv4,
+    Ipv6,
+    Url,
+}
+
+impl TextKind {
+    const ALL: [TextKind; 5] = [TextKind::Uuid, TextKind::Email, TextKind::Ipv4, TextKind::Ipv6, TextKind::Url];
+
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            TextKind::Uuid => value.len() == 36 && value.char_indices().all(|(i, c)| match i {
+                8 | 13 | 18 | 23 => c == '-',
+                _ => c.is_ascii_hexdigit(),
+            }),
+            TextKind::Email => value.split_once('@').is_some_and(|(user, domain)| {
+                !user.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.')
+                    && !value.contains(char::is_whitespace)
+            }),
+            TextKind::Ipv4 => value.parse::<Ipv4Addr>().is_ok(),
+            TextKind::Ipv6 => value.parse::<Ipv6Addr>().is_ok(),
+            TextKind::Url => (value.starts_with("http://") || value.starts_with("https://"))
+                && !value.contains(char::is_whitespace),
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            TextKind::Uuid => DataType::Uuid,
+            TextKind::Email => DataType::Email,
+            TextKind::Ipv4 => DataType::Ipv4,
+            TextKind::Ipv6 => DataType::Ipv6,
+            TextKind::Url => DataType::Url,
+        }
+    }
+}
+
+/// 单列在所有批次上累计的统计信息
+struct ColumnStats {
+    rows: usize,
+    nulls: usize,
+    /// 不同值不超过max_enum时每个值的出现次数和JSON形式，键为文本形式；超过后不能输出enum，不再保存
+    counts: Option<HashMap<String, (usize, Value)>>,
+    /// 输出enum的最多不同值数
+    max_enum: usize,
+    /// 不同值的文本形式，用于统计不同值的数量，最多记录MAX_TRACKED_DISTINCT个
+    distinct: HashSet<String>,
+    /// 不同值超过MAX_TRACKED_DISTINCT个后不再记录
+    distinct_capped: bool,
+    /// 最小值和最大值，保存为只含一个元素的数组
+    min: Option<ArrayRef>,
+    max: Option<ArrayRef>,
+    /// 字符串的字符数或二进制数据的字节数范围
+    length: Option<(usize, usize)>,
+    /// 所有非空字符串都符合的内容规则
+    text_kinds: Vec<TextKind>,
+}
+
+impl ColumnStats {
+    fn new(max_enum: usize) -> Self {
+        ColumnStats {
+            rows: 0,
+            nulls: 0,
+            counts: (max_enum > 0).then(HashMap::new),
+            max_enum,
+            distinct: HashSet::new(),
+            distinct_capped: false,
+            min: None,
+            max: None,
+            length: None,
+            text_kinds: TextKind::ALL.to_vec(),
+        }
+    }
+
+    fn update(&mut self, array: &ArrayRef) -> Result<()> {
+        self.rows += array.len();
+        self.nulls += array.null_count();
+
+        let is_text = matches!(array.data_type(), ArrowType::Utf8 | ArrowType::LargeUtf8 | ArrowType::Utf8View);
+        let is_binary = matches!(
+            array.data_type(),
+            ArrowType::Binary | ArrowType::LargeBinary | ArrowType::BinaryView | ArrowType::FixedSizeBinary(_)
+        );
+
+        for index in (0..array.len()).filter(|&index| array.is_valid(index)) {
+            let text = array_value_to_string(array, index);
+
+            let length = match (is_text, is_binary) {
+                (true, _) => Some(text.chars().count()),
+                // 二进制数据的文本形式为十六进制
+                (_, true) => Some(text.len() / 2),
+                _ => None,
+            };
+            if let Some(length) = length {
+                let (min, max) = self.length.get_or_insert((length, length));
+                *min = (*min).min(length);
+                *max = (*max).max(length);
+            }
+            if is_text {
+                self.text_kinds.retain(|kind| kind.matches(&text));
+            }
+
+            if let Some(counts) = self.counts.as_mut() {
+                if let Some((count, _)) = counts.get_mut(&text) {
+                    *count += 1;
+                } else if counts.len() < self.max_enum {
+                    counts.insert(text.clone(), (1, array_value_to_json(array, index)));
+                } else {
+                    self.counts = None;
+                }
+            }
+            if self.distinct.len() < MAX_TRACKED_DISTINCT {
+                self.distinct.insert(text);
+            } else if !self.distinct.contains(&text) {
+                self.distinct_capped = true;
+            }
+        }
+
+        if is_orderable(array.data_type()) {
+            self.update_min_max(array)?;
+        }
+        Ok(())
+    }
+
+    /// 用比较器找出本批次的最小值和最大值，再与之前的结果比较
+    fn update_min_max(&mut self, array: &ArrayRef) -> Result<()> {
+        let compare = make_comparator(array.as_ref(), array.as_ref(), SortOptions::default())?;
+        let mut valid = (0..array.len()).filter(|&index| array.is_valid(index));
+        let Some(first) = valid.next() else {
+            return Ok(());
+        };
+        let (mut min, mut max) = (first, first);
+        for index in valid {
+            if compare(index, min) == Ordering::Less {
+                min = index;
+            }
+            if compare(index, max) == Ordering::Greater {
+                max = index;
+            }
+        }
+
+        let min = array.slice(min, 1);
+        let max = array.slice(max, 1);
+        self.min = Some(match self.min.take() {
+            Some(current) if make_comparator(current.as_ref(), min.as_ref(), SortOptions::default())?(0, 0) != Ordering::Greater => current,
+            _ => min,
+        });
+        self.max = Some(match self.max.take() {
+            Some(current) if make_comparator(current.as_ref(), max.as_ref(), SortOptions::default())?(0, 0) != Ordering::Less => current,
+            _ => max,
+        });
+        Ok(())
+    }
+
+    /// 生成data-gen的列定义
+    fn into_column(self, name: &str, arrow_type: &ArrowType) -> InferredColumn {
+        let (data_type, precision, scale, width) = data_gen_type(name, arrow_type);
+        let mut definition = ColumnDefinition {
+            precision,
+            scale,
+            length: width.map(LengthSpec::Exact),
+            ..ColumnDefinition::new(name.to_string(), data_type)
+        };
+
+        let non_null = self.rows - self.nulls;
+        let distinct_count = self.distinct.len();
+        if self.nulls > 0 {
+            definition.null_ratio = Some(self.nulls as f64 / self.rows as f64);
+        }
+
+        // 只有每个值都重复出现时才输出enum，否则按类型、长度和取值范围生成
+        let enumerable = distinct_count > 0
+            && self.counts.as_ref().is_some_and(|counts| counts.values().all(|(count, _)| *count >= MIN_ENUM_VALUE_COUNT))
+            && !matches!(definition.data_type, DataType::Null)
+            && TypedColumnBuilder::supports(&definition.arrow_type());
+        if let Some(counts) = self.counts.filter(|_| enumerable) {
+            // 按出现次数从多到少排列，次数作为权重
+            let mut values: Vec<(usize, Value)> = counts.into_values().collect();
+            values.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.to_string().cmp(&b.1.to_string())));
+            definition.weights = Some(values.iter().map(|(count, _)| *count as f64).collect());
+            definition.values = Some(values.into_iter().map(|(_, value)| value).collect());
+            return InferredColumn { definition, distinct_count };
+        }
+
+        // 所有值都不相同时生成不重复的值，布尔和浮点数的重复没有意义
+        if self.distinct_capped {
+            warn!("列'{}'的不同值超过{}个，不再统计，按不重复的值处理", name, MAX_TRACKED_DISTINCT);
+        }
+        definition.unique = non_null > 1
+            && (self.distinct_capped || distinct_count == non_null)
+            && !matches!(
+                definition.data_type,
+                DataType::Boolean | DataType::Float | DataType::Float32 | DataType::Float64 | DataType::Null
+            );
+
+        match definition.data_type {
+            DataType::String => {
+                match self.text_kinds.first() {
+                    // 所有值都符合同一种内容规则时使用对应的语义化类型
+                    Some(kind) if non_null > 0 => definition.data_type = kind.data_type(),
+                    _ => definition.length = self.length.map(|(min, max)| LengthSpec::Range { min, max }),
+                }
+            }
+            DataType::Binary => {
+                definition.length = self.length.map(|(min, max)| LengthSpec::Range { min, max });
+            }
+            // 不重复的列按采样的取值范围生成时，行数超过采样的不同值数就无法生成，因此不输出范围
+            _ if definition.unique => {}
+            _ => {
+                definition.min = self.min.map(|min| array_value_to_json(&min, 0)).filter(is_finite);
+                definition.max = self.max.map(|max| array_value_to_json(&max, 0)).filter(is_finite);
+                // 只有一端有效时两端都不输出，避免范围与默认值不一致
+                if definition.min.is_none() || definition.max.is_none() {
+                    definition.min = None;
+                    definition.max = None;
+                }
+            }
+        }
+
+        InferredColumn { definition, distinct_count }
+    }
+}
+
+/// 数值、高精度小数、日期和时间类型可以统计最小值和最大值
+fn is_orderable(data_type: &ArrowType) -> bool {
+    data_type.is_integer()
+        || data_type.is_floating()
+        || matches!(
+            data_type,
+            ArrowType::Decimal128(_, _) | ArrowType::Decimal256(_, _)
+                | ArrowType::Date32 | ArrowType::Date64
+                | ArrowType::Timestamp(_, _) | ArrowType::Time32(_) | ArrowType::Time64(_)
+                | ArrowType::Duration(_)
+        )
+}
+
+/// 浮点数的NaN和无穷大不能作为取值范围
+fn is_finite(value: &Value) -> bool {
+    match value {
+        Value::Number(n) => n.as_f64().is_some_and(f64::is_finite),
+        Value::Null => false,
+        _ => true,
+    }
+}
+
+/// Arrow类型对应的data-gen类型，以及decimal的precision、scale和fixedsizebinary的字节数
+fn data_gen_type(name: &str, arrow_type: &ArrowType) -> (DataType, Option<u8>, Option<i8>, Option<usize>) {
+    let data_type = match arrow_type {
+        ArrowType::Boolean => DataType::Boolean,
+        ArrowType::Int8 => DataType::Int8,
+        ArrowType::Int16 => DataType::Int16,
+        ArrowType::Int32 => DataType::Int32,
+        ArrowType::Int64 => DataType::Int64,
+        ArrowType::UInt8 => DataType::UInt8,
+        ArrowType::UInt16 => DataType::UInt16,
+        ArrowType::UInt32 => DataType::UInt32,
+        ArrowType::UInt64 => DataType::UInt64,
+        ArrowType::Float16 | ArrowType::Float32 => DataType::Float32,
+        ArrowType::Float64 => DataType::Float64,
+        ArrowType::Decimal128(precision, scale) => return (DataType::Decimal128, Some(*precision), Some(*scale), None),
+        ArrowType::Decimal256(precision, scale) => return (DataType::Decimal256, Some(*precision), Some(*scale), None),
+        ArrowType::Date32 | ArrowType::Date64 => DataType::Date,
+        ArrowType::Timestamp(_, _) => DataType::Timestamp,
+        ArrowType::Time32(_) => DataType::Time32,
+        ArrowType::Time64(_) => DataType::Time64,
+        ArrowType::Duration(_) => DataType::Duration,
+        ArrowType::Interval(_) => DataType::Interval,
+        ArrowType::Binary | ArrowType::LargeBinary | ArrowType::BinaryView => DataType::Binary,
+        ArrowType::FixedSizeBinary(width) => return (DataType::FixedSizeBinary, None, None, Some(*width as usize)),
+        ArrowType::Null => DataType::Null,
+        ArrowType::Utf8 | ArrowType::LargeUtf8 | ArrowType::Utf8View => DataType::String,
+        other => {
+            warn!("列'{}'的类型{}没有对应的data-gen类型，按字符串处理", name, other);
+            DataType::String
+        }
+    };
+    (data_type, None, None, None)
+}
+
+/// 读取数据文件，推断每列的data-gen列定义并以JSON格式写入输出文件
+///
+/// 列定义包含类型、取值范围、空值比例、字符串长度等统计信息；不同值较少的列输出为带权重的enum，
+/// 其中包含输入中的真实值，max_enum为0时不输出任何真实值。
+pub fn infer_schema(input_path: &Path, output_path: &Path, options: &InferSchemaOptions) -> Result<()> {
+    let start_time = std::time::Instant::now();
+
+    let convert_options = ConvertOptions {
+        delimiter: options.delimiter,
+        infer_rows: Some(options.infer_rows),
+        ..Default::default()
+    };
+    let (schema, batches) = stream_batches(input_path, &convert_options)?;
+
+    // 逐个批次累计统计信息，不保留读取过的数据
+    let mut stats: Vec<ColumnStats> = schema.fields().iter().map(|_| ColumnStats::new(options.max_enum)).collect();
+    let mut rows = 0;
+    for batch in batches {
+        let batch = batch?;
+        for (column_stats, array) in stats.iter_mut().zip(batch.columns()) {
+            column_stats.update(array)?;
+        }
+        rows += batch.num_rows();
+    }
+    info!("统计了{}行、{}列数据", rows, schema.fields().len());
+
+    let columns: Vec<InferredColumn> = stats.into_iter()
+        .zip(schema.fields())
+        .map(|(column_stats, field)| column_stats.into_column(field.name(), field.data_type()))
+        .collect();
+
+    let mut writer = utils::create_buf_writer(output_path)?;
+    serde_json::to_writer_pretty(&mut writer, &columns)?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    info!("列定义已写入: {}", output_path.display());
+    info!("总处理时间: {:.2}秒", start_time.elapsed().as_secs_f64());
+    Ok(())
+}
@@ -0,0 +1,1163 @@
+use crate::cli::{self, IpcCompression, JsonStyle, OutputFormat, ParquetWriterVersion, StatisticsLevel};
+use crate::error::{Result, TransmutaError};
+use crate::utils;
+use super::common::{array_value_to_json, array_value_to_string};
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use arrow::array::*;
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::ipc::writer::{FileWriter, IpcWriteOptions, StreamWriter};
+use arrow::ipc::CompressionType;
+use arrow::record_batch::RecordBatch;
+use parquet::basic::Compression;
+use parquet::file::properties::{EnabledStatistics, WriterProperties, WriterVersion};
+use parquet::arrow::ArrowWriter;
+use parquet::schema::types::ColumnPath;
+use rust_xlsxwriter::{Format, Workbook};
+use clap::ValueEnum;
+use log::{info, debug};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// 单个Parquet列的写入选项，未指定的项使用文件级别的设置
+#[derive(Debug, Clone, Default)]
+pub struct ParquetColumnOptions {
+    /// 压缩算法
+    pub compression: Option<Compression>,
+    /// 是否启用字典编码
+    pub dictionary: Option<bool>,
+    /// 统计信息级别
+    pub statistics: Option<EnabledStatistics>,
+}
+
+/// Parquet输出的写入选项，未指定的项使用parquet库的默认值
+#[derive(Debug, Clone, Default)]
+pub struct ParquetWriteOptions {
+    /// 压缩算法
+    pub compression: Option<Compression>,
+    /// 每个行组的最大行数，为None时每个批次写为一个行组
+    pub row_group_size: Option<usize>,
+    /// 是否启用字典编码
+    pub dictionary: Option<bool>,
+    /// 统计信息级别
+    pub statistics: Option<EnabledStatistics>,
+    /// 数据页的大致最大字节数
+    pub data_page_size: Option<usize>,
+    /// 文件格式版本
+    pub writer_version: Option<WriterVersion>,
+    /// 按列名指定的写入选项，嵌套列用.分隔路径
+    pub columns: BTreeMap<String, ParquetColumnOptions>,
+}
+
+/// Parquet写入配置文件中的单列设置
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParquetColumnConfig {
+    compression: Option<String>,
+    dictionary: Option<bool>,
+    statistics: Option<String>,
+}
+
+/// Parquet写入配置文件，字段与命令行选项相同
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParquetConfigFile {
+    compression: Option<String>,
+    row_group_size: Option<usize>,
+    dictionary: Option<bool>,
+    statistics: Option<String>,
+    data_page_size: Option<String>,
+    writer_version: Option<String>,
+    #[serde(default)]
+    columns: BTreeMap<String, ParquetColumnConfig>,
+}
+
+/// 解析配置文件中的统计信息级别
+fn parse_statistics(s: &str) -> std::result::Result<EnabledStatistics, String> {
+    StatisticsLevel::from_str(s, true)
+        .map(EnabledStatistics::from)
+        .map_err(|_| format!("无效的统计信息级别: {}，可选none、chunk、page", s))
+}
+
+impl ParquetWriteOptions {
+    /// 从JSON配置文件读取写入选项，例如：
+    ///
+    /// ```json
+    /// {
+    ///   "compression": "zstd:9",
+    ///   "row_group_size": 100000,
+    ///   "columns": {
+    ///     "id": { "compression": "snappy", "dictionary": false },
+    ///     "payload": { "statistics": "none" }
+    ///   }
+    /// }
+    /// ```
+    pub fn from_config_file(path: &Path) -> Result<Self> {
+        let config: ParquetConfigFile = serde_json::from_reader(utils::create_buf_reader(path)?)?;
+        let invalid = |e: String| TransmutaError::InvalidArgument(
+            format!("Parquet配置文件{}有误: {}", path.display(), e)
+        );
+        
+        let mut columns = BTreeMap::new();
+        for (name, column) in config.columns {
+            columns.insert(name, ParquetColumnOptions {
+                compression: column.compression.as_deref().map(cli::parse_compression).transpose().map_err(invalid)?,
+                dictionary: column.dictionary,
+                statistics: column.statistics.as_deref().map(parse_statistics).transpose().map_err(invalid)?,
+            });
+        }
+        
+        Ok(ParquetWriteOptions {
+            compression: config.compression.as_deref().map(cli::parse_compression).transpose().map_err(invalid)?,
+            row_group_size: config.row_group_size,
+            dictionary: config.dictionary,
+            statistics: config.statistics.as_deref().map(parse_statistics).transpose().map_err(invalid)?,
+            data_page_size: config.data_page_size.as_deref().map(cli::parse_size).transpose().map_err(invalid)?
+                .map(|size| size as usize),
+            writer_version: config.writer_version.as_deref()
+                .map(|s| ParquetWriterVersion::from_str(s, true)
+                    .map(WriterVersion::from)
+                    .map_err(|_| format!("无效的Parquet版本: {}，可选1.0、2.0", s)))
+                .transpose().map_err(invalid)?,
+            columns,
+        })
+    }
+    
+    /// 生成parquet库的写入属性
+    fn to_writer_properties(&self) -> Result<WriterProperties> {
+        if self.row_group_size == Some(0) {
+            return Err(TransmutaError::InvalidArgument("行组大小必须大于0".to_string()));
+        }
+        
+        let mut builder = WriterProperties::builder();
+        if let Some(compression) = self.compression {
+            builder = builder.set_compression(compression);
+        }
+        if let Some(row_group_size) = self.row_group_size {
+            builder = builder.set_max_row_group_size(row_group_size);
+        }
+        if let Some(dictionary) = self.dictionary {
+            builder = builder.set_dictionary_enabled(dictionary);
+        }
+        if let Some(statistics) = self.statistics {
+            builder = builder.set_statistics_enabled(statistics);
+        }
+        if let Some(data_page_size) = self.data_page_size {
+            builder = builder.set_data_page_size_limit(data_page_size);
+        }
+        if let Some(writer_version) = self.writer_version {
+            builder = builder.set_writer_version(writer_version);
+        }
+        
+        for (name, column) in &self.columns {
+            let path = ColumnPath::new(name.split('.').map(str::to_string).collect());
+            if let Some(compression) = column.compression {
+                builder = builder.set_column_compression(path.clone(), compression);
+            }
+            if let Some(dictionary) = column.dictionary {
+                builder = builder.set_column_dictionary_enabled(path.clone(), dictionary);
+            }
+            if let Some(statistics) = column.statistics {
+                builder = builder.set_column_statistics_enabled(path, statistics);
+            }
+        }
+        
+        Ok(builder.build())
+    }
+}
+
+/// 输出文件的写入选项
+#[derive(Debug, Clone)]
+pub struct WriterOptions {
+    /// CSV分隔符
+    pub delimiter: char,
+    /// 每个分片文件的最大行数，为None时不按行数分片
+    pub split_rows: Option<usize>,
+    /// 每个分片文件的大致最大字节数，为None时不按大小分片
+    pub split_size: Option<u64>,
+    /// JSON数组输出的排版方式
+    pub json_style: JsonStyle,
+    /// Arrow IPC输出的数据压缩方式
+    pub ipc_compression: IpcCompression,
+    /// 输出为Excel时每个批次写入一个工作表
+    pub sheet_per_batch: bool,
+    /// Parquet输出的写入选项
+    pub parquet: ParquetWriteOptions,
+}
+
+impl WriterOptions {
+    /// 只指定分隔符、不分片的写入选项
+    pub fn new(delimiter: char) -> Self {
+        WriterOptions {
+            delimiter,
+            split_rows: None,
+            split_size: None,
+            json_style: JsonStyle::default(),
+            ipc_compression: IpcCompression::default(),
+            sheet_per_batch: false,
+            parquet: ParquetWriteOptions::default(),
+        }
+    }
+}
+
+/// 流式写入器：打开一次，写入多个RecordBatch，最后关闭
+pub trait BatchWriter {
+    /// 写入一个批次
+    fn write(&mut self, batch: &RecordBatch) -> Result<()>;
+
+    /// 已写入输出的字节数（可能不包含尚在缓冲区中的数据）
+    fn bytes_written(&self) -> u64;
+
+    /// 完成写入并关闭文件
+    fn close(self: Box<Self>) -> Result<()>;
+}
+
+/// 统计写入字节数的包装器
+struct CountingWriter<W: Write> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        CountingWriter { inner, count: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// CSV格式的流式写入器，标题行在打开时写入
+struct CsvBatchWriter {
+    writer: csv::Writer<CountingWriter<Box<dyn Write + Send>>>,
+}
+
+impl CsvBatchWriter {
+    fn try_new(file: Box<dyn Write + Send>, schema: &SchemaRef, delimiter: char) -> Result<Self> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(delimiter as u8)
+            .from_writer(CountingWriter::new(file));
+
+        // 写入标题行
+        let header: Vec<&String> = schema.fields().iter()
+            .map(|f| f.name())
+            .collect();
+        writer.write_record(&header)?;
+
+        Ok(CsvBatchWriter { writer })
+    }
+}
+
+impl BatchWriter for CsvBatchWriter {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let mut record = Vec::with_capacity(batch.num_columns());
+
+        for row_idx in 0..batch.num_rows() {
+            record.clear();
+            for column in batch.columns() {
+                record.push(array_value_to_string(column, row_idx));
+            }
+            self.writer.write_record(&record)?;
+        }
+
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.writer.get_ref().count
+    }
+
+    fn close(mut self: Box<Self>) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// 把一行数据转换为JSON对象
+fn row_to_json(batch: &RecordBatch, row_idx: usize) -> Value {
+    let mut row_obj = serde_json::Map::new();
+    for (field, column) in batch.schema_ref().fields().iter().zip(batch.columns()) {
+        row_obj.insert(field.name().clone(), array_value_to_json(column, row_idx));
+    }
+    Value::Object(row_obj)
+}
+
+/// JSON格式的流式写入器，输出对象数组
+///
+/// pretty格式与serde_json::to_writer_pretty相同，compact格式与serde_json::to_writer相同。
+struct JsonBatchWriter {
+    writer: CountingWriter<Box<dyn Write + Send>>,
+    style: JsonStyle,
+    records_written: usize,
+}
+
+impl JsonBatchWriter {
+    fn new(file: Box<dyn Write + Send>, style: JsonStyle) -> Self {
+        JsonBatchWriter {
+            writer: CountingWriter::new(file),
+            style,
+            records_written: 0,
+        }
+    }
+}
+
+impl BatchWriter for JsonBatchWriter {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        for row_idx in 0..batch.num_rows() {
+            let row = row_to_json(batch, row_idx);
+
+            match self.style {
+                JsonStyle::Pretty => {
+                    // 数组元素缩进两个空格
+                    let text = serde_json::to_string_pretty(&row)?;
+                    let separator = if self.records_written == 0 { "[\n" } else { ",\n" };
+                    self.writer.write_all(separator.as_bytes())?;
+                    for (line_idx, line) in text.lines().enumerate() {
+                        if line_idx > 0 {
+                            self.writer.write_all(b"\n")?;
+                        }
+                        self.writer.write_all(b"  ")?;
+                        self.writer.write_all(line.as_bytes())?;
+                    }
+                }
+                JsonStyle::Compact => {
+                    let separator = if self.records_written == 0 { b"[" } else { b"," };
+                    self.writer.write_all(separator)?;
+                    serde_json::to_writer(&mut self.writer, &row)?;
+                }
+            }
+
+            self.records_written += 1;
+        }
+
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.writer.count
+    }
+
+    fn close(mut self: Box<Self>) -> Result<()> {
+        let closing = match (self.records_written, self.style) {
+            (0, _) => "[]",
+            (_, JsonStyle::Pretty) => "\n]",
+            (_, JsonStyle::Compact) => "]",
+        };
+        self.writer.write_all(closing.as_bytes())?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// NDJSON格式的流式写入器，每行一个紧凑的JSON对象
+struct NdjsonBatchWriter {
+    writer: CountingWriter<Box<dyn Write + Send>>,
+}
+
+impl BatchWriter for NdjsonBatchWriter {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        for row_idx in 0..batch.num_rows() {
+            serde_json::to_writer(&mut self.writer, &row_to_json(batch, row_idx))?;
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.writer.count
+    }
+
+    fn close(mut self: Box<Self>) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Parquet不支持持续时间类型，写入时转换为对应时间单位的Int64
+fn parquet_compatible_type(data_type: &DataType) -> DataType {
+    match data_type {
+        DataType::Duration(_) => DataType::Int64,
+        other => other.clone(),
+    }
+}
+
+/// Parquet格式的流式写入器，未指定行组大小时每个批次写为一个行组
+struct ParquetBatchWriter {
+    writer: ArrowWriter<Box<dyn Write + Send>>,
+    schema: SchemaRef,
+    needs_cast: bool,
+    flush_per_batch: bool,
+}
+
+impl ParquetBatchWriter {
+    fn try_new(file: Box<dyn Write + Send>, schema: &SchemaRef, options: &ParquetWriteOptions) -> Result<Self> {
+        let props = options.to_writer_properties()?;
+        
+        let needs_cast = schema.fields().iter()
+            .any(|f| parquet_compatible_type(f.data_type()) != *f.data_type());
+        let schema = if needs_cast {
+            Arc::new(Schema::new(
+                schema.fields().iter()
+                    .map(|f| f.as_ref().clone().with_data_type(parquet_compatible_type(f.data_type())))
+                    .collect::<Vec<Field>>()
+            ))
+        } else {
+            schema.clone()
+        };
+        
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
+        Ok(ParquetBatchWriter {
+            writer,
+            schema,
+            needs_cast,
+            flush_per_batch: options.row_group_size.is_none(),
+        })
+    }
+}
+
+impl BatchWriter for ParquetBatchWriter {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        if self.needs_cast {
+            let columns = batch.columns().iter()
+                .zip(self.schema.fields())
+                .map(|(column, field)| cast(column, field.data_type()))
+                .collect::<std::result::Result<Vec<ArrayRef>, _>>()?;
+            self.writer.write(&RecordBatch::try_new(self.schema.clone(), columns)?)?;
+        } else {
+            self.writer.write(batch)?;
+        }
+        // 指定了行组大小时由ArrowWriter按行数切分行组
+        if self.flush_per_batch {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        // 包含尚未写出的行组的估计大小，使按大小分片在大行组时也能生效
+        (self.writer.bytes_written() + self.writer.in_progress_size()) as u64
+    }
+
+    fn close(self: Box<Self>) -> Result<()> {
+        let mut inner = self.writer.into_inner()?;
+        inner.flush()?;
+        Ok(())
+    }
+}
+
+/// Arrow IPC的文件格式或流格式写入器
+enum IpcWriter {
+    File(FileWriter<CountingWriter<Box<dyn Write + Send>>>),
+    Stream(StreamWriter<CountingWriter<Box<dyn Write + Send>>>),
+}
+
+/// Arrow IPC格式的流式写入器，每个批次写为一个IPC记录批次
+struct IpcBatchWriter {
+    writer: IpcWriter,
+}
+
+impl IpcBatchWriter {
+    fn try_new(
+        file: Box<dyn Write + Send>,
+        schema: &SchemaRef,
+        stream: bool,
+        compression: IpcCompression,
+    ) -> Result<Self> {
+        let compression = match compression {
+            IpcCompression::None => None,
+            IpcCompression::Lz4 => Some(CompressionType::LZ4_FRAME),
+            IpcCompression::Zstd => Some(CompressionType::ZSTD),
+        };
+        let options = IpcWriteOptions::default().try_with_compression(compression)?;
+        
+        let file = CountingWriter::new(file);
+        let writer = if stream {
+            IpcWriter::Stream(StreamWriter::try_new_with_options(file, schema, options)?)
+        } else {
+            IpcWriter::File(FileWriter::try_new_with_options(file, schema, options)?)
+        };
+        Ok(IpcBatchWriter { writer })
+    }
+}
+
+impl BatchWriter for IpcBatchWriter {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        match &mut self.writer {
+            IpcWriter::File(writer) => writer.write(batch)?,
+            IpcWriter::Stream(writer) => writer.write(batch)?,
+        }
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        match &self.writer {
+            IpcWriter::File(writer) => writer.get_ref().count,
+            IpcWriter::Stream(writer) => writer.get_ref().count,
+        }
+    }
+
+    fn close(self: Box<Self>) -> Result<()> {
+        // 写入文件尾或流结束标记
+        let mut inner = match self.writer {
+            IpcWriter::File(writer) => writer.into_inner()?,
+            IpcWriter::Stream(writer) => writer.into_inner()?,
+        };
+        inner.flush()?;
+        Ok(())
+    }
+}
+
+/// Excel工作表的最大行数（含标题行）
+const XLSX_MAX_ROWS: u32 = 1_048_576;
+
+/// Excel工作表的最大列数
+const XLSX_MAX_COLUMNS: usize = 16_384;
+
+/// Excel数值只有15位有效数字，超出的整数按文本写入以免丢失精度
+const XLSX_MAX_EXACT_INTEGER: u64 = 999_999_999_999_999;
+
+/// Excel数值最多保留的有效数字位数
+const XLSX_MAX_SIGNIFICANT_DIGITS: usize = 15;
+
+/// 1970-01-01在Excel 1900日期系统中的序列号
+const XLSX_UNIX_EPOCH_SERIAL: f64 = 25569.0;
+
+/// 1900-03-01的序列号，之前的日期受Excel把1900年当作闰年的错误影响，按文本写入
+const XLSX_MIN_DATE_SERIAL: f64 = 61.0;
+
+/// 9999-12-31之后的日期Excel无法表示，按文本写入
+const XLSX_MAX_DATE_SERIAL: f64 = 2958466.0;
+
+/// 写入Excel单元格的值
+enum XlsxCellValue {
+    Blank,
+    Number(f64),
+    Boolean(bool),
+    Date(f64),
+    DateTime(f64),
+    Time(f64),
+    Duration(f64),
+    /// 高精度小数，按列的scale设置数字格式
+    Decimal(f64),
+    Text(String),
+}
+
+/// 每个时间单位的数量
+fn units_per_second(unit: &TimeUnit) -> f64 {
+    match unit {
+        TimeUnit::Second => 1.0,
+        TimeUnit::Millisecond => 1_000.0,
+        TimeUnit::Microsecond => 1_000_000.0,
+        TimeUnit::Nanosecond => 1_000_000_000.0,
+    }
+}
+
+/// 整数在Excel能精确表示时写为数值，否则写为文本
+fn xlsx_integer(value: i128) -> XlsxCellValue {
+    if value.unsigned_abs() <= XLSX_MAX_EXACT_INTEGER as u128 {
+        XlsxCellValue::Number(value as f64)
+    } else {
+        XlsxCellValue::Text(value.to_string())
+    }
+}
+
+/// 高精度小数的有效数字不超过15位时写为数值，否则写为文本以免丢失精度
+fn xlsx_decimal(text: String) -> XlsxCellValue {
+    let digits: String = text.chars().filter(char::is_ascii_digit).collect();
+    let significant = digits.trim_start_matches('0').trim_end_matches('0').len();
+    match text.parse::<f64>() {
+        Ok(value) if significant <= XLSX_MAX_SIGNIFICANT_DIGITS => XlsxCellValue::Decimal(value),
+        _ => XlsxCellValue::Text(text),
+    }
+}
+
+/// 高精度小数列的数字格式，显示scale位小数（Excel最多30位）
+fn xlsx_decimal_format(scale: i8) -> Format {
+    let places = scale.clamp(0, 30) as usize;
+    let num_format = if places == 0 { "0".to_string() } else { format!("0.{}", "0".repeat(places)) };
+    Format::new().set_num_format(num_format)
+}
+
+/// 把Arrow数组中的一个值转换为Excel单元格的值
+///
+/// 数值（包括有效数字不超过15位的高精度小数）、布尔值、日期和时间写为对应类型的单元格，其他类型按文本写入。
+fn xlsx_cell_value(array: &ArrayRef, index: usize) -> XlsxCellValue {
+    if array.is_null(index) {
+        return XlsxCellValue::Blank;
+    }
+    
+    // 日期时间写为序列号，超出Excel日期范围的按文本写入
+    let date_value = |serial: f64, value: XlsxCellValue| {
+        if (XLSX_MIN_DATE_SERIAL..XLSX_MAX_DATE_SERIAL).contains(&serial) {
+            value
+        } else {
+            XlsxCellValue::Text(array_value_to_string(array, index))
+        }
+    };
+    
+    match array.data_type() {
+        DataType::Null => XlsxCellValue::Blank,
+        DataType::Boolean => {
+            let array = array.as_any().downcast_ref::<BooleanArray>().unwrap();
+            XlsxCellValue::Boolean(array.value(index))
+        }
+        DataType::Int8 => {
+            let array = array.as_any().downcast_ref::<Int8Array>().unwrap();
+            XlsxCellValue::Number(array.value(index) as f64)
+        }
+        DataType::Int16 => {
+            let array = array.as_any().downcast_ref::<Int16Array>().unwrap();
+            XlsxCellValue::Number(array.value(index) as f64)
+        }
+        DataType::Int32 => {
+            let array = array.as_any().downcast_ref::<Int32Array>().unwrap();
+            XlsxCellValue::Number(array.value(index) as f64)
+        }
+        DataType::Int64 => {
+            let array = array.as_any().downcast_ref::<Int64Array>().unwrap();
+            xlsx_integer(array.value(index) as i128)
+        }
+        DataType::UInt8 => {
+            let array = array.as_any().downcast_ref::<UInt8Array>().unwrap();
+            XlsxCellValue::Number(array.value(index) as f64)
+        }
+        DataType::UInt16 => {
+            let array = array.as_any().downcast_ref::<UInt16Array>().unwrap();
+            XlsxCellValue::Number(array.value(index) as f64)
+        }
+        DataType::UInt32 => {
+            let array = array.as_any().downcast_ref::<UInt32Array>().unwrap();
+            XlsxCellValue::Number(array.value(index) as f64)
+        }
+        DataType::UInt64 => {
+            let array = array.as_any().downcast_ref::<UInt64Array>().unwrap();
+            xlsx_integer(array.value(index) as i128)
+        }
+        DataType::Float32 | DataType::Float64 => {
+            let value = match array.data_type() {
+                DataType::Float32 => array.as_any().downcast_ref::<Float32Array>().unwrap().value(index) as f64,
+                _ => array.as_any().downcast_ref::<Float64Array>().unwrap().value(index),
+            };
+            // Excel不支持NaN和无穷大
+            if value.is_finite() {
+                XlsxCellValue::Number(value)
+            } else {
+                XlsxCellValue::Text(value.to_string())
+            }
+        }
+        DataType::Date32 => {
+            let array = array.as_any().downcast_ref::<Date32Array>().unwrap();
+            let serial = array.value(index) as f64 + XLSX_UNIX_EPOCH_SERIAL;
+            date_value(serial, XlsxCellValue::Date(serial))
+        }
+        DataType::Date64 => {
+            let array = array.as_any().downcast_ref::<Date64Array>().unwrap();
+            let serial = array.value(index) as f64 / 86_400_000.0 + XLSX_UNIX_EPOCH_SERIAL;
+            date_value(serial, XlsxCellValue::DateTime(serial))
+        }
+        DataType::Timestamp(unit, _) => {
+            let value = match unit {
+                TimeUnit::Second => array.as_any().downcast_ref::<TimestampSecondArray>().unwrap().value(index),
+                TimeUnit::Millisecond => array.as_any().downcast_ref::<TimestampMillisecondArray>().unwrap().value(index),
+                TimeUnit::Microsecond => array.as_any().downcast_ref::<TimestampMicrosecondArray>().unwrap().value(index),
+                TimeUnit::Nanosecond => array.as_any().downcast_ref::<TimestampNanosecondArray>().unwrap().value(index),
+            };
+            let serial = value as f64 / units_per_second(unit) / 86_400.0 + XLSX_UNIX_EPOCH_SERIAL;
+            date_value(serial, XlsxCellValue::DateTime(serial))
+        }
+        DataType::Time32(unit) | DataType::Time64(unit) => {
+            let value = match unit {
+                TimeUnit::Second => array.as_any().downcast_ref::<Time32SecondArray>().unwrap().value(index) as i64,
+                TimeUnit::Millisecond => array.as_any().downcast_ref::<Time32MillisecondArray>().unwrap().value(index) as i64,
+                TimeUnit::Microsecond => array.as_any().downcast_ref::<Time64MicrosecondArray>().unwrap().value(index),
+                TimeUnit::Nanosecond => array.as_any().downcast_ref::<Time64NanosecondArray>().unwrap().value(index),
+            };
+            XlsxCellValue::Time(value as f64 / units_per_second(unit) / 86_400.0)
+        }
+        DataType::Duration(unit) => {
+            let value = match unit {
+                TimeUnit::Second => array.as_any().downcast_ref::<DurationSecondArray>().unwrap().value(index),
+                TimeUnit::Millisecond => array.as_any().downcast_ref::<DurationMillisecondArray>().unwrap().value(index),
+                TimeUnit::Microsecond => array.as_any().downcast_ref::<DurationMicrosecondArray>().unwrap().value(index),
+                TimeUnit::Nanosecond => array.as_any().downcast_ref::<DurationNanosecondArray>().unwrap().value(index),
+            };
+            XlsxCellValue::Duration(value as f64 / units_per_second(unit) / 86_400.0)
+        }
+        DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => xlsx_decimal(array_value_to_string(array, index)),
+        _ => XlsxCellValue::Text(array_value_to_string(array, index)),
+    }
+}
+
+/// Excel格式的写入器
+///
+/// 所有批次写入同一个工作簿，标题行加粗并冻结。工作表写满Excel的1,048,576行上限时
+/// 自动续写到新的工作表，也可以每个批次写入一个工作表。工作表按行流式写入临时文件，
+/// 关闭时才生成工作簿，因此写入过程中无法得知输出大小。
+struct XlsxBatchWriter {
+    file: Box<dyn Write + Send>,
+    workbook: Workbook,
+    schema: SchemaRef,
+    sheet_per_batch: bool,
+    /// 已创建的工作表数
+    sheet_count: usize,
+    /// 当前工作表中已写入的行数（含标题行）
+    sheet_rows: u32,
+    header_format: Format,
+    date_format: Format,
+    datetime_format: Format,
+    time_format: Format,
+    duration_format: Format,
+    /// 每一列的高精度小数格式，其他类型的列为None
+    decimal_formats: Vec<Option<Format>>,
+}
+
+impl XlsxBatchWriter {
+    fn try_new(file: Box<dyn Write + Send>, schema: &SchemaRef, sheet_per_batch: bool) -> Result<Self> {
+        if schema.fields().len() > XLSX_MAX_COLUMNS {
+            return Err(TransmutaError::InvalidArgument(format!(
+                "Excel工作表最多{}列，数据有{}列", XLSX_MAX_COLUMNS, schema.fields().len()
+            )));
+        }
+        
+        Ok(XlsxBatchWriter {
+            file,
+            workbook: Workbook::new(),
+            schema: schema.clone(),
+            sheet_per_batch,
+            sheet_count: 0,
+            sheet_rows: 0,
+            header_format: Format::new().set_bold(),
+            date_format: Format::new().set_num_format("yyyy-mm-dd"),
+            datetime_format: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+            time_format: Format::new().set_num_format("hh:mm:ss"),
+            duration_format: Format::new().set_num_format("[h]:mm:ss"),
+            decimal_formats: schema.fields().iter()
+                .map(|field| match field.data_type() {
+                    DataType::Decimal128(_, scale) | DataType::Decimal256(_, scale) => Some(xlsx_decimal_format(*scale)),
+                    _ => None,
+                })
+                .collect(),
+        })
+    }
+    
+    /// 新建工作表并写入加粗、冻结的标题行
+    fn add_sheet(&mut self) -> Result<()> {
+        let worksheet = self.workbook.add_worksheet_with_constant_memory();
+        for (col, field) in self.schema.fields().iter().enumerate() {
+            worksheet.write_string_with_format(0, col as u16, field.name(), &self.header_format)?;
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+        
+        self.sheet_count += 1;
+        self.sheet_rows = 1;
+        debug!("新建Excel工作表: Sheet{}", self.sheet_count);
+        Ok(())
+    }
+}
+
+impl BatchWriter for XlsxBatchWriter {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        if self.sheet_count == 0 || self.sheet_per_batch {
+            self.add_sheet()?;
+        }
+        
+        for row_idx in 0..batch.num_rows() {
+            if self.sheet_rows == XLSX_MAX_ROWS {
+                self.add_sheet()?;
+            }
+            let row = self.sheet_rows;
+            
+            let worksheet = self.workbook.worksheet_from_index(self.sheet_count - 1)?;
+            
+            for (col, column) in batch.columns().iter().enumerate() {
+                let col = col as u16;
+                match xlsx_cell_value(column, row_idx) {
+                    XlsxCellValue::Blank => {}
+                    XlsxCellValue::Number(value) => { worksheet.write_number(row, col, value)?; }
+                    XlsxCellValue::Boolean(value) => { worksheet.write_boolean(row, col, value)?; }
+                    XlsxCellValue::Date(value) => { worksheet.write_number_with_format(row, col, value, &self.date_format)?; }
+                    XlsxCellValue::DateTime(value) => { worksheet.write_number_with_format(row, col, value, &self.datetime_format)?; }
+                    XlsxCellValue::Time(value) => { worksheet.write_number_with_format(row, col, value, &self.time_format)?; }
+                    XlsxCellValue::Duration(value) => { worksheet.write_number_with_format(row, col, value, &self.duration_format)?; }
+                    XlsxCellValue::Decimal(value) => match &self.decimal_formats[col as usize] {
+                        Some(format) => { worksheet.write_number_with_format(row, col, value, format)?; }
+                        None => { worksheet.write_number(row, col, value)?; }
+                    },
+                    XlsxCellValue::Text(value) => { worksheet.write_string(row, col, value)?; }
+                }
+            }
+            
+            self.sheet_rows += 1;
+        }
+        
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        0
+    }
+
+    fn close(mut self: Box<Self>) -> Result<()> {
+        // 没有任何数据时仍输出只有标题行的工作表
+        if self.sheet_count == 0 {
+            self.add_sheet()?;
+        }
+        
+        let buffer = self.workbook.save_to_buffer()?;
+        self.file.write_all(&buffer)?;
+        self.file.flush()?;
+        
+        if self.sheet_count > 1 {
+            info!("Excel输出共{}个工作表", self.sheet_count);
+        }
+        Ok(())
+    }
+}
+
+/// 打开单个输出文件的写入器
+fn open_file_writer(
+    output_path: &Path,
+    format: &OutputFormat,
+    schema: &SchemaRef,
+    options: &WriterOptions,
+) -> Result<Box<dyn BatchWriter>> {
+    utils::ensure_output_dir(output_path)?;
+    let file = utils::create_buf_writer(output_path)?;
+
+    let writer: Box<dyn BatchWriter> = match format {
+        OutputFormat::Csv => Box::new(CsvBatchWriter::try_new(file, schema, options.delimiter)?),
+        OutputFormat::Json => Box::new(JsonBatchWriter::new(file, options.json_style)),
+        OutputFormat::Ndjson => Box::new(NdjsonBatchWriter { writer: CountingWriter::new(file) }),
+        OutputFormat::Parquet => Box::new(ParquetBatchWriter::try_new(file, schema, &options.parquet)?),
+        OutputFormat::Arrow => Box::new(IpcBatchWriter::try_new(file, schema, false, options.ipc_compression)?),
+        OutputFormat::ArrowStream => Box::new(IpcBatchWriter::try_new(file, schema, true, options.ipc_compression)?),
+        OutputFormat::Xlsx => Box::new(XlsxBatchWriter::try_new(file, schema, options.sheet_per_batch)?),
+    };
+
+    debug!("打开{}格式输出文件: {}", format, output_path.display());
+    Ok(writer)
+}
+
+/// 单文件写入器，关闭时记录输出路径
+struct SingleFileWriter {
+    inner: Box<dyn BatchWriter>,
+    path: PathBuf,
+}
+
+impl BatchWriter for SingleFileWriter {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.inner.write(batch)
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.inner.bytes_written()
+    }
+
+    fn close(self: Box<Self>) -> Result<()> {
+        self.inner.close()?;
+        if utils::is_stdio(&self.path) {
+            info!("数据已写入标准输出");
+        } else {
+            info!("数据已保存到: {}", self.path.display());
+        }
+        Ok(())
+    }
+}
+
+/// 分片写入器，按行数或大小把数据拆分为 name_part0001.ext 等多个文件
+///
+/// 按行数分片是精确的；按大小分片在批次之间检查，单个分片可能超出限制最多一个批次的数据量。
+struct SplitWriter {
+    output_path: PathBuf,
+    format: OutputFormat,
+    schema: SchemaRef,
+    options: WriterOptions,
+    current: Option<Box<dyn BatchWriter>>,
+    current_rows: usize,
+    part_count: usize,
+}
+
+impl SplitWriter {
+    /// 关闭当前分片
+    fn finish_part(&mut self) -> Result<()> {
+        if let Some(writer) = self.current.take() {
+            writer.close()?;
+            info!("数据已保存到: {}", utils::part_file_path(&self.output_path, self.part_count).display());
+        }
+        Ok(())
+    }
+
+    /// 获取当前分片的写入器，需要时打开新分片
+    fn current_writer(&mut self) -> Result<&mut Box<dyn BatchWriter>> {
+        if self.current.is_none() {
+            self.part_count += 1;
+            self.current_rows = 0;
+            let part_path = utils::part_file_path(&self.output_path, self.part_count);
+            self.current = Some(open_file_writer(&part_path, &self.format, &self.schema, &self.options)?);
+        }
+        Ok(self.current.as_mut().unwrap())
+    }
+}
+
+impl BatchWriter for SplitWriter {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let mut offset = 0;
+
+        while offset < batch.num_rows() {
+            self.current_writer()?;
+
+            // 当前分片还能写入的行数
+            let remaining = match self.options.split_rows {
+                Some(split_rows) => split_rows - self.current_rows,
+                None => usize::MAX,
+            };
+            let len = remaining.min(batch.num_rows() - offset);
+
+            self.current.as_mut().unwrap().write(&batch.slice(offset, len))?;
+            self.current_rows += len;
+            offset += len;
+
+            let rows_full = self.options.split_rows.is_some_and(|n| self.current_rows >= n);
+            let size_full = self.options.split_size
+                .is_some_and(|n| self.current.as_ref().unwrap().bytes_written() >= n);
+            if rows_full || size_full {
+                self.finish_part()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.current.as_ref().map(|w| w.bytes_written()).unwrap_or(0)
+    }
+
+    fn close(mut self: Box<Self>) -> Result<()> {
+        // 没有任何数据时仍输出一个只包含schema的分片
+        if self.part_count == 0 {
+            self.current_writer()?;
+        }
+        self.finish_part()?;
+        info!("共输出{}个分片文件", self.part_count);
+        Ok(())
+    }
+}
+
+/// 打开输出写入器。未指定分片选项时所有批次写入同一个文件
+pub fn open_writer(
+    output_path: &Path,
+    format: &OutputFormat,
+    schema: SchemaRef,
+    options: &WriterOptions,
+) -> Result<Box<dyn BatchWriter>> {
+    if options.split_rows == Some(0) || options.split_size == Some(0) {
+        return Err(TransmutaError::InvalidArgument("分片的行数和大小必须大于0".to_string()));
+    }
+
+    // Excel工作簿在关闭时才生成，写入过程中无法得知大小
+    if options.split_size.is_some() && matches!(format, OutputFormat::Xlsx) {
+        return Err(TransmutaError::InvalidArgument("输出为Excel时不能按大小拆分，请使用--split-rows".to_string()));
+    }
+
+    if options.split_rows.is_some() || options.split_size.is_some() {
+        if utils::is_stdio(output_path) {
+            return Err(TransmutaError::InvalidArgument("输出到标准输出时不能拆分为多个文件".to_string()));
+        }
+        return Ok(Box::new(SplitWriter {
+            output_path: output_path.to_path_buf(),
+            format: format.clone(),
+            schema,
+            options: options.clone(),
+            current: None,
+            current_rows: 0,
+            part_count: 0,
+        }));
+    }
+
+    let inner = open_file_writer(output_path, format, &schema, options)?;
+    Ok(Box::new(SingleFileWriter {
+        inner,
+        path: output_path.to_path_buf(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// 可以在写入器关闭后读取内容的内存缓冲区
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// id从first开始连续编号、共rows行的批次，name的第二行为空值
+    fn batch(first: i64, rows: usize) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let ids: Vec<i64> = (first..first + rows as i64).collect();
+        let names: Vec<Option<String>> = ids.iter()
+            .enumerate()
+            .map(|(i, id)| (i != 1).then(|| format!("n{}", id)))
+            .collect();
+        RecordBatch::try_new(schema, vec![
+            Arc::new(Int64Array::from(ids)),
+            Arc::new(StringArray::from(names)),
+        ]).unwrap()
+    }
+
+    /// 把批次依次写入内存，返回输出的文本
+    fn write_all(writer: impl FnOnce(Box<dyn Write + Send>) -> Box<dyn BatchWriter>, batches: &[RecordBatch]) -> String {
+        let buffer = SharedBuffer::default();
+        let mut writer = writer(Box::new(buffer.clone()));
+        for batch in batches {
+            writer.write(batch).unwrap();
+        }
+        writer.close().unwrap();
+        buffer.text()
+    }
+
+    /// 测试用的临时目录，每个测试使用不同的名称
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("transmuta-writer-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn csv_writes_header_once_and_nulls_as_empty() {
+        let schema = batch(1, 0).schema();
+        let text = write_all(
+            |file| Box::new(CsvBatchWriter::try_new(file, &schema, ';').unwrap()),
+            &[batch(1, 2), batch(3, 1)],
+        );
+        assert_eq!(text, "id;name\n1;n1\n2;\n3;n3\n");
+    }
+
+    #[test]
+    fn json_styles_match_serde_json() {
+        let batches = [batch(1, 2), batch(3, 1)];
+        let expected: Vec<Value> = (1..=3).map(|id| {
+            let name = if id == 2 { Value::Null } else { Value::from(format!("n{}", id)) };
+            serde_json::json!({"id": id, "name": name})
+        }).collect();
+
+        let pretty = write_all(|file| Box::new(JsonBatchWriter::new(file, JsonStyle::Pretty)), &batches);
+        assert_eq!(pretty, serde_json::to_string_pretty(&expected).unwrap());
+        let compact = write_all(|file| Box::new(JsonBatchWriter::new(file, JsonStyle::Compact)), &batches);
+        assert_eq!(compact, serde_json::to_string(&expected).unwrap());
+        let empty = write_all(|file| Box::new(JsonBatchWriter::new(file, JsonStyle::Pretty)), &[]);
+        assert_eq!(empty, "[]");
+    }
+
+    #[test]
+    fn ndjson_writes_one_object_per_line() {
+        let text = write_all(|file| Box::new(NdjsonBatchWriter { writer: CountingWriter::new(file) }), &[batch(1, 2)]);
+        assert_eq!(text, "{\"id\":1,\"name\":\"n1\"}\n{\"id\":2,\"name\":null}\n");
+    }
+
+    #[test]
+    fn xlsx_integers_beyond_15_digits_are_text() {
+        let array: ArrayRef = Arc::new(Int64Array::from(vec![999_999_999_999_999, -1_000_000_000_000_000]));
+        assert!(matches!(xlsx_cell_value(&array, 0), XlsxCellValue::Number(v) if v == 999_999_999_999_999.0));
+        assert!(matches!(xlsx_cell_value(&array, 1), XlsxCellValue::Text(ref s) if s == "-1000000000000000"));
+    }
+
+    #[test]
+    fn xlsx_decimals_within_15_digits_are_numbers() {
+        let array: ArrayRef = Arc::new(
+            Decimal128Array::from(vec![Some(51660), Some(-5), Some(1_234_567_890_123_456_789), None])
+                .with_precision_and_scale(20, 2)
+                .unwrap(),
+        );
+        assert!(matches!(xlsx_cell_value(&array, 0), XlsxCellValue::Decimal(v) if v == 516.6));
+        assert!(matches!(xlsx_cell_value(&array, 1), XlsxCellValue::Decimal(v) if v == -0.05));
+        assert!(matches!(xlsx_cell_value(&array, 2), XlsxCellValue::Text(ref s) if s == "12345678901234567.89"));
+        assert!(matches!(xlsx_cell_value(&array, 3), XlsxCellValue::Blank));
+        // 末尾的0不计入有效数字
+        assert!(matches!(xlsx_decimal("1000000000000000000.00".to_string()), XlsxCellValue::Decimal(_)));
+    }
+
+    #[test]
+    fn xlsx_dates_outside_excel_range_are_text() {
+        // 1970-01-01、1900-02-28、10000-01-01
+        let array: ArrayRef = Arc::new(Date32Array::from(vec![0, -25509, 2_932_897]));
+        assert!(matches!(xlsx_cell_value(&array, 0), XlsxCellValue::Date(v) if v == XLSX_UNIX_EPOCH_SERIAL));
+        assert!(matches!(xlsx_cell_value(&array, 1), XlsxCellValue::Text(ref s) if s == "1900-02-28"));
+        assert!(matches!(xlsx_cell_value(&array, 2), XlsxCellValue::Text(_)));
+    }
+
+    #[test]
+    fn xlsx_non_finite_floats_are_text() {
+        let array: ArrayRef = Arc::new(Float64Array::from(vec![1.5, f64::NAN, f64::INFINITY]));
+        assert!(matches!(xlsx_cell_value(&array, 0), XlsxCellValue::Number(v) if v == 1.5));
+        assert!(matches!(xlsx_cell_value(&array, 1), XlsxCellValue::Text(ref s) if s == "NaN"));
+        assert!(matches!(xlsx_cell_value(&array, 2), XlsxCellValue::Text(ref s) if s == "inf"));
+    }
+
+    #[test]
+    fn split_rows_is_exact_across_batches() {
+        let dir = temp_dir("split");
+        let output = dir.join("out.csv");
+        let options = WriterOptions { split_rows: Some(2), ..WriterOptions::new(',') };
+        let mut writer = open_writer(&output, &OutputFormat::Csv, batch(1, 0).schema(), &options).unwrap();
+        writer.write(&batch(1, 3)).unwrap();
+        writer.write(&batch(4, 2)).unwrap();
+        writer.close().unwrap();
+
+        let parts: Vec<String> = (1..=3)
+            .map(|part| std::fs::read_to_string(utils::part_file_path(&output, part)).unwrap())
+            .collect();
+        assert_eq!(parts, vec!["id,name\n1,n1\n2,\n", "id,name\n3,n3\n4,n4\n", "id,name\n5,\n"]);
+        assert!(!utils::part_file_path(&output, 4).exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_split_options_are_rejected() {
+        let schema = batch(1, 0).schema();
+        let error = |path: &str, format: OutputFormat, split_rows, split_size| {
+            let options = WriterOptions { split_rows, split_size, ..WriterOptions::new(',') };
+            match open_writer(Path::new(path), &format, schema.clone(), &options) {
+                Ok(_) => panic!("应当报错"),
+                Err(e) => e.to_string(),
+            }
+        };
+        assert!(error("out.csv", OutputFormat::Csv, Some(0), None).contains("必须大于0"));
+        assert!(error("out.xlsx", OutputFormat::Xlsx, None, Some(1024)).contains("--split-rows"));
+        assert!(error("-", OutputFormat::Csv, Some(10), None).contains("标准输出"));
+    }
+}
@@ -8,6 +8,9 @@ pub enum TransmutaError {
     #[error("Excel解析错误: {0}")]
     ExcelError(String),
 
+    #[error("Excel写入错误: {0}")]
+    ExcelWriteError(String),
+
     #[error("CSV解析错误: {0}")]
     CsvError(#[from] csv::Error),
 
@@ -61,4 +64,11 @@ impl From<calamine::XlsxError> for TransmutaError {
     }
 }
 
+// 实现从rust_xlsxwriter错误到我们的错误类型的转换
+impl From<rust_xlsxwriter::XlsxError> for TransmutaError {
+    fn from(err: rust_xlsxwriter::XlsxError) -> Self {
+        TransmutaError::ExcelWriteError(err.to_string())
+    }
+}
+
 pub type Result<T> = std::result::Result<T, TransmutaError>; 
\ No newline at end of file
@@ -5,15 +5,18 @@ mod utils;
 
 use anyhow::Result;
 use clap::Parser;
-use cli::{Cli, Commands, OutputFormat, DiffOutputMode};
+use cli::{Cli, Commands, OutputFormat, DiffOutputMode, ParquetArgs, SchemaCommands, SchemaFormat};
 use log::{error, info, warn};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 // 获取输出格式，优先使用用户指定的格式，否则从文件扩展名推断
 fn get_output_format(format_opt: Option<OutputFormat>, output_path: &Path) -> Result<OutputFormat, String> {
     match format_opt {
         // 用户明确指定了格式
         Some(format) => Ok(format),
+        // 标准输出没有扩展名可供推断
+        None if utils::is_stdio(output_path) => Err("输出到标准输出时必须使用 --format 参数指定格式".to_string()),
         // 尝试从文件扩展名推断格式
         None => {
             cli::guess_format_from_extension(output_path)
@@ -23,6 +26,75 @@ fn get_output_format(format_opt: Option<OutputFormat>, output_path: &Path) -> Re
     }
 }
 
+// 读取--schema指定的列类型定义，未指定格式时按扩展名判断
+fn declared_schema(
+    schema: Option<PathBuf>,
+    schema_format: Option<SchemaFormat>,
+    delimiter: char,
+) -> Result<Option<arrow::datatypes::SchemaRef>> {
+    let Some(path) = schema else {
+        return Ok(None);
+    };
+    let schema_format = schema_format.unwrap_or_else(|| {
+        match utils::get_file_extension(&path).ok().as_deref() {
+            Some("json") => SchemaFormat::Json,
+            _ => SchemaFormat::Csv,
+        }
+    });
+    
+    let column_defs = converters::datagen::read_column_definitions(&path, &schema_format, delimiter)
+        .inspect_err(|e| error!("读取列类型定义失败: {}", e))?;
+    let fields: Vec<arrow::datatypes::Field> = column_defs.iter()
+        .map(|col| arrow::datatypes::Field::new(&col.name, col.arrow_type(), true))
+        .collect();
+    
+    Ok(Some(Arc::new(arrow::datatypes::Schema::new(fields))))
+}
+
+// 解析--where指定的行过滤条件
+fn row_filter(expr: Option<String>) -> Result<Option<converters::filter::Filter>> {
+    let Some(expr) = expr else {
+        return Ok(None);
+    };
+    let filter = converters::filter::Filter::parse(&expr)
+        .inspect_err(|e| error!("解析行过滤条件失败: {}", e))?;
+    info!("行过滤条件: {}", filter);
+    Ok(Some(filter))
+}
+
+// 合并Parquet配置文件和命令行选项，命令行选项优先
+fn parquet_options(args: ParquetArgs) -> Result<converters::writer::ParquetWriteOptions> {
+    let mut options = match &args.parquet_config {
+        Some(path) => converters::writer::ParquetWriteOptions::from_config_file(path)
+            .inspect_err(|e| error!("读取Parquet配置文件失败: {}", e))?,
+        None => converters::writer::ParquetWriteOptions::default(),
+    };
+    
+    if args.compression.is_some() {
+        options.compression = args.compression;
+    }
+    if args.row_group_size.is_some() {
+        options.row_group_size = args.row_group_size;
+    }
+    if args.dictionary {
+        options.dictionary = Some(true);
+    }
+    if args.no_dictionary {
+        options.dictionary = Some(false);
+    }
+    if let Some(statistics) = args.statistics {
+        options.statistics = Some(statistics.into());
+    }
+    if let Some(data_page_size) = args.data_page_size {
+        options.data_page_size = Some(data_page_size as usize);
+    }
+    if let Some(writer_version) = args.writer_version {
+        options.writer_version = Some(writer_version.into());
+    }
+    
+    Ok(options)
+}
+
 fn main() -> Result<()> {
     // 初始化日志
     env_logger::init_from_env(
@@ -33,7 +105,18 @@ fn main() -> Result<()> {
     info!("传变工具 (transmuta) v{}", env!("CARGO_PKG_VERSION"));
 
     match cli.command {
-        Commands::Excel { input, output, format, batch_size, delimiter, threads, skip_rows } => {
+        Commands::Excel { input, output, sheet, all_sheets, list_sheets, format, batch_size, delimiter, threads, skip_rows, json_style, ipc_compression, sheet_per_batch, infer_rows, no_infer, schema, schema_format, on_cast_error, cast_report, split_rows, split_size, filter, columns, parquet } => {
+            // 只列出工作表
+            if list_sheets {
+                if let Err(e) = converters::excel::list_sheets(&input) {
+                    error!("读取Excel工作表失败: {}", e);
+                    return Err(e.into());
+                }
+                return Ok(());
+            }
+            
+            let output = output.expect("clap保证未指定--list-sheets时必须提供--output");
+            
             // 获取输出格式，如果未指定则从文件扩展名推断
             let format = match get_output_format(format, &output) {
                 Ok(f) => f,
@@ -43,20 +126,43 @@ fn main() -> Result<()> {
                 }
             };
             
+            let sheet = match (sheet, all_sheets) {
+                (_, true) => converters::excel::SheetSelection::All,
+                (Some(name), false) => converters::excel::SheetSelection::Named(name),
+                (None, false) => converters::excel::SheetSelection::First,
+            };
+            
+            let options = converters::common::ConvertOptions {
+                batch_size,
+                delimiter,
+                threads,
+                infer_rows: if no_infer { None } else { Some(infer_rows) },
+                split_rows,
+                split_size,
+                json_style,
+                ipc_compression,
+                sheet_per_batch,
+                parquet: parquet_options(parquet)?,
+                schema: declared_schema(schema, schema_format, delimiter)?,
+                on_cast_error,
+                cast_report,
+                columns: columns.into(),
+                filter: row_filter(filter)?,
+            };
+            
             if let Err(e) = converters::excel::convert_excel(
                 &input, 
                 &output, 
                 &format, 
-                batch_size, 
-                delimiter, 
-                threads, 
-                skip_rows
+                skip_rows,
+                &sheet,
+                &options
             ) {
                 error!("转换Excel失败: {}", e);
                 return Err(e.into());
             }
         }
-        Commands::Csv { input, output, format, batch_size, delimiter, threads, has_header } => {
+        Commands::Csv { input, output, format, batch_size, delimiter, threads, has_header, json_style, ipc_compression, sheet_per_batch, infer_rows, no_infer, schema, schema_format, on_cast_error, cast_report, split_rows, split_size, filter, columns, parquet } => {
             // 获取输出格式，如果未指定则从文件扩展名推断
             let format = match get_output_format(format, &output) {
                 Ok(f) => f,
@@ -66,20 +172,69 @@ fn main() -> Result<()> {
                 }
             };
             
+            let options = converters::common::ConvertOptions {
+                batch_size,
+                delimiter,
+                threads,
+                infer_rows: if no_infer { None } else { Some(infer_rows) },
+                split_rows,
+                split_size,
+                json_style,
+                ipc_compression,
+                sheet_per_batch,
+                parquet: parquet_options(parquet)?,
+                schema: declared_schema(schema, schema_format, delimiter)?,
+                on_cast_error,
+                cast_report,
+                columns: columns.into(),
+                filter: row_filter(filter)?,
+            };
+            
             if let Err(e) = converters::csv::convert_csv(
                 &input, 
                 &output, 
                 &format, 
-                batch_size, 
-                delimiter, 
-                threads,
-                has_header
+                has_header,
+                &options
             ) {
                 error!("转换CSV失败: {}", e);
                 return Err(e.into());
             }
         }
-        Commands::DataGen { schema, schema_format, output, format, rows, delimiter, seed } => {
+        Commands::Parquet { input, output, format, batch_size, delimiter, json_style, ipc_compression, sheet_per_batch, split_rows, split_size, filter, parquet } => {
+            // 获取输出格式，如果未指定则从文件扩展名推断
+            let format = match get_output_format(format, &output) {
+                Ok(f) => f,
+                Err(e) => {
+                    error!("{}", e);
+                    return Err(anyhow::anyhow!(e));
+                }
+            };
+            
+            let options = converters::common::ConvertOptions {
+                batch_size,
+                delimiter,
+                split_rows,
+                split_size,
+                json_style,
+                ipc_compression,
+                sheet_per_batch,
+                parquet: parquet_options(parquet)?,
+                filter: row_filter(filter)?,
+                ..Default::default()
+            };
+            
+            if let Err(e) = converters::parquet::convert_parquet(
+                &input,
+                &output,
+                &format,
+                &options
+            ) {
+                error!("转换Parquet失败: {}", e);
+                return Err(e.into());
+            }
+        }
+        Commands::Json { input, output, format, batch_size, delimiter, json_style, ipc_compression, sheet_per_batch, split_rows, split_size, filter, parquet } => {
             // 获取输出格式，如果未指定则从文件扩展名推断
             let format = match get_output_format(format, &output) {
                 Ok(f) => f,
@@ -89,20 +244,130 @@ fn main() -> Result<()> {
                 }
             };
             
+            let options = converters::common::ConvertOptions {
+                batch_size,
+                delimiter,
+                split_rows,
+                split_size,
+                json_style,
+                ipc_compression,
+                sheet_per_batch,
+                parquet: parquet_options(parquet)?,
+                filter: row_filter(filter)?,
+                ..Default::default()
+            };
+            
+            if let Err(e) = converters::json::convert_json(
+                &input,
+                &output,
+                &format,
+                &options
+            ) {
+                error!("转换JSON失败: {}", e);
+                return Err(e.into());
+            }
+        }
+        Commands::Arrow { input, output, format, delimiter, json_style, ipc_compression, sheet_per_batch, split_rows, split_size, filter, parquet } => {
+            // 获取输出格式，如果未指定则从文件扩展名推断
+            let format = match get_output_format(format, &output) {
+                Ok(f) => f,
+                Err(e) => {
+                    error!("{}", e);
+                    return Err(anyhow::anyhow!(e));
+                }
+            };
+            
+            // Arrow IPC按输入的批次写出，不使用batch_size
+            let options = converters::common::ConvertOptions {
+                delimiter,
+                split_rows,
+                split_size,
+                json_style,
+                ipc_compression,
+                sheet_per_batch,
+                parquet: parquet_options(parquet)?,
+                filter: row_filter(filter)?,
+                ..Default::default()
+            };
+            
+            if let Err(e) = converters::ipc::convert_ipc(
+                &input,
+                &output,
+                &format,
+                &options,
+            ) {
+                error!("转换Arrow IPC失败: {}", e);
+                return Err(e.into());
+            }
+        }
+        Commands::DataGen { schema, schema_format, output, format, rows, batch_size, threads, delimiter, json_style, ipc_compression, sheet_per_batch, seed, locale, parquet } => {
+            // 获取输出格式，如果未指定则从文件扩展名推断
+            let format = match get_output_format(format, &output) {
+                Ok(f) => f,
+                Err(e) => {
+                    error!("{}", e);
+                    return Err(anyhow::anyhow!(e));
+                }
+            };
+            
+            let writer_options = converters::writer::WriterOptions {
+                json_style,
+                ipc_compression,
+                sheet_per_batch,
+                parquet: parquet_options(parquet)?,
+                ..converters::writer::WriterOptions::new(delimiter)
+            };
+            
             if let Err(e) = converters::datagen::generate_data(
                 &schema,
                 &schema_format,
                 &output,
                 &format,
-                rows,
-                delimiter,
-                seed
+                &converters::datagen::GenerateOptions { rows, batch_size, threads, seed, locale },
+                &writer_options,
             ) {
                 error!("生成随机数据失败: {}", e);
                 return Err(e.into());
             }
         }
-        Commands::Diff { input1, input2, output, mode, delimiter, report, ignore_case, ignore_whitespace, one_field_per_line } => {
+        Commands::Diff { input1, input2, output, mode, delimiter, report, ignore_case, ignore_whitespace, one_field_per_line, key_columns, ignore_columns, tolerance, format } => {
+            // 标准输入只能读取一次
+            if utils::is_stdio(&input1) && utils::is_stdio(&input2) {
+                error!("只能有一个输入文件使用标准输入");
+                return Err(anyhow::anyhow!("只能有一个输入文件使用标准输入"));
+            }
+            
+            // 指定了键列时按记录比较
+            if !key_columns.is_empty() {
+                let format = match get_output_format(format, &output) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        error!("{}", e);
+                        return Err(anyhow::anyhow!(e));
+                    }
+                };
+                
+                if let Err(e) = converters::diff::diff_records(
+                    &input1,
+                    &input2,
+                    &output,
+                    converters::diff::RecordDiffOptions {
+                        key_columns,
+                        ignore_columns,
+                        tolerance,
+                        ignore_case,
+                        ignore_whitespace,
+                        delimiter,
+                        format,
+                        report_path: report.as_deref(),
+                    }
+                ) {
+                    error!("比较记录差异失败: {}", e);
+                    return Err(e);
+                }
+                return Ok(());
+            }
+            
             if let Err(e) = converters::diff::diff_fields(
                 &input1,
                 &input2,
@@ -129,6 +394,18 @@ fn main() -> Result<()> {
                 return Err(e.into());
             }
         }
+        Commands::Schema { command } => match command {
+            SchemaCommands::Infer { input, output, delimiter, infer_rows, max_enum } => {
+                if let Err(e) = converters::schema::infer_schema(
+                    &input,
+                    &output,
+                    &converters::schema::InferSchemaOptions { delimiter, infer_rows, max_enum },
+                ) {
+                    error!("推断列定义失败: {}", e);
+                    return Err(e.into());
+                }
+            }
+        },
     }
 
     Ok(())
@@ -1,7 +1,7 @@
 use crate::error::{Result, TransmutaError};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Read, Write};
 use log::{info, debug};
 
 /// 获取文件扩展名
@@ -12,18 +12,85 @@ pub fn get_file_extension(path: &Path) -> Result<String> {
     }
 }
 
-/// 创建缓冲读取器
-pub fn create_buf_reader(path: &Path) -> Result<BufReader<File>> {
+/// 表示标准输入或标准输出的路径
+pub const STDIO_PATH: &str = "-";
+
+/// 路径是否表示标准输入或标准输出
+pub fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == STDIO_PATH
+}
+
+/// 创建缓冲读取器，路径为"-"时读取标准输入
+pub fn create_buf_reader(path: &Path) -> Result<Box<dyn BufRead + Send>> {
+    if is_stdio(path) {
+        return Ok(Box::new(BufReader::new(std::io::stdin())));
+    }
+    
     let file = File::open(path)
         .map_err(|e| TransmutaError::IoError(e))?;
-    Ok(BufReader::new(file))
+    Ok(Box::new(BufReader::new(file)))
 }
 
-/// 创建缓冲写入器
-pub fn create_buf_writer(path: &Path) -> Result<BufWriter<File>> {
+/// 获取输入文件的字节数，标准输入无法预知大小，返回None
+pub fn input_size(path: &Path) -> Option<u64> {
+    if is_stdio(path) {
+        return None;
+    }
+    std::fs::metadata(path).ok().map(|metadata| metadata.len())
+}
+
+/// 创建缓冲写入器，路径为"-"时写入标准输出
+pub fn create_buf_writer(path: &Path) -> Result<Box<dyn Write + Send>> {
+    if is_stdio(path) {
+        return Ok(Box::new(BufWriter::new(std::io::stdout())));
+    }
+    
     let file = File::create(path)
         .map_err(|e| TransmutaError::IoError(e))?;
-    Ok(BufWriter::new(file))
+    Ok(Box::new(BufWriter::new(file)))
+}
+
+/// 把标准输入的全部内容读入内存，用于需要随机访问的格式
+pub fn read_stdin_to_memory() -> Result<Cursor<Vec<u8>>> {
+    let mut data = Vec::new();
+    std::io::stdin().read_to_end(&mut data)?;
+    debug!("从标准输入读取了{}字节", data.len());
+    Ok(Cursor::new(data))
+}
+
+/// 在文件名（扩展名之前）追加后缀，例如 data.csv + "sheet1" -> data_sheet1.csv
+pub fn suffixed_file_path(path: &Path, suffix: &str) -> PathBuf {
+    match path.file_stem() {
+        Some(file_name) => {
+            let mut new_file_name = file_name.to_string_lossy().to_string();
+            new_file_name.push('_');
+            new_file_name.push_str(suffix);
+            
+            if let Some(ext) = path.extension() {
+                new_file_name.push('.');
+                new_file_name.push_str(&ext.to_string_lossy());
+            }
+            
+            path.with_file_name(new_file_name)
+        }
+        None => path.to_path_buf(),
+    }
+}
+
+/// 为分批输出生成带序号的文件路径，例如 data.csv -> data_part0001.csv
+pub fn part_file_path(path: &Path, part: usize) -> PathBuf {
+    suffixed_file_path(path, &format!("part{:04}", part))
+}
+
+/// 将任意名称转换为可以用作文件名的形式，替换路径分隔符等不允许的字符
+pub fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
 }
 
 /// 获取处理数据时使用的线程数
@@ -58,6 +125,9 @@ pub fn estimate_time_remaining(elapsed_secs: f64, progress_percent: f64) -> Opti
 
 /// 创建输出目录（如果不存在）
 pub fn ensure_output_dir(path: &Path) -> Result<()> {
+    if is_stdio(path) {
+        return Ok(());
+    }
     if let Some(parent) = path.parent() {
         if !parent.exists() {
             debug!("创建输出目录: {:?}", parent);